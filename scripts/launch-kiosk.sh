@@ -1,8 +1,7 @@
 #!/bin/bash
 
 # syaOS Kiosk Mode Launcher
-# Launches syaOS in full-screen kiosk mode using Chromium browser
+# Launches the syaOS desktop app in kiosk mode (see src-tauri/README.md).
+# Extra arguments are passed through, e.g. `--profile lobby` or `--remote`.
 
-
-# Launch Chromium in kiosk mode with proper flags for Raspberry Pi
-chromium-browser --noerrdialogs --disable-infobars --disable-pinch --overscroll-history-navigation=0 --kiosk https://sya-os.vercel.app
\ No newline at end of file
+exec syaos --kiosk "$@"
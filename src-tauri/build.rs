@@ -1,4 +1,48 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
 fn main() {
+    println!("cargo:rustc-env=SYAOS_BUILD_COMMIT={}", build_commit());
+    println!("cargo:rerun-if-env-changed=SYAOS_BUILD_COMMIT");
+    rerun_on_commit();
     tauri_build::build()
 }
 
+/// Commit reported by `get_shell_info`; CI can pin it via `SYAOS_BUILD_COMMIT`.
+fn build_commit() -> String {
+    if let Ok(commit) = env::var("SYAOS_BUILD_COMMIT") {
+        return commit;
+    }
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "dev".to_string())
+}
+
+/// Rebuilds when HEAD moves: on checkout (`.git/HEAD`) and on commit (the
+/// branch it points at, loose or packed).
+fn rerun_on_commit() {
+    let git_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../.git");
+    let head = git_dir.join("HEAD");
+    if !head.exists() {
+        return;
+    }
+    println!("cargo:rerun-if-changed={}", head.display());
+    let Ok(head) = fs::read_to_string(&head) else {
+        return;
+    };
+    if let Some(branch) = head.trim().strip_prefix("ref: ") {
+        // A path that doesn't exist would rerun every build
+        for path in [git_dir.join(branch), git_dir.join("packed-refs")] {
+            if path.exists() {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+    }
+}
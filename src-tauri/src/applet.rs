@@ -0,0 +1,566 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::atomic;
+use crate::file_open::Opened;
+
+/// Largest applet accepted, after decompression.
+pub const MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Extensions `applet_import` offers; `importAppletFile` takes the same.
+pub const EXTENSIONS: &[&str] = &["app", "gz", "html", "htm", "json"];
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Window sizes outside this range are dropped on import.
+const WINDOW_SIZE: RangeInclusive<u64> = 100..=8192;
+
+/// Longest icon kept on import; emoji are a few chars, paths and URLs more.
+const MAX_ICON_LEN: usize = 256;
+
+/// An applet and the metadata syaOS keeps for it in `/Applets`. Serialized
+/// in the key order `exportAppletAsApp` writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Applet {
+    /// File name in `/Applets`, always ending in `.app`.
+    pub name: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_width: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_height: Option<u64>,
+    /// Milliseconds since the epoch, like the rest of the file store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<u64>,
+}
+
+/// How an applet is packaged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// The applet as JSON, gzipped (`.app`).
+    App,
+    /// The page with its metadata in leading `<!-- key: value -->` comments.
+    Html,
+}
+
+/// A decoded package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+    pub applet: Applet,
+    pub format: Format,
+    pub compressed: bool,
+    /// Metadata that was dropped as invalid.
+    pub warnings: Vec<String>,
+}
+
+/// Decodes a package (`file_name` stands in for a missing name) and
+/// validates it.
+pub fn decode(bytes: &[u8], file_name: &str) -> Result<Package, String> {
+    let compressed = bytes.starts_with(&GZIP_MAGIC);
+    let bytes = if compressed {
+        let mut decoded = Vec::new();
+        GzDecoder::new(bytes)
+            .take(MAX_SIZE + 1)
+            .read_to_end(&mut decoded)
+            .map_err(|err| format!("the package is not valid gzip: {err}"))?;
+        decoded
+    } else {
+        bytes.to_vec()
+    };
+    if bytes.len() as u64 > MAX_SIZE {
+        return Err(format!(
+            "the applet is larger than {} MiB",
+            MAX_SIZE / 1024 / 1024
+        ));
+    }
+    let text = String::from_utf8(bytes).map_err(|_| "the applet is not UTF-8 text")?;
+
+    let mut warnings = Vec::new();
+    let (mut applet, format) = match from_json(&text, &mut warnings) {
+        Some(applet) => (applet, Format::App),
+        None => (from_html(&text, &mut warnings), Format::Html),
+    };
+    if applet.name.trim().is_empty() {
+        applet.name = file_name.to_string();
+    }
+    normalize_name(&mut applet);
+    validate(&mut applet, &mut warnings)?;
+
+    Ok(Package {
+        applet,
+        format,
+        compressed,
+        warnings,
+    })
+}
+
+/// Reads the package at `path`.
+pub fn read(path: &Path) -> Result<Package, String> {
+    let size = fs::metadata(path)
+        .map_err(|err| format!("{}: {err}", path.display()))?
+        .len();
+    if size > MAX_SIZE {
+        return Err(format!("{} is too large to be an applet", path.display()));
+    }
+    let bytes = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    decode(&bytes, &name)
+}
+
+/// Packages `applet` as `format`.
+pub fn encode(applet: &Applet, format: Format) -> Result<Vec<u8>, String> {
+    if applet.content.trim().is_empty() {
+        return Err("the applet has no content".to_string());
+    }
+    match format {
+        Format::App => {
+            let json = serde_json::to_vec_pretty(applet).map_err(|err| err.to_string())?;
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&json).map_err(|err| err.to_string())?;
+            encoder.finish().map_err(|err| err.to_string())
+        }
+        Format::Html => Ok(to_html(applet).into_bytes()),
+    }
+}
+
+/// The name an export is offered under: `📦 Name.app` (with the applet's
+/// emoji when it has one) or `Name.html`, as the web exports do.
+pub fn export_file_name(applet: &Applet, format: Format) -> String {
+    let base = strip_suffix_ignore_case(&applet.name, ".app")
+        .or_else(|| strip_suffix_ignore_case(&applet.name, ".html"))
+        .unwrap_or(&applet.name);
+    let base = if base.is_empty() { "Untitled" } else { base };
+    match format {
+        Format::App => {
+            let emoji = applet
+                .icon
+                .as_deref()
+                .filter(|icon| is_emoji_icon(icon))
+                .unwrap_or("📦");
+            format!("{emoji} {base}.app")
+        }
+        Format::Html => format!("{base}.html"),
+    }
+}
+
+/// A summary of `package` for `syaos applet <file>`.
+pub fn describe(package: &Package) -> String {
+    let applet = &package.applet;
+    let format = match (package.format, package.compressed) {
+        (Format::App, true) => "app (gzipped JSON)",
+        (Format::App, false) => "app (JSON)",
+        (Format::Html, true) => "html (gzipped)",
+        (Format::Html, false) => "html",
+    };
+    let mut lines = vec![
+        format!("name:      {}", applet.name),
+        format!("format:    {format}"),
+        format!("content:   {} bytes of HTML", applet.content.len()),
+    ];
+    let optional = [
+        ("icon", applet.icon.clone()),
+        ("shareId", applet.share_id.clone()),
+        ("createdBy", applet.created_by.clone()),
+        (
+            "window",
+            applet
+                .window_width
+                .zip(applet.window_height)
+                .map(|(width, height)| format!("{width}x{height}")),
+        ),
+        ("createdAt", applet.created_at.map(format_time)),
+        ("modifiedAt", applet.modified_at.map(format_time)),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            lines.push(format!("{:<11}{value}", format!("{key}:")));
+        }
+    }
+    for warning in &package.warnings {
+        lines.push(format!("warning:   {warning}"));
+    }
+    lines.join("\n")
+}
+
+fn format_time(millis: u64) -> String {
+    i64::try_from(millis)
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| millis.to_string())
+}
+
+/// The JSON package `exportAppletAsApp` writes, if `text` is one.
+fn from_json(text: &str, warnings: &mut Vec<String>) -> Option<Applet> {
+    let Ok(Value::Object(object)) = serde_json::from_str::<Value>(text) else {
+        return None;
+    };
+    let content = object.get("content")?.as_str().filter(|s| !s.is_empty())?;
+    let string = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+    let mut number = |key: &str| match object.get(key) {
+        None | Some(Value::Null) => None,
+        Some(value) => value.as_u64().or_else(|| {
+            warnings.push(format!("ignored {key} `{value}`: not a whole number"));
+            None
+        }),
+    };
+
+    Some(Applet {
+        name: string("name").unwrap_or_default(),
+        content: content.to_string(),
+        icon: string("icon"),
+        share_id: string("shareId"),
+        created_by: string("createdBy"),
+        window_width: number("windowWidth"),
+        window_height: number("windowHeight"),
+        created_at: number("createdAt"),
+        modified_at: number("modifiedAt"),
+    })
+}
+
+/// A `<!-- key: value -->` line, as `extractMetadataFromHtml` matches it.
+fn metadata_comment(line: &str) -> Option<(&str, &str)> {
+    let inner = line.strip_prefix("<!--")?.strip_suffix("-->")?;
+    let (key, value) = inner.split_once(':')?;
+    let (key, value) = (key.trim(), value.trim());
+    (!key.is_empty() && !value.is_empty()).then_some((key, value))
+}
+
+/// The page with its leading metadata comments taken off, mirroring
+/// `extractMetadataFromHtml`.
+fn from_html(text: &str, warnings: &mut Vec<String>) -> Applet {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut metadata = Map::new();
+    let mut content_start = lines.len();
+    for (index, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if let Some((key, value)) = metadata_comment(line) {
+            metadata.insert(key.to_string(), Value::String(value.to_string()));
+        } else if !line.is_empty() {
+            content_start = index;
+            break;
+        }
+    }
+
+    let mut number = |key: &str| {
+        let value = metadata.get(key)?.as_str()?;
+        value.parse::<u64>().ok().or_else(|| {
+            warnings.push(format!("ignored {key} `{value}`: not a whole number"));
+            None
+        })
+    };
+    let window_width = number("windowWidth");
+    let window_height = number("windowHeight");
+    let created_at = number("createdAt");
+    let modified_at = number("modifiedAt");
+    let string = |key: &str| {
+        metadata
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    Applet {
+        name: string("name").unwrap_or_default(),
+        content: lines[content_start..].join("\n"),
+        icon: string("icon"),
+        share_id: string("shareId"),
+        created_by: string("createdBy"),
+        window_width,
+        window_height,
+        created_at,
+        modified_at,
+    }
+}
+
+/// Prepends `applet`'s metadata as comments, like `injectMetadataIntoHtml`.
+fn to_html(applet: &Applet) -> String {
+    let name = (!applet.name.is_empty()).then(|| applet.name.clone());
+    let fields = [
+        ("shareId", applet.share_id.clone()),
+        ("name", name),
+        ("icon", applet.icon.clone()),
+        ("createdBy", applet.created_by.clone()),
+        ("windowWidth", applet.window_width.map(|n| n.to_string())),
+        ("windowHeight", applet.window_height.map(|n| n.to_string())),
+        ("createdAt", applet.created_at.map(|n| n.to_string())),
+        ("modifiedAt", applet.modified_at.map(|n| n.to_string())),
+    ];
+    let mut html = String::new();
+    for (key, value) in fields {
+        // A value that ends the comment would leak into the page
+        if let Some(value) = value.filter(|value| !value.contains("-->")) {
+            html.push_str(&format!("<!-- {key}: {value} -->\n"));
+        }
+    }
+    html.push_str(&applet.content);
+    html
+}
+
+/// Whether `c` is in the ranges `extractEmojiIcon` treats as emoji.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1F02F
+            | 0x1F0A0..=0x1F0FF
+            | 0x1F100..=0x1F64F
+            | 0x1F300..=0x1F9FF
+            | 0x1FA00..=0x1FAFF
+            | 0x2300..=0x23FF
+            | 0x2600..=0x27BF
+            | 0x2B50
+            | 0x2B55
+            | 0x3030
+            | 0x303D
+            | 0x3297
+            | 0x3299
+    )
+}
+
+/// Splits a leading emoji (and the whitespace after it) off `name`.
+fn split_emoji(name: &str) -> (Option<&str>, &str) {
+    let end = name
+        .char_indices()
+        .find(|(_, c)| !is_emoji(*c))
+        .map_or(name.len(), |(index, _)| index);
+    if end == 0 {
+        (None, name)
+    } else {
+        (Some(&name[..end]), name[end..].trim_start())
+    }
+}
+
+/// The check `exportAppletAsApp` uses before putting an icon in the name.
+fn is_emoji_icon(icon: &str) -> bool {
+    !icon.starts_with('/') && !icon.starts_with("http") && icon.encode_utf16().count() <= 10
+}
+
+fn strip_suffix_ignore_case<'a>(name: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = name.len().checked_sub(suffix.len())?;
+    (name.is_char_boundary(split) && name[split..].eq_ignore_ascii_case(suffix))
+        .then(|| &name[..split])
+}
+
+/// Moves an emoji prefix into the icon and gives the name an `.app`
+/// extension, as `importAppletFile` does.
+fn normalize_name(applet: &mut Applet) {
+    let (emoji, rest) = split_emoji(applet.name.trim());
+    if applet.icon.is_none() {
+        applet.icon = emoji.map(str::to_string);
+    }
+    let base = ["html", "htm", "json", "gz", "app"]
+        .iter()
+        .find_map(|ext| strip_suffix_ignore_case(rest, &format!(".{ext}")))
+        .unwrap_or(rest);
+    let base = if base.is_empty() { "Untitled" } else { base };
+    // Names become paths under /Applets
+    applet.name = format!("{}.app", base.replace('/', "-"));
+}
+
+fn validate(applet: &mut Applet, warnings: &mut Vec<String>) -> Result<(), String> {
+    if applet.content.trim().is_empty() {
+        return Err("the applet has no content".to_string());
+    }
+    if !applet.content.contains('<') {
+        return Err("the applet content is not HTML".to_string());
+    }
+    for (key, size) in [
+        ("windowWidth", &mut applet.window_width),
+        ("windowHeight", &mut applet.window_height),
+    ] {
+        if let Some(value) = size.filter(|value| !WINDOW_SIZE.contains(value)) {
+            warnings.push(format!(
+                "ignored {key} {value}: outside {}..={}",
+                WINDOW_SIZE.start(),
+                WINDOW_SIZE.end()
+            ));
+            *size = None;
+        }
+    }
+    if let Some(icon) = applet.icon.take() {
+        if icon.len() > MAX_ICON_LEN {
+            warnings.push(format!("ignored icon: longer than {MAX_ICON_LEN} bytes"));
+        } else {
+            applet.icon = Some(icon);
+        }
+    }
+    for (key, value) in [
+        ("shareId", &mut applet.share_id),
+        ("createdBy", &mut applet.created_by),
+    ] {
+        if value
+            .as_deref()
+            .is_some_and(|value| value.trim().is_empty())
+        {
+            warnings.push(format!("ignored empty {key}"));
+            *value = None;
+        }
+    }
+    Ok(())
+}
+
+/// Reads an applet package: `path` when the OS opened it with syaOS (see
+/// `file_open`), otherwise one the user picks. Returns `None` when the
+/// dialog is cancelled.
+#[tauri::command]
+pub async fn applet_import<R: Runtime>(
+    app: AppHandle<R>,
+    path: Option<PathBuf>,
+) -> Result<Option<Package>, String> {
+    let path = match path {
+        Some(path) => {
+            if !app.state::<Opened>().contains(&path) {
+                return Err(format!("{} was not opened", path.display()));
+            }
+            path
+        }
+        None => {
+            let picked = app
+                .dialog()
+                .file()
+                .add_filter("syaOS Applet", EXTENSIONS)
+                .blocking_pick_file();
+            match picked {
+                Some(picked) => picked.into_path().map_err(|err| err.to_string())?,
+                None => return Ok(None),
+            }
+        }
+    };
+    read(&path).map(Some)
+}
+
+/// Asks where to save `applet` as `format` and writes it. Returns the path,
+/// or `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn applet_export<R: Runtime>(
+    app: AppHandle<R>,
+    applet: Applet,
+    format: Format,
+) -> Result<Option<PathBuf>, String> {
+    let bytes = encode(&applet, format)?;
+    let (filter, extension) = match format {
+        Format::App => ("syaOS Applet", "app"),
+        Format::Html => ("HTML", "html"),
+    };
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter(filter, &[extension])
+        .set_file_name(export_file_name(&applet, format))
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|err| err.to_string())?;
+    atomic::write(&path, &bytes).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applet() -> Applet {
+        Applet {
+            name: "Clock.app".to_string(),
+            content: "<html><body>12:00</body></html>".to_string(),
+            icon: Some("⏰".to_string()),
+            share_id: Some("abc123".to_string()),
+            window_width: Some(320),
+            window_height: Some(240),
+            created_at: Some(1_700_000_000_000),
+            ..Applet::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_both_formats() {
+        let applet = applet();
+        for format in [Format::App, Format::Html] {
+            let package = decode(&encode(&applet, format).unwrap(), "x").unwrap();
+            assert_eq!(package.applet, applet);
+            assert_eq!(package.format, format);
+            assert_eq!(package.compressed, format == Format::App);
+            assert!(package.warnings.is_empty());
+        }
+    }
+
+    #[test]
+    fn writes_the_web_export_layout() {
+        let package = encode(&applet(), Format::App).unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&package[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        assert!(json.starts_with("{\n  \"name\": \"Clock.app\",\n  \"content\": "));
+        assert!(!json.contains("createdBy"));
+
+        let html = String::from_utf8(encode(&applet(), Format::Html).unwrap()).unwrap();
+        assert!(html.starts_with("<!-- shareId: abc123 -->\n<!-- name: Clock.app -->\n"));
+        assert!(html.ends_with("\n<html><body>12:00</body></html>"));
+    }
+
+    #[test]
+    fn takes_name_and_icon_from_the_file_name() {
+        let package = decode(b"<p>hi</p>", "\u{1F4E6} Notes.html").unwrap();
+        assert_eq!(package.applet.name, "Notes.app");
+        assert_eq!(package.applet.icon.as_deref(), Some("\u{1F4E6}"));
+
+        let package = decode(b"<p>hi</p>", "notes").unwrap();
+        assert_eq!(package.applet.name, "notes.app");
+        assert_eq!(package.applet.icon, None);
+    }
+
+    #[test]
+    fn drops_invalid_metadata_with_warnings() {
+        let html = "<!-- windowWidth: 99999 -->\n<!-- createdAt: soon -->\n\n<div>x</div>";
+        let package = decode(html.as_bytes(), "a.html").unwrap();
+        assert_eq!(package.applet.window_width, None);
+        assert_eq!(package.applet.created_at, None);
+        assert_eq!(package.applet.content, "<div>x</div>");
+        assert_eq!(package.warnings.len(), 2);
+    }
+
+    #[test]
+    fn rejects_non_applets() {
+        assert!(decode(b"", "a.app").is_err());
+        assert!(decode(b"just text", "a.app").is_err());
+        assert!(decode(&[0x1f, 0x8b, 0, 1, 2], "a.gz").is_err());
+        assert!(decode(&[0xff, 0xfe, b'<'], "a.html").is_err());
+    }
+
+    #[test]
+    fn names_exports_like_the_web_app() {
+        assert_eq!(export_file_name(&applet(), Format::App), "⏰ Clock.app");
+        assert_eq!(export_file_name(&applet(), Format::Html), "Clock.html");
+        let plain = Applet {
+            icon: Some("/icons/clock.png".to_string()),
+            ..applet()
+        };
+        assert_eq!(export_file_name(&plain, Format::App), "📦 Clock.app");
+    }
+}
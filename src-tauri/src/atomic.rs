@@ -0,0 +1,136 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+/// A path next to `path` to write to before renaming over it. The name is
+/// unique, so concurrent writers don't share one, short enough to fit
+/// wherever `path` does, and hidden like the other dotfiles.
+pub fn temp_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!(".{}.tmp", Uuid::new_v4().simple()))
+}
+
+/// Writes next to `path`, then renames over it, so a crash or failed write
+/// leaves the old file intact. The temporary file is removed on failure.
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = temp_path(path);
+    let result = fs::write(&tmp, contents).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Like [`write`], for a file of the user's rather than the shell's. A
+/// symlink is followed, so the link stays, and the new file gets the old
+/// one's permissions and owner. When it can't have the owner, the file is
+/// written in place instead.
+pub fn replace(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let target = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return write(path, contents),
+        Err(err) => return Err(err),
+    };
+    let metadata = fs::metadata(&target)?;
+    let tmp = temp_path(&target);
+    let result = fs::write(&tmp, contents)
+        .and_then(|()| fs::set_permissions(&tmp, metadata.permissions()))
+        .and_then(|()| {
+            if set_owner(&tmp, &metadata).is_ok() {
+                fs::rename(&tmp, &target)
+            } else {
+                fs::remove_file(&tmp)?;
+                fs::write(&target, contents)
+            }
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(unix)]
+fn set_owner(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::os::unix::fs::chown(path, Some(metadata.uid()), Some(metadata.gid()))
+}
+
+#[cfg(not(unix))]
+fn set_owner(_path: &Path, _metadata: &fs::Metadata) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn names(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_whole_files_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let long = dir.path().join("n".repeat(255));
+        write(&long, b"a").unwrap();
+        assert_eq!(fs::read(&long).unwrap(), b"a");
+
+        // A file can't replace a non-empty directory
+        let taken = dir.path().join("taken");
+        fs::create_dir_all(taken.join("child")).unwrap();
+        assert!(write(&taken, b"b").is_err());
+        assert_eq!(names(dir.path()).len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replaces_through_symlinks_keeping_the_mode() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        fs::write(&file, "old").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o640)).unwrap();
+        let link = dir.path().join("link.md");
+        symlink(&file, &link).unwrap();
+
+        replace(&link, b"new").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read(&file).unwrap(), b"new");
+        let mode = fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        assert_eq!(names(dir.path()).len(), 2);
+
+        let new = dir.path().join("new.md");
+        replace(&new, b"a").unwrap();
+        assert_eq!(fs::read(&new).unwrap(), b"a");
+    }
+
+    #[test]
+    fn concurrent_writers_dont_collide() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.json");
+        let writers: Vec<_> = (0..8u8)
+            .map(|index| {
+                let path = path.clone();
+                thread::spawn(move || write(&path, &[index; 4096]))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+        let contents = fs::read(&path).unwrap();
+        assert!(contents.iter().all(|byte| *byte == contents[0]));
+        assert_eq!(names(dir.path()), ["index.json"]);
+    }
+}
@@ -0,0 +1,792 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::ipc::{Request, Response};
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::atomic;
+use crate::ipc_body;
+
+/// `format` of every profile archive's manifest.
+pub const FORMAT: &str = "syaos-profile";
+
+/// Version of the archive layout; archives from newer shells are rejected.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// IndexedDB object stores of the web app (`STORES` in
+/// `src/utils/indexedDB.ts`); others in an archive are skipped.
+pub const STORES: &[&str] = &[
+    "documents",
+    "images",
+    "trash",
+    "custom_wallpapers",
+    "applets",
+];
+
+const MANIFEST: &str = "manifest.json";
+const LOCAL_STORAGE: &str = "localStorage.json";
+const BLOBS: &str = "blobs.json";
+const STORES_DIR: &str = "stores";
+const BLOBS_DIR: &str = "blobs";
+
+/// Key marking where a `Blob` was in a stored value: `{ "$blob": <index> }`.
+const BLOB_REF: &str = "$blob";
+
+/// Upper bound on what an archive may unpack to.
+const MAX_UNPACKED: u64 = 4 << 30;
+
+/// Describes an archive; the first entry in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub created_at: u64,
+    pub app_version: String,
+    pub local_storage_keys: usize,
+    /// Number of records per IndexedDB store.
+    pub stores: BTreeMap<String, usize>,
+    pub blobs: usize,
+}
+
+/// An IndexedDB record; keys are usually UUID strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub key: Value,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlobInfo {
+    pub size: u64,
+    #[serde(rename = "type", default)]
+    pub mime: String,
+}
+
+/// The web app's storage, as exchanged with the webview: this JSON followed
+/// by the bytes of each blob in `blobs`, in order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    #[serde(default)]
+    pub local_storage: BTreeMap<String, String>,
+    #[serde(default)]
+    pub stores: BTreeMap<String, Vec<Record>>,
+    #[serde(default)]
+    pub blobs: Vec<BlobInfo>,
+}
+
+/// Keys the web app already has, for conflict checks.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Existing {
+    #[serde(default)]
+    pub local_storage: Vec<String>,
+    #[serde(default)]
+    pub stores: BTreeMap<String, Vec<Value>>,
+}
+
+/// What to do with entries the web app already has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    #[default]
+    Overwrite,
+    Skip,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    pub added: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+}
+
+/// An entry present both in the archive and the web app.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Conflict {
+    /// `localStorage` or an IndexedDB store name.
+    pub store: String,
+    pub key: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub path: PathBuf,
+    /// Names the archive for [`profile_import`] after a dry run.
+    pub token: String,
+    pub dry_run: bool,
+    pub manifest: Manifest,
+    pub local_storage: Counts,
+    pub stores: BTreeMap<String, Counts>,
+    pub conflicts: Vec<Conflict>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportReport {
+    pub path: PathBuf,
+    pub manifest: Manifest,
+    pub bytes: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Splits a webview message into its JSON and the blobs after it.
+pub fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<(T, Vec<&[u8]>), String> {
+    let (length, rest) = body
+        .split_first_chunk::<4>()
+        .ok_or("message is too short")?;
+    let length = u32::from_le_bytes(*length) as usize;
+    if rest.len() < length {
+        return Err("message is truncated".to_string());
+    }
+    let (json, mut rest) = rest.split_at(length);
+    let value: Value = serde_json::from_slice(json).map_err(|err| err.to_string())?;
+    let sizes: Vec<u64> = value
+        .get("blobs")
+        .and_then(Value::as_array)
+        .map(|blobs| {
+            blobs
+                .iter()
+                .map(|blob| blob.get("size").and_then(Value::as_u64).unwrap_or_default())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut blobs = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        if (rest.len() as u64) < size {
+            return Err("message is missing blob data".to_string());
+        }
+        let (blob, next) = rest.split_at(size as usize);
+        blobs.push(blob);
+        rest = next;
+    }
+    if !rest.is_empty() {
+        return Err("message has unexpected trailing data".to_string());
+    }
+    let decoded = serde_json::from_value(value).map_err(|err| err.to_string())?;
+    Ok((decoded, blobs))
+}
+
+/// The inverse of [`decode`].
+pub fn encode<T: Serialize>(value: &T, blobs: &[&[u8]]) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(value).map_err(|err| err.to_string())?;
+    let length = u32::try_from(json.len()).map_err(|_| "message is too large".to_string())?;
+    let mut body = length.to_le_bytes().to_vec();
+    body.extend(json);
+    for blob in blobs {
+        body.extend_from_slice(blob);
+    }
+    Ok(body)
+}
+
+/// Blob indices referenced from `value`.
+fn blob_refs(value: &Value, refs: &mut Vec<usize>) {
+    match value {
+        Value::Object(object) => match blob_ref(object) {
+            Some(index) => refs.push(index),
+            None => object.values().for_each(|value| blob_refs(value, refs)),
+        },
+        Value::Array(values) => values.iter().for_each(|value| blob_refs(value, refs)),
+        _ => {}
+    }
+}
+
+fn blob_ref(object: &Map<String, Value>) -> Option<usize> {
+    if object.len() != 1 {
+        return None;
+    }
+    Some(object.get(BLOB_REF)?.as_u64()? as usize)
+}
+
+/// Points blob references at their new indices.
+fn remap_blobs(value: &mut Value, map: &HashMap<usize, usize>) {
+    match value {
+        Value::Object(object) => match blob_ref(object) {
+            Some(index) => {
+                object.insert(BLOB_REF.into(), map[&index].into());
+            }
+            None => object
+                .values_mut()
+                .for_each(|value| remap_blobs(value, map)),
+        },
+        Value::Array(values) => values.iter_mut().for_each(|value| remap_blobs(value, map)),
+        _ => {}
+    }
+}
+
+/// Checks that every blob reference in `snapshot` points at a blob.
+fn check_blob_refs(snapshot: &Snapshot) -> Result<(), String> {
+    for (store, records) in &snapshot.stores {
+        for record in records {
+            let mut refs = Vec::new();
+            blob_refs(&record.value, &mut refs);
+            if let Some(index) = refs.iter().find(|&&index| index >= snapshot.blobs.len()) {
+                return Err(format!(
+                    "record {} in `{store}` refers to missing blob {index}",
+                    record.key
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn manifest(snapshot: &Snapshot) -> Manifest {
+    Manifest {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        created_at: now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        local_storage_keys: snapshot.local_storage.len(),
+        stores: snapshot
+            .stores
+            .iter()
+            .map(|(store, records)| (store.clone(), records.len()))
+            .collect(),
+        blobs: snapshot.blobs.len(),
+    }
+}
+
+fn add_json<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &impl Serialize,
+) -> ZipResult<()> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(name, options)?;
+    serde_json::to_writer_pretty(zip, value).map_err(io::Error::from)?;
+    Ok(())
+}
+
+/// Writes `snapshot` to a new archive at `path`, replacing it only once
+/// the archive is complete.
+pub fn write_archive(
+    path: &Path,
+    snapshot: &Snapshot,
+    blobs: &[&[u8]],
+) -> Result<Manifest, String> {
+    check_blob_refs(snapshot)?;
+    let manifest = manifest(snapshot);
+    let tmp = atomic::temp_path(path);
+    let write = || -> ZipResult<()> {
+        let mut zip = ZipWriter::new(File::create(&tmp)?);
+        add_json(&mut zip, MANIFEST, &manifest)?;
+        add_json(&mut zip, LOCAL_STORAGE, &snapshot.local_storage)?;
+        add_json(&mut zip, BLOBS, &snapshot.blobs)?;
+        for (store, records) in &snapshot.stores {
+            add_json(&mut zip, &format!("{STORES_DIR}/{store}.json"), records)?;
+        }
+        // Blobs are mostly images, which don't compress any further
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for (index, blob) in blobs.iter().enumerate() {
+            zip.start_file(format!("{BLOBS_DIR}/{index}"), stored)?;
+            zip.write_all(blob)?;
+        }
+        zip.finish()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    };
+    write().map(|()| manifest).map_err(|err| {
+        let _ = fs::remove_file(&tmp);
+        format!("{}: {err}", path.display())
+    })
+}
+
+fn parse<T: DeserializeOwned>(name: &str, contents: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(contents).map_err(|err| format!("{name}: {err}"))
+}
+
+/// Reads the archive at `path`, checking it against its manifest.
+pub fn read_archive(path: &Path) -> Result<(Manifest, Snapshot, Vec<Vec<u8>>), String> {
+    read_archive_within(path, MAX_UNPACKED)
+}
+
+/// [`read_archive`], unpacking at most `limit` bytes in all.
+fn read_archive_within(
+    path: &Path,
+    limit: u64,
+) -> Result<(Manifest, Snapshot, Vec<Vec<u8>>), String> {
+    let file = File::open(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let mut zip =
+        ZipArchive::new(file).map_err(|err| format!("not a syaOS profile archive: {err}"))?;
+    let unpacked: u64 = (0..zip.len())
+        .filter_map(|index| zip.by_index(index).ok().map(|entry| entry.size()))
+        .sum();
+    if unpacked > limit {
+        return Err("archive is too large".to_string());
+    }
+
+    // `size` comes from the archive itself, so it isn't trusted: every read
+    // draws on what is left of the limit
+    let mut remaining = limit;
+    let mut read = |name: &str| -> Result<Vec<u8>, String> {
+        let entry = zip
+            .by_name(name)
+            .map_err(|_| format!("archive is missing `{name}`"))?;
+        let mut contents = Vec::new();
+        entry
+            .take(remaining.saturating_add(1))
+            .read_to_end(&mut contents)
+            .map_err(|err| format!("{name}: {err}"))?;
+        remaining = remaining
+            .checked_sub(contents.len() as u64)
+            .ok_or("archive is too large")?;
+        Ok(contents)
+    };
+
+    let manifest: Manifest = parse(MANIFEST, &read(MANIFEST)?)?;
+    if manifest.format != FORMAT {
+        return Err("not a syaOS profile archive".to_string());
+    }
+    if manifest.version > FORMAT_VERSION {
+        return Err(format!(
+            "archive format {} is newer than this version of syaOS supports ({FORMAT_VERSION})",
+            manifest.version
+        ));
+    }
+
+    let mut snapshot = Snapshot {
+        local_storage: parse(LOCAL_STORAGE, &read(LOCAL_STORAGE)?)?,
+        blobs: parse(BLOBS, &read(BLOBS)?)?,
+        ..Snapshot::default()
+    };
+    for (store, count) in &manifest.stores {
+        let name = format!("{STORES_DIR}/{store}.json");
+        let records: Vec<Record> = parse(&name, &read(&name)?)?;
+        if records.len() != *count {
+            return Err(format!(
+                "`{name}` has {} records instead of {count}",
+                records.len()
+            ));
+        }
+        snapshot.stores.insert(store.clone(), records);
+    }
+    if snapshot.local_storage.len() != manifest.local_storage_keys
+        || snapshot.blobs.len() != manifest.blobs
+    {
+        return Err("archive contents don't match its manifest".to_string());
+    }
+    check_blob_refs(&snapshot)?;
+
+    let mut blobs = Vec::with_capacity(snapshot.blobs.len());
+    for (index, info) in snapshot.blobs.iter().enumerate() {
+        let blob = read(&format!("{BLOBS_DIR}/{index}"))?;
+        if blob.len() as u64 != info.size {
+            return Err(format!("blob {index} is damaged"));
+        }
+        blobs.push(blob);
+    }
+    Ok((manifest, snapshot, blobs))
+}
+
+/// Archives the user picked to import, by token, so an import only ever
+/// reads a file the shell itself asked for.
+#[derive(Default)]
+pub struct PickedArchives {
+    paths: Mutex<HashMap<String, PathBuf>>,
+}
+
+impl PickedArchives {
+    pub fn insert(&self, path: PathBuf) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        self.paths.lock().unwrap().insert(token.clone(), path);
+        token
+    }
+
+    pub fn get(&self, token: &str) -> Result<PathBuf, String> {
+        self.paths
+            .lock()
+            .unwrap()
+            .get(token)
+            .cloned()
+            .ok_or_else(|| format!("no picked archive `{token}`"))
+    }
+
+    pub fn remove(&self, token: &str) {
+        self.paths.lock().unwrap().remove(token);
+    }
+}
+
+/// What importing an archive does.
+pub struct Plan {
+    /// Entries to write, referring to blobs by their index in `blobs`.
+    pub apply: Snapshot,
+    /// Archive indices of the blobs `apply` uses, in their new order.
+    pub blobs: Vec<usize>,
+    pub report: ImportReport,
+}
+
+/// Counts an entry and reports whether to write it.
+fn admit(
+    on_conflict: OnConflict,
+    counts: &mut Counts,
+    conflicts: &mut Vec<Conflict>,
+    store: &str,
+    key: &Value,
+    exists: bool,
+) -> bool {
+    if !exists {
+        counts.added += 1;
+        return true;
+    }
+    conflicts.push(Conflict {
+        store: store.to_string(),
+        key: key.clone(),
+    });
+    match on_conflict {
+        OnConflict::Overwrite => counts.overwritten += 1,
+        OnConflict::Skip => counts.skipped += 1,
+    }
+    on_conflict == OnConflict::Overwrite
+}
+
+/// Works out what importing `snapshot` over `existing` would write.
+pub fn plan(
+    snapshot: Snapshot,
+    existing: &Existing,
+    on_conflict: OnConflict,
+    report: ImportReport,
+) -> Plan {
+    let mut plan = Plan {
+        apply: Snapshot::default(),
+        blobs: Vec::new(),
+        report,
+    };
+    let report = &mut plan.report;
+
+    for (key, value) in snapshot.local_storage {
+        let exists = existing.local_storage.contains(&key);
+        let admitted = admit(
+            on_conflict,
+            &mut report.local_storage,
+            &mut report.conflicts,
+            "localStorage",
+            &key.clone().into(),
+            exists,
+        );
+        if admitted {
+            plan.apply.local_storage.insert(key, value);
+        }
+    }
+
+    let mut renumbered = HashMap::new();
+    for (store, records) in snapshot.stores {
+        if !STORES.contains(&store.as_str()) {
+            report
+                .warnings
+                .push(format!("skipped unknown store `{store}`"));
+            continue;
+        }
+        let current = existing.stores.get(&store);
+        let counts = report.stores.entry(store.clone()).or_default();
+        let mut kept = Vec::new();
+        for mut record in records {
+            let exists = current.is_some_and(|keys| keys.contains(&record.key));
+            if !admit(
+                on_conflict,
+                counts,
+                &mut report.conflicts,
+                &store,
+                &record.key,
+                exists,
+            ) {
+                continue;
+            }
+            let mut refs = Vec::new();
+            blob_refs(&record.value, &mut refs);
+            for index in refs {
+                renumbered.entry(index).or_insert_with(|| {
+                    plan.blobs.push(index);
+                    plan.apply.blobs.push(snapshot.blobs[index].clone());
+                    plan.blobs.len() - 1
+                });
+            }
+            remap_blobs(&mut record.value, &renumbered);
+            kept.push(record);
+        }
+        plan.apply.stores.insert(store, kept);
+    }
+    plan
+}
+
+/// Saves the snapshot in the request body ([`decode`]'s layout) to an
+/// archive the user picks. Returns `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn profile_export<R: Runtime>(
+    app: AppHandle<R>,
+    request: Request<'_>,
+) -> Result<Option<ExportReport>, String> {
+    let body = ipc_body::body(&request)?;
+    let (snapshot, blobs): (Snapshot, _) = decode(body)?;
+    let file_name = format!("syaOS Profile {}.zip", Local::now().format("%Y-%m-%d"));
+    let picked = app
+        .dialog()
+        .file()
+        .set_title("Export syaOS Profile")
+        .set_file_name(file_name)
+        .add_filter("syaOS Profile", &["zip"])
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|err| err.to_string())?;
+    let manifest = write_archive(&path, &snapshot, &blobs)?;
+    let bytes = fs::metadata(&path)
+        .map(|meta| meta.len())
+        .unwrap_or_default();
+    Ok(Some(ExportReport {
+        path,
+        manifest,
+        bytes,
+    }))
+}
+
+/// Validates the archive named by `token`, from a dry run's report, or
+/// one the user picks, and plans its import over `existing`. Responds in
+/// [`decode`]'s layout with `{ report, apply }`, `apply` and the blobs
+/// being left out on a dry run, or with `null` when the dialog is
+/// cancelled. A token is used up by an import that isn't a dry run.
+#[tauri::command]
+pub async fn profile_import<R: Runtime>(
+    app: AppHandle<R>,
+    token: Option<String>,
+    dry_run: bool,
+    on_conflict: Option<OnConflict>,
+    existing: Existing,
+    picked: State<'_, PickedArchives>,
+) -> Result<Response, String> {
+    let (token, path) = match token {
+        Some(token) => {
+            let path = picked.get(&token)?;
+            (token, path)
+        }
+        None => {
+            let file = app
+                .dialog()
+                .file()
+                .set_title("Import syaOS Profile")
+                .add_filter("syaOS Profile", &["zip"])
+                .blocking_pick_file();
+            match file {
+                Some(file) => {
+                    let path = file.into_path().map_err(|err| err.to_string())?;
+                    (picked.insert(path.clone()), path)
+                }
+                None => return encode(&Value::Null, &[]).map(Response::new),
+            }
+        }
+    };
+
+    let (manifest, snapshot, blobs) = read_archive(&path)?;
+    if !dry_run {
+        picked.remove(&token);
+    }
+    let report = ImportReport {
+        path,
+        token,
+        dry_run,
+        manifest,
+        local_storage: Counts::default(),
+        stores: BTreeMap::new(),
+        conflicts: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let plan = plan(snapshot, &existing, on_conflict.unwrap_or_default(), report);
+    let body = if dry_run {
+        encode(&serde_json::json!({ "report": plan.report }), &[])?
+    } else {
+        let used: Vec<&[u8]> = plan.blobs.iter().map(|&index| &blobs[index][..]).collect();
+        encode(
+            &serde_json::json!({ "report": plan.report, "apply": plan.apply }),
+            &used,
+        )?
+    };
+    Ok(Response::new(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn snapshot() -> (Snapshot, Vec<&'static [u8]>) {
+        let snapshot = Snapshot {
+            local_storage: BTreeMap::from([
+                ("ryos:files".to_string(), "{\"state\":{}}".to_string()),
+                ("ryos:theme".to_string(), "\"xp\"".to_string()),
+            ]),
+            stores: BTreeMap::from([
+                (
+                    "documents".to_string(),
+                    vec![Record {
+                        key: json!("doc"),
+                        value: json!({ "name": "a.md", "content": "# hi" }),
+                    }],
+                ),
+                (
+                    "images".to_string(),
+                    vec![
+                        Record {
+                            key: json!("one"),
+                            value: json!({ "name": "a.png", "content": { "$blob": 0 } }),
+                        },
+                        Record {
+                            key: json!("two"),
+                            value: json!({ "name": "b.png", "content": { "$blob": 1 } }),
+                        },
+                    ],
+                ),
+            ]),
+            blobs: vec![
+                BlobInfo {
+                    size: 3,
+                    mime: "image/png".to_string(),
+                },
+                BlobInfo {
+                    size: 2,
+                    mime: "image/png".to_string(),
+                },
+            ],
+        };
+        (snapshot, vec![b"png", b"pn"])
+    }
+
+    fn report(manifest: Manifest) -> ImportReport {
+        ImportReport {
+            path: PathBuf::from("profile.zip"),
+            token: String::new(),
+            dry_run: false,
+            manifest,
+            local_storage: Counts::default(),
+            stores: BTreeMap::new(),
+            conflicts: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let (snapshot, blobs) = snapshot();
+        let body = encode(&snapshot, &blobs).unwrap();
+        let (decoded, decoded_blobs): (Snapshot, _) = decode(&body).unwrap();
+        assert_eq!(decoded, snapshot);
+        assert_eq!(decoded_blobs, blobs);
+
+        assert!(decode::<Snapshot>(&body[..body.len() - 1]).is_err());
+        let mut longer = body.clone();
+        longer.push(0);
+        assert!(decode::<Snapshot>(&longer).is_err());
+    }
+
+    #[test]
+    fn archives_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profile.zip");
+        let (snapshot, blobs) = snapshot();
+        let manifest = write_archive(&path, &snapshot, &blobs).unwrap();
+        assert_eq!(manifest.stores["images"], 2);
+
+        let (read_manifest, read_snapshot, read_blobs) = read_archive(&path).unwrap();
+        assert_eq!(read_manifest, manifest);
+        assert_eq!(read_snapshot, snapshot);
+        assert_eq!(read_blobs, [b"png".to_vec(), b"pn".to_vec()]);
+
+        let size = fs::metadata(&path).unwrap().len();
+        let err = read_archive_within(&path, size / 4).unwrap_err();
+        assert_eq!(err, "archive is too large");
+    }
+
+    #[test]
+    fn rejects_invalid_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profile.zip");
+        fs::write(&path, "not a zip").unwrap();
+        assert!(read_archive(&path).is_err());
+
+        let (mut snapshot, blobs) = snapshot();
+        snapshot.stores.get_mut("images").unwrap()[1].value = json!({ "$blob": 5 });
+        assert!(write_archive(&path, &snapshot, &blobs).is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let mut manifest = manifest(&Snapshot::default());
+        manifest.version = FORMAT_VERSION + 1;
+        add_json(&mut zip, MANIFEST, &manifest).unwrap();
+        zip.finish().unwrap();
+        let err = read_archive(&path).unwrap_err();
+        assert!(err.contains("newer"), "{err}");
+    }
+
+    #[test]
+    fn only_imports_picked_archives() {
+        let picked = PickedArchives::default();
+        let token = picked.insert(PathBuf::from("profile.zip"));
+        assert_eq!(picked.get(&token).unwrap(), PathBuf::from("profile.zip"));
+        assert!(picked.get("/etc/passwd").is_err());
+        picked.remove(&token);
+        assert!(picked.get(&token).is_err());
+    }
+
+    #[test]
+    fn plans_conflicts_and_renumbers_blobs() {
+        let (mut snapshot, _) = snapshot();
+        snapshot.stores.insert("unknown".to_string(), Vec::new());
+        let existing = Existing {
+            local_storage: vec!["ryos:theme".to_string()],
+            stores: BTreeMap::from([("images".to_string(), vec![json!("one")])]),
+        };
+        let manifest = manifest(&snapshot);
+
+        let skipped = plan(
+            snapshot.clone(),
+            &existing,
+            OnConflict::Skip,
+            report(manifest.clone()),
+        );
+        assert_eq!(
+            skipped.report.local_storage,
+            Counts {
+                added: 1,
+                overwritten: 0,
+                skipped: 1
+            }
+        );
+        assert_eq!(skipped.report.stores["images"].skipped, 1);
+        assert_eq!(skipped.report.conflicts.len(), 2);
+        assert_eq!(skipped.report.warnings.len(), 1);
+        // Only the second image is written, so its blob becomes blob 0
+        assert_eq!(skipped.blobs, [1]);
+        assert_eq!(
+            skipped.apply.stores["images"][0].value["content"],
+            json!({ "$blob": 0 })
+        );
+        assert!(!skipped.apply.local_storage.contains_key("ryos:theme"));
+
+        let overwritten = plan(snapshot, &existing, OnConflict::Overwrite, report(manifest));
+        assert_eq!(overwritten.report.local_storage.overwritten, 1);
+        assert_eq!(overwritten.blobs, [0, 1]);
+        assert_eq!(overwritten.apply.local_storage.len(), 2);
+    }
+}
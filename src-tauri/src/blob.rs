@@ -0,0 +1,684 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::ipc;
+use tauri::{AppHandle, Manager, Runtime, State, UriSchemeContext, UriSchemeResponder};
+
+use crate::atomic;
+use crate::ipc_body;
+
+/// Scheme blobs are served from, by hash.
+pub const SCHEME: &str = "syaos-blob";
+
+/// Directory in the profile's data dir the blobs are kept in.
+pub const DIR_NAME: &str = "blobs";
+
+/// Name of the file in [`DIR_NAME`] with each blob's type and age.
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// Header carrying the URI-encoded [`BlobOptions`] JSON for `blob_put`,
+/// whose body is the content.
+pub const OPTIONS_HEADER: &str = "x-syaos-blob";
+
+/// Blobs put more recently than this survive `blob_gc` unreferenced, since
+/// the web app may not have saved their hashes yet.
+const GC_GRACE: Duration = Duration::from_secs(10 * 60);
+
+const DEFAULT_TYPE: &str = "application/octet-stream";
+
+/// How often a changed index is saved.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Whether `hash` is a SHA-256 digest as the store names blobs: 64
+/// lowercase hex digits. Anything else never reaches the file system.
+fn is_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn hash_of(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Whether `mime` is a plausible `type/subtype`, parameters allowed, that
+/// can go in a header as it is.
+fn is_valid_type(mime: &str) -> bool {
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    essence.split_once('/').is_some_and(|(kind, subtype)| {
+        !kind.is_empty() && !subtype.is_empty() && !subtype.contains('/')
+    }) && mime.bytes().all(|b| (b' '..=b'~').contains(&b))
+}
+
+/// The type of `content`: recognised from its first bytes when it's a
+/// binary format, else `declared` (e.g. SVG or text), else
+/// [`DEFAULT_TYPE`].
+fn content_type(content: &[u8], declared: Option<&str>) -> String {
+    if let Some(kind) = infer::get(content) {
+        return kind.mime_type().to_string();
+    }
+    declared
+        .filter(|mime| is_valid_type(mime))
+        .unwrap_or(DEFAULT_TYPE)
+        .to_string()
+}
+
+/// A stored blob, as the commands return it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobInfo {
+    pub hash: String,
+    #[serde(rename = "type")]
+    pub mime: String,
+    pub size: u64,
+    /// When it was last put, in milliseconds since the epoch.
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    #[serde(rename = "type")]
+    mime: String,
+    size: u64,
+    created_at: u64,
+}
+
+/// What `blob_gc` did.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    pub removed: usize,
+    pub freed_bytes: u64,
+    pub remaining: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    /// Counts changes, so a save knows whether it has everything.
+    revision: u64,
+    /// The revision last written to disk.
+    saved: u64,
+}
+
+impl Inner {
+    fn insert(&mut self, hash: String, entry: Entry) {
+        self.entries.insert(hash, entry);
+        self.revision += 1;
+    }
+
+    fn remove(&mut self, hash: &str) -> Option<Entry> {
+        let entry = self.entries.remove(hash)?;
+        self.revision += 1;
+        Some(entry)
+    }
+}
+
+/// Content-addressed blobs, each in `<dir>/<first two hex digits>/<hash>`.
+/// The index only records types and ages and is saved every
+/// [`SAVE_INTERVAL`]; the files are the truth, so a blob written since is
+/// picked up again on the next launch, with its type recognised anew.
+pub struct BlobStore {
+    dir: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+impl BlobStore {
+    /// Reads the index and reconciles it with the blobs in `dir`.
+    pub fn open(dir: PathBuf) -> Self {
+        let mut indexed: HashMap<String, Entry> = fs::read(dir.join(INDEX_FILE_NAME))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        let mut entries = HashMap::new();
+        let shards = fs::read_dir(&dir).into_iter().flatten().flatten();
+        for shard in shards.filter(|shard| shard.path().is_dir()) {
+            for file in fs::read_dir(shard.path()).into_iter().flatten().flatten() {
+                let Some(hash) = file.file_name().to_str().map(str::to_string) else {
+                    continue;
+                };
+                if !is_hash(&hash) || shard.file_name() != hash[..2] {
+                    continue;
+                }
+                let Ok(metadata) = file.metadata() else {
+                    continue;
+                };
+                let entry = match indexed.remove(&hash) {
+                    Some(entry) => Entry {
+                        size: metadata.len(),
+                        ..entry
+                    },
+                    None => {
+                        let mut head = Vec::new();
+                        let _ = File::open(file.path())
+                            .and_then(|file| file.take(8192).read_to_end(&mut head));
+                        let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+                        Entry {
+                            mime: content_type(&head, None),
+                            size: metadata.len(),
+                            created_at: modified
+                                .duration_since(UNIX_EPOCH)
+                                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+                        }
+                    }
+                };
+                entries.insert(hash, entry);
+            }
+        }
+        Self {
+            dir,
+            inner: Mutex::new(Inner {
+                entries,
+                ..Inner::default()
+            }),
+        }
+    }
+
+    fn path(&self, hash: &str) -> PathBuf {
+        self.dir.join(&hash[..2]).join(hash)
+    }
+
+    /// Writes the index if it changed since it was last saved. It only
+    /// counts as saved once the write succeeds, so a failed save is retried
+    /// next time.
+    pub fn save(&self) {
+        let (revision, json) = {
+            let inner = self.inner.lock().unwrap();
+            if inner.saved == inner.revision {
+                return;
+            }
+            (inner.revision, serde_json::to_vec(&inner.entries))
+        };
+        let written = json
+            .map_err(io::Error::other)
+            .and_then(|json| atomic::write(&self.dir.join(INDEX_FILE_NAME), &json));
+        match written {
+            Ok(()) => {
+                let mut inner = self.inner.lock().unwrap();
+                inner.saved = inner.saved.max(revision);
+            }
+            Err(err) => eprintln!("failed to save the blob index: {err}"),
+        }
+    }
+
+    fn info(hash: &str, entry: &Entry) -> BlobInfo {
+        BlobInfo {
+            hash: hash.to_string(),
+            mime: entry.mime.clone(),
+            size: entry.size,
+            created_at: entry.created_at,
+        }
+    }
+
+    /// Stores `content` unless it's already there. Putting it again counts
+    /// as new for [`Self::gc`].
+    pub fn put(&self, content: &[u8], declared_type: Option<&str>) -> Result<BlobInfo, String> {
+        let hash = hash_of(content);
+        let path = self.path(&hash);
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.entries.contains_key(&hash) || !path.is_file() {
+            fs::create_dir_all(path.parent().unwrap())
+                .and_then(|()| atomic::write(&path, content))
+                .map_err(|err| err.to_string())?;
+        }
+        let mime = match inner.entries.get(&hash) {
+            Some(entry) => entry.mime.clone(),
+            None => content_type(content, declared_type),
+        };
+        let entry = Entry {
+            mime,
+            size: content.len() as u64,
+            created_at: now_ms(),
+        };
+        let info = Self::info(&hash, &entry);
+        inner.insert(hash, entry);
+        Ok(info)
+    }
+
+    pub fn stat(&self, hash: &str) -> Option<BlobInfo> {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.get(hash).map(|entry| Self::info(hash, entry))
+    }
+
+    /// The blob's file, opened, and what it is.
+    pub fn open_blob(&self, hash: &str) -> Result<(File, BlobInfo), String> {
+        let info = self.stat(hash).ok_or_else(|| format!("no blob `{hash}`"))?;
+        let file = File::open(self.path(hash)).map_err(|err| err.to_string())?;
+        Ok((file, info))
+    }
+
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>, String> {
+        let (mut file, _) = self.open_blob(hash)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)
+            .map_err(|err| err.to_string())?;
+        Ok(content)
+    }
+
+    /// Whether there was such a blob.
+    pub fn delete(&self, hash: &str) -> Result<bool, String> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.remove(hash).is_none() {
+            return Ok(false);
+        }
+        match fs::remove_file(self.path(hash)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.to_string()),
+        }
+        let _ = fs::remove_dir(self.path(hash).parent().unwrap());
+        Ok(true)
+    }
+
+    /// Deletes the blobs that aren't in `keep`, except those put within
+    /// [`GC_GRACE`].
+    pub fn gc(&self, keep: &HashSet<String>) -> GcReport {
+        self.gc_before(keep, now_ms().saturating_sub(GC_GRACE.as_millis() as u64))
+    }
+
+    fn gc_before(&self, keep: &HashSet<String>, cutoff: u64) -> GcReport {
+        let mut inner = self.inner.lock().unwrap();
+        let garbage: Vec<String> = inner
+            .entries
+            .iter()
+            .filter(|(hash, entry)| !keep.contains(*hash) && entry.created_at < cutoff)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        let mut report = GcReport::default();
+        for hash in garbage {
+            let path = self.path(&hash);
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    eprintln!("failed to delete {}: {err}", path.display());
+                    continue;
+                }
+            }
+            let _ = fs::remove_dir(path.parent().unwrap());
+            let entry = inner.remove(&hash).unwrap();
+            report.removed += 1;
+            report.freed_bytes += entry.size;
+        }
+        report.remaining = inner.entries.len();
+        report.total_bytes = inner.entries.values().map(|entry| entry.size).sum();
+        report
+    }
+}
+
+/// The byte range a `Range` header asks for out of `len` bytes, as
+/// `start..end`. `Ok(None)` serves the whole blob: no header, or one this
+/// doesn't handle, like several ranges, which may be ignored. `Err` is
+/// for a range past the end.
+fn parse_range(range: Option<&str>, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = range.and_then(|range| range.trim().strip_prefix("bytes=")) else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Ok(None);
+    };
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", "") => return Ok(None),
+        // The last `suffix` bytes
+        ("", suffix) => {
+            let Ok(suffix) = suffix.parse::<u64>() else {
+                return Ok(None);
+            };
+            if suffix == 0 {
+                return Err(());
+            }
+            (len.saturating_sub(suffix), len)
+        }
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return Ok(None);
+            };
+            let end = match end {
+                "" => len,
+                end => match end.parse::<u64>() {
+                    Ok(end) if end >= start => end.saturating_add(1).min(len),
+                    _ => return Ok(None),
+                },
+            };
+            (start, end)
+        }
+    };
+    if start >= len {
+        return Err(());
+    }
+    Ok(Some((start, end)))
+}
+
+/// The hash a blob URL names: `syaos-blob://localhost/<hash>` as
+/// `convertFileSrc` makes it, or `syaos-blob://<hash>`.
+fn hash_from_uri(host: Option<&str>, path: &str) -> Option<String> {
+    let hash = match path.trim_matches('/') {
+        "" => host?,
+        path => path,
+    };
+    let hash = percent_decode_str(hash)
+        .decode_utf8()
+        .ok()?
+        .to_ascii_lowercase();
+    is_hash(&hash).then_some(hash)
+}
+
+fn read_range(file: &mut File, start: u64, end: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(start))?;
+    let mut content = Vec::with_capacity((end - start) as usize);
+    file.take(end - start).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+fn serve<R: Runtime>(app: &AppHandle<R>, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let status = |status: StatusCode| {
+        Response::builder()
+            .status(status)
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(Vec::new())
+            .unwrap()
+    };
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let Some(hash) = hash_from_uri(request.uri().host(), request.uri().path()) else {
+        return status(StatusCode::BAD_REQUEST);
+    };
+    let Some(store) = app.try_state::<BlobStore>() else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    let Ok((mut file, info)) = store.open_blob(&hash) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    let etag = format!("\"{hash}\"");
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, &info.mime)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        // The URL names the content, so it never changes
+        .header(header::CACHE_CONTROL, "max-age=31536000, immutable")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(
+            header::ACCESS_CONTROL_EXPOSE_HEADERS,
+            "Accept-Ranges, Content-Length, Content-Range",
+        );
+    let request_header = |name| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    if request_header(header::IF_NONE_MATCH)
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag))
+    {
+        return response
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Vec::new())
+            .unwrap();
+    }
+    let (response, start, end) = match parse_range(request_header(header::RANGE), info.size) {
+        Ok(None) => (response.status(StatusCode::OK), 0, info.size),
+        Ok(Some((start, end))) => (
+            response.status(StatusCode::PARTIAL_CONTENT).header(
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{}/{}", end - 1, info.size),
+            ),
+            start,
+            end,
+        ),
+        Err(()) => {
+            return response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", info.size))
+                .body(Vec::new())
+                .unwrap();
+        }
+    };
+    let response = response.header(header::CONTENT_LENGTH, end - start);
+    if request.method() == Method::HEAD {
+        return response.body(Vec::new()).unwrap();
+    }
+    match read_range(&mut file, start, end) {
+        Ok(content) => response.body(content).unwrap(),
+        Err(err) => {
+            eprintln!("failed to read blob {hash}: {err}");
+            status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Handler for [`SCHEME`]. Blobs can be large, so they're read off the
+/// webview's thread.
+pub fn protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    thread::spawn(move || responder.respond(serve(&app, &request)));
+}
+
+/// Saves the index whenever it changed; it's also saved on exit.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(SAVE_INTERVAL);
+        if let Some(store) = app.try_state::<BlobStore>() {
+            store.save();
+        }
+    });
+}
+
+/// Options of `blob_put`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobOptions {
+    /// The content's MIME type, used when it isn't recognised from the
+    /// content itself.
+    #[serde(rename = "type")]
+    pub mime: Option<String>,
+}
+
+fn check_hash(hash: &str) -> Result<(), String> {
+    if is_hash(hash) {
+        Ok(())
+    } else {
+        Err(format!("`{hash}` is not a blob hash"))
+    }
+}
+
+/// Takes the content as the raw request body and, optionally, the
+/// [`BlobOptions`] as URI-encoded JSON in the [`OPTIONS_HEADER`] header.
+#[tauri::command]
+pub async fn blob_put(
+    request: ipc::Request<'_>,
+    store: State<'_, BlobStore>,
+) -> Result<BlobInfo, String> {
+    let options: BlobOptions = ipc_body::json_header(&request, OPTIONS_HEADER)?.unwrap_or_default();
+    let content = ipc_body::body(&request)?;
+    store.put(content, options.mime.as_deref())
+}
+
+/// The blob's content as raw bytes.
+#[tauri::command]
+pub async fn blob_get(hash: String, store: State<'_, BlobStore>) -> Result<ipc::Response, String> {
+    check_hash(&hash)?;
+    store.get(&hash).map(ipc::Response::new)
+}
+
+#[tauri::command]
+pub fn blob_stat(hash: String, store: State<'_, BlobStore>) -> Option<BlobInfo> {
+    store.stat(&hash)
+}
+
+/// Whether there was such a blob.
+#[tauri::command]
+pub fn blob_delete(hash: String, store: State<'_, BlobStore>) -> Result<bool, String> {
+    check_hash(&hash)?;
+    store.delete(&hash)
+}
+
+/// Deletes every blob not in `keep`, the hashes the web app still refers
+/// to, except those put in the last few minutes.
+#[tauri::command]
+pub async fn blob_gc(keep: Vec<String>, store: State<'_, BlobStore>) -> Result<GcReport, String> {
+    Ok(store.gc(&keep.into_iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn stores_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path().join(DIR_NAME));
+
+        let info = store.put(PNG, Some("text/plain")).unwrap();
+        assert_eq!(info.hash, hash_of(PNG));
+        // Recognised content wins over the declared type
+        assert_eq!(info.mime, "image/png");
+        assert_eq!(info.size, PNG.len() as u64);
+        assert!(dir
+            .path()
+            .join(DIR_NAME)
+            .join(&info.hash[..2])
+            .join(&info.hash)
+            .is_file());
+        assert_eq!(store.get(&info.hash).unwrap(), PNG);
+        assert_eq!(store.put(PNG, None).unwrap().hash, info.hash);
+
+        let svg = store.put(b"<svg/>", Some("image/svg+xml")).unwrap();
+        assert_eq!(svg.mime, "image/svg+xml");
+        let text = store.put(b"hello", Some("bad\ntype")).unwrap();
+        assert_eq!(text.mime, DEFAULT_TYPE);
+
+        assert!(store.delete(&text.hash).unwrap());
+        assert!(!store.delete(&text.hash).unwrap());
+        assert!(store.get(&text.hash).is_err());
+    }
+
+    #[test]
+    fn reopens_from_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = dir.path().join(DIR_NAME);
+        let store = BlobStore::open(blobs.clone());
+        let svg = store.put(b"<svg/>", Some("image/svg+xml")).unwrap();
+        let png = store.put(PNG, None).unwrap();
+        // A blob written before a crash, missing from the index
+        let orphan = b"GIF89a\x01\x00\x01\x00";
+        let orphan_hash = hash_of(orphan);
+        fs::create_dir_all(blobs.join(&orphan_hash[..2])).unwrap();
+        fs::write(blobs.join(&orphan_hash[..2]).join(&orphan_hash), orphan).unwrap();
+        // And an indexed one that's gone
+        fs::remove_file(blobs.join(&png.hash[..2]).join(&png.hash)).unwrap();
+        store.save();
+
+        let reopened = BlobStore::open(blobs);
+        assert_eq!(reopened.stat(&svg.hash), Some(svg));
+        assert_eq!(reopened.stat(&orphan_hash).unwrap().mime, "image/gif");
+        assert_eq!(reopened.stat(&png.hash), None);
+    }
+
+    #[test]
+    fn saves_the_index_only_when_it_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join(DIR_NAME).join(INDEX_FILE_NAME);
+        let store = BlobStore::open(dir.path().join(DIR_NAME));
+        store.save();
+        assert!(!index.exists());
+
+        let svg = store.put(b"<svg/>", Some("image/svg+xml")).unwrap();
+        assert!(!index.exists());
+        store.save();
+        assert!(index.is_file());
+
+        fs::remove_file(&index).unwrap();
+        store.save();
+        assert!(!index.exists());
+        assert!(store.delete(&svg.hash).unwrap());
+        store.save();
+        assert_eq!(fs::read(&index).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn collects_unreferenced_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::open(dir.path().join(DIR_NAME));
+        let kept = store.put(b"kept", None).unwrap();
+        let dropped = store.put(b"dropped", None).unwrap();
+        let keep = HashSet::from([kept.hash.clone()]);
+
+        // Everything is too recent
+        assert_eq!(store.gc(&keep).removed, 0);
+        let report = store.gc_before(&keep, now_ms() + 1);
+        assert_eq!(
+            report,
+            GcReport {
+                removed: 1,
+                freed_bytes: 7,
+                remaining: 1,
+                total_bytes: 4,
+            }
+        );
+        assert!(store.stat(&dropped.hash).is_none());
+        assert!(!store.path(&dropped.hash).exists());
+        assert_eq!(store.get(&kept.hash).unwrap(), b"kept");
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_range(None, 100), Ok(None));
+        assert_eq!(parse_range(Some("bytes=0-9"), 100), Ok(Some((0, 10))));
+        assert_eq!(parse_range(Some("bytes=90-"), 100), Ok(Some((90, 100))));
+        assert_eq!(parse_range(Some("bytes=90-500"), 100), Ok(Some((90, 100))));
+        assert_eq!(parse_range(Some("bytes=-10"), 100), Ok(Some((90, 100))));
+        assert_eq!(parse_range(Some("bytes=-500"), 100), Ok(Some((0, 100))));
+        assert_eq!(parse_range(Some("bytes=100-"), 100), Err(()));
+        assert_eq!(parse_range(Some("bytes=-0"), 100), Err(()));
+        // Not handled, so the whole blob is sent
+        assert_eq!(parse_range(Some("bytes=0-1,5-6"), 100), Ok(None));
+        assert_eq!(parse_range(Some("bytes=9-1"), 100), Ok(None));
+        assert_eq!(parse_range(Some("items=0-1"), 100), Ok(None));
+    }
+
+    #[test]
+    fn finds_hashes_in_urls() {
+        let hash = hash_of(b"x");
+        // `convertFileSrc` URLs, then the short form
+        assert_eq!(
+            hash_from_uri(Some("localhost"), &format!("/{hash}")),
+            Some(hash.clone())
+        );
+        assert_eq!(
+            hash_from_uri(
+                Some("syaos-blob.localhost"),
+                &format!("/{}", hash.to_uppercase())
+            ),
+            Some(hash.clone())
+        );
+        assert_eq!(hash_from_uri(Some(&hash), "/"), Some(hash));
+        assert_eq!(hash_from_uri(Some("localhost"), "/..%2Findex.json"), None);
+        assert_eq!(hash_from_uri(Some("localhost"), ""), None);
+    }
+}
@@ -0,0 +1,13 @@
+use tauri::ipc::CapabilityBuilder;
+
+/// Grants the main window's permissions to an origin only known at runtime.
+/// Keep the permission list in sync with `capabilities/default.json`.
+pub fn main_window(identifier: &str, remote_url: &str) -> CapabilityBuilder {
+    CapabilityBuilder::new(identifier)
+        .remote(remote_url.to_string())
+        .window("main")
+        .permission("core:default")
+        .permission("shell:allow-open")
+        .permission("core:window:allow-start-dragging")
+        .permission("core:window:allow-toggle-maximize")
+}
@@ -0,0 +1,332 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::deep_link;
+use crate::event_queue::EventQueue;
+use crate::file_open;
+use crate::launch::LaunchMode;
+
+pub const USAGE: &str = "\
+Usage: syaos [options] [open <app-id> [path] | syaos://<link> | <file>...]
+       syaos applet <file>
+
+Commands:
+  open <app-id> [path]  Open an app, optionally at a syaOS path
+                        (e.g. `syaos open textedit /Documents/notes.md`)
+  syaos://<link>        Open a shared applet, song or chat room link
+  <file>...             Open host files in their app: .app/.gz applets,
+                        .md/.txt documents, .jsdos game bundles
+  applet <file>         Check an applet package (.app, .gz, .html) and
+                        print its metadata
+
+Options:
+  --new-window          Open the app in a new window even if one is open
+  --kiosk               Run fullscreen and locked down for public displays
+  --profile <name>      Use a separate profile (storage, window state)
+  --remote              Load the remote origin instead of the bundled app
+  --offline, --bundled  Load the bundled app
+  --origin <url>        Remote origin to load in remote mode
+  -h, --help            Print this help
+  -V, --version         Print the version";
+
+/// App ids accepted by `open`, mirroring `src/config/appIds.ts`; a test
+/// checks the two match.
+pub const APP_IDS: &[&str] = &[
+    "finder",
+    "soundboard",
+    "internet-explorer",
+    "chats",
+    "textedit",
+    "paint",
+    "photo-booth",
+    "minesweeper",
+    "videos",
+    "ipod",
+    "synth",
+    "pc",
+    "terminal",
+    "applet-viewer",
+    "control-panels",
+    "admin",
+];
+
+/// Event carrying a [`LaunchRequest`] once the frontend is listening.
+pub const LAUNCH_EVENT: &str = "shell://launch";
+
+/// An app the shell asks the frontend to open, delivered as a `launchApp`
+/// event on the web side.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchRequest {
+    pub app_id: String,
+    pub path: Option<String>,
+    pub new_window: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Cli {
+    pub launch_mode: Option<LaunchMode>,
+    pub origin: Option<String>,
+    pub profile: Option<String>,
+    pub new_window: bool,
+    pub kiosk: bool,
+    pub open: Option<(String, Option<String>)>,
+    /// A `syaos://` link, as passed by the OS when one is opened.
+    pub deep_link: Option<String>,
+    /// Host files to open, as passed by the OS for a file association.
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Parsed {
+    Run(Cli),
+    Help,
+    Version,
+    /// `applet <file>`: inspect a package without starting the app.
+    InspectApplet(PathBuf),
+}
+
+impl Cli {
+    /// The app to open on launch, if any.
+    pub fn launch_request(&self) -> Option<LaunchRequest> {
+        let (app_id, path) = self.open.as_ref()?;
+        Some(LaunchRequest {
+            app_id: app_id.clone(),
+            path: path.clone(),
+            new_window: self.new_window,
+        })
+    }
+}
+
+/// Called by the frontend once it can handle launches; returns the ones
+/// queued during startup.
+#[tauri::command]
+pub fn take_launch_requests(queue: State<'_, EventQueue<LaunchRequest>>) -> Vec<LaunchRequest> {
+    queue.take()
+}
+
+/// Parses the arguments after the program name.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Parsed, String> {
+    let mut cli = Cli::default();
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{name} needs a value"))
+        };
+
+        match flag.as_str() {
+            "--help" | "--version" | "--new-window" | "--kiosk" | "--remote" | "--offline"
+            | "--bundled"
+                if inline_value.is_some() =>
+            {
+                return Err(format!("`{flag}` doesn't take a value"));
+            }
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "-V" | "--version" => return Ok(Parsed::Version),
+            "--new-window" => cli.new_window = true,
+            "--kiosk" => cli.kiosk = true,
+            "--remote" => cli.launch_mode = Some(LaunchMode::Remote),
+            "--offline" | "--bundled" => cli.launch_mode = Some(LaunchMode::Bundled),
+            "--origin" => cli.origin = Some(value("--origin")?),
+            "--profile" => cli.profile = Some(parse_profile(&value("--profile")?)?),
+            // Added by older macOS versions when launched from Finder
+            _ if flag.starts_with("-psn_") => {}
+            _ if flag.starts_with('-') => return Err(format!("unknown option `{flag}`")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    match positional.next().as_deref() {
+        None => {}
+        Some("open") => {
+            let app_id = positional.next().ok_or("`open` needs an app id")?;
+            if !APP_IDS.contains(&app_id.as_str()) {
+                return Err(format!("unknown app `{app_id}`"));
+            }
+            cli.open = Some((app_id, positional.next()));
+        }
+        Some("applet") => {
+            let file = positional.next().ok_or("`applet` needs a file")?;
+            if let Some(extra) = positional.next() {
+                return Err(format!("unexpected argument `{extra}`"));
+            }
+            return Ok(Parsed::InspectApplet(PathBuf::from(file)));
+        }
+        Some(link) if link.starts_with(&format!("{}:", deep_link::SCHEME)) => {
+            cli.deep_link = Some(link.to_string());
+        }
+        Some(arg) => match file_open::parse_arg(arg) {
+            Some(file) => {
+                cli.files.push(file);
+                for arg in positional.by_ref() {
+                    let file = file_open::parse_arg(&arg)
+                        .ok_or_else(|| format!("unexpected argument `{arg}`"))?;
+                    cli.files.push(file);
+                }
+            }
+            None => return Err(format!("unknown command `{arg}`")),
+        },
+    }
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument `{extra}`"));
+    }
+
+    Ok(Parsed::Run(cli))
+}
+
+/// Profile names become directory names, so keep them to a safe charset.
+fn parse_profile(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "invalid profile `{name}`: use letters, digits, `-` and `_`"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_ids_match_the_web_app() {
+        let source = include_str!("../../src/config/appIds.ts");
+        let list = source
+            .split_once("appIds = [")
+            .and_then(|(_, rest)| rest.split_once(']'))
+            .map(|(list, _)| list)
+            .expect("appIds.ts should declare `appIds = [...]`");
+        let web: Vec<&str> = list
+            .split(',')
+            .map(|id| id.trim().trim_matches('"'))
+            .filter(|id| !id.is_empty())
+            .collect();
+        assert_eq!(web, APP_IDS);
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn run(line: &str) -> Cli {
+        match parse(args(line)) {
+            Ok(Parsed::Run(cli)) => cli,
+            other => panic!("`{line}` parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_options() {
+        let cases: &[(&str, Cli)] = &[
+            ("", Cli::default()),
+            (
+                "--remote --origin https://os.example.com",
+                Cli {
+                    launch_mode: Some(LaunchMode::Remote),
+                    origin: Some("https://os.example.com".to_string()),
+                    ..Cli::default()
+                },
+            ),
+            (
+                "--origin=https://os.example.com/a=b --offline",
+                Cli {
+                    launch_mode: Some(LaunchMode::Bundled),
+                    origin: Some("https://os.example.com/a=b".to_string()),
+                    ..Cli::default()
+                },
+            ),
+            (
+                "--bundled --kiosk --new-window --profile=work",
+                Cli {
+                    launch_mode: Some(LaunchMode::Bundled),
+                    profile: Some("work".to_string()),
+                    new_window: true,
+                    kiosk: true,
+                    ..Cli::default()
+                },
+            ),
+            ("-psn_0_12345", Cli::default()),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(&run(line), expected, "{line}");
+        }
+        assert_eq!(parse(args("--kiosk -h")), Ok(Parsed::Help));
+        assert_eq!(parse(args("--version")), Ok(Parsed::Version));
+        assert_eq!(parse(args("-V")), Ok(Parsed::Version));
+    }
+
+    #[test]
+    fn parses_commands() {
+        let cli = run("--new-window open textedit /Documents/notes.md");
+        assert_eq!(
+            cli.launch_request(),
+            Some(LaunchRequest {
+                app_id: "textedit".to_string(),
+                path: Some("/Documents/notes.md".to_string()),
+                new_window: true,
+            })
+        );
+        assert_eq!(run("open finder").open, Some(("finder".to_string(), None)));
+        assert_eq!(run("").launch_request(), None);
+
+        assert_eq!(
+            run("syaos://applet/abc").deep_link.as_deref(),
+            Some("syaos://applet/abc")
+        );
+        assert_eq!(
+            run("notes.md doom.jsdos").files,
+            [PathBuf::from("notes.md"), PathBuf::from("doom.jsdos")]
+        );
+        assert_eq!(
+            parse(args("applet Clock.app")),
+            Ok(Parsed::InspectApplet(PathBuf::from("Clock.app")))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        let cases = [
+            ("--origin", "--origin needs a value"),
+            ("--profile", "--profile needs a value"),
+            (
+                "--profile ../x",
+                "invalid profile `../x`: use letters, digits, `-` and `_`",
+            ),
+            ("--frobnicate", "unknown option `--frobnicate`"),
+            ("--new-window=foo", "`--new-window` doesn't take a value"),
+            ("--offline=no", "`--offline` doesn't take a value"),
+            ("--help=me", "`--help` doesn't take a value"),
+            ("open", "`open` needs an app id"),
+            ("open calculator", "unknown app `calculator`"),
+            ("open finder / extra", "unexpected argument `extra`"),
+            ("applet", "`applet` needs a file"),
+            ("applet a.app b.app", "unexpected argument `b.app`"),
+            ("syaos://applet/abc extra", "unexpected argument `extra`"),
+            ("notes.md README", "unexpected argument `README`"),
+            ("frobnicate", "unknown command `frobnicate`"),
+        ];
+        for (line, error) in cases {
+            assert_eq!(parse(args(line)), Err(error.to_string()), "{line}");
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::kiosk::KioskConfig;
+use crate::launch::LaunchMode;
+
+/// Shell settings read from `shell.json` in the app config directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShellConfig {
+    pub launch_mode: Option<LaunchMode>,
+    /// Remote origin for self-hosted deployments; see [`crate::launch::DEFAULT_ORIGIN`].
+    pub origin: Option<String>,
+    /// Port for serving the bundled app; see [`crate::localhost::DEFAULT_PORT`].
+    pub localhost_port: Option<u16>,
+    pub kiosk: KioskConfig,
+    /// Where the Finder tree is stored; see [`crate::vfs::default_root`].
+    pub home_dir: Option<PathBuf>,
+}
+
+impl ShellConfig {
+    pub const FILE_NAME: &'static str = "shell.json";
+
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        match app.path().app_config_dir() {
+            Ok(dir) => Self::load_from(&dir.join(Self::FILE_NAME)),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Missing files give the defaults; malformed ones are reported and ignored.
+    pub fn load_from(path: &Path) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            eprintln!("ignoring invalid {}: {err}", path.display());
+            Self::default()
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime, State, Url};
+
+use crate::event_queue::EventQueue;
+use crate::kiosk;
+
+/// URL scheme registered for the app (see `plugins.deep-link` in `tauri.conf.json`).
+pub const SCHEME: &str = "syaos";
+
+/// Event carrying a [`DeepLink`] once the frontend is listening.
+pub const DEEP_LINK_EVENT: &str = "shell://deep-link";
+
+/// Longest id accepted in a link; share, song and room ids are all far shorter.
+const MAX_ID_LEN: usize = 128;
+
+/// A `syaos://` link, routed to the app that can open it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLink {
+    pub url: String,
+    #[serde(flatten)]
+    pub route: Route,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "route", rename_all = "camelCase")]
+pub enum Route {
+    /// `syaos://applet/<shareId>`
+    #[serde(rename_all = "camelCase")]
+    Applet { share_id: String },
+    /// `syaos://ipod/<songId>`
+    #[serde(rename_all = "camelCase")]
+    Ipod { song_id: String },
+    /// `syaos://chats/room/<roomId>`
+    #[serde(rename_all = "camelCase")]
+    ChatRoom { room_id: String },
+}
+
+impl Route {
+    /// The app that opens this route.
+    pub fn app_id(&self) -> &'static str {
+        match self {
+            Route::Applet { .. } => "applet-viewer",
+            Route::Ipod { .. } => "ipod",
+            Route::ChatRoom { .. } => "chats",
+        }
+    }
+}
+
+pub fn parse(value: &str) -> Result<DeepLink, String> {
+    let url = Url::parse(value).map_err(|err| err.to_string())?;
+    if url.scheme() != SCHEME {
+        return Err(format!("not a {SCHEME}:// link"));
+    }
+
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let route = match (url.host_str(), segments.as_slice()) {
+        (Some("applet"), [share_id]) => Route::Applet {
+            share_id: parse_id(share_id)?,
+        },
+        (Some("ipod"), [song_id]) => Route::Ipod {
+            song_id: parse_id(song_id)?,
+        },
+        (Some("chats"), ["room", room_id]) => Route::ChatRoom {
+            room_id: parse_id(room_id)?,
+        },
+        _ => return Err("unknown route".to_string()),
+    };
+
+    Ok(DeepLink {
+        url: value.to_string(),
+        route,
+    })
+}
+
+/// Ids end up in API paths on the frontend, so only allow URL-safe ones.
+fn parse_id(id: &str) -> Result<String, String> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(id.to_string())
+    } else {
+        Err(format!("invalid id `{id}`"))
+    }
+}
+
+/// Parses `value` and queues it for the frontend, logging links it can't route.
+pub fn dispatch<R: Runtime>(app: &AppHandle<R>, value: &str) {
+    match parse(value) {
+        Ok(link) if !kiosk::allows(app, link.route.app_id()) => {
+            eprintln!("ignoring deep link {value}: app not allowed in kiosk mode")
+        }
+        Ok(link) => app.state::<EventQueue<DeepLink>>().push(app, link),
+        Err(err) => eprintln!("ignoring deep link {value}: {err}"),
+    }
+}
+
+/// Same as [`crate::cli::take_launch_requests`], for `syaos://` links.
+#[tauri::command]
+pub fn take_deep_links(queue: State<'_, EventQueue<DeepLink>>) -> Vec<DeepLink> {
+    queue.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_links() {
+        let cases = [
+            (
+                "syaos://applet/abc-123_X",
+                Route::Applet {
+                    share_id: "abc-123_X".to_string(),
+                },
+            ),
+            (
+                "syaos://ipod/dQw4w9WgXcQ",
+                Route::Ipod {
+                    song_id: "dQw4w9WgXcQ".to_string(),
+                },
+            ),
+            (
+                "syaos://chats/room/lobby",
+                Route::ChatRoom {
+                    room_id: "lobby".to_string(),
+                },
+            ),
+            // Trailing slashes, queries and fragments don't matter
+            (
+                "syaos://ipod/song1/?t=30#x",
+                Route::Ipod {
+                    song_id: "song1".to_string(),
+                },
+            ),
+        ];
+        for (url, route) in cases {
+            let link = parse(url).unwrap();
+            assert_eq!(link.route, route, "{url}");
+            assert_eq!(link.url, url);
+        }
+        assert_eq!(
+            parse("syaos://chats/room/lobby").unwrap().route.app_id(),
+            "chats"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_links() {
+        let long = format!("syaos://ipod/{}", "a".repeat(MAX_ID_LEN + 1));
+        let cases = [
+            ("not a url", None),
+            ("https://os.ryo.lu/applet/abc", Some("not a syaos:// link")),
+            ("syaos://settings/abc", Some("unknown route")),
+            ("syaos://applet", Some("unknown route")),
+            ("syaos://applet/a/b", Some("unknown route")),
+            ("syaos://chats/abc", Some("unknown route")),
+            ("syaos://chats/dm/abc", Some("unknown route")),
+            ("syaos://applet/..%2Fadmin", Some("invalid id `..%2Fadmin`")),
+            ("syaos://ipod/a%20b", Some("invalid id `a%20b`")),
+            ("syaos://ipod/caf%C3%A9", Some("invalid id `caf%C3%A9`")),
+            (long.as_str(), None),
+        ];
+        for (url, error) in cases {
+            let result = parse(url);
+            assert!(result.is_err(), "{url}");
+            if let Some(error) = error {
+                assert_eq!(result.unwrap_err(), error, "{url}");
+            }
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::Serialize;
+use serde_json::Map;
+use tauri::ipc::Response;
+use tauri::{
+    AppHandle, DragDropEvent, Emitter, Manager, Runtime, State, WebviewWindow, WindowEvent,
+};
+
+use crate::search::SearchIndex;
+use crate::vfs::{self, FileSystemItem, NewItem, Vfs};
+
+/// Emitted while host files are dragged over the window, with the pointer
+/// [`Position`].
+pub const DRAG_OVER_EVENT: &str = "drop://over";
+
+/// Emitted when the drag leaves the window or is cancelled.
+pub const DRAG_LEAVE_EVENT: &str = "drop://leave";
+
+/// Emitted with a [`FileDrop`] when host files are dropped on the window.
+pub const DROP_EVENT: &str = "drop://drop";
+
+/// Largest item (directories count their whole tree) accepted from a drop.
+pub const MAX_SIZE: u64 = 512 * 1024 * 1024;
+
+/// How much of a file is read to sniff its type.
+const SNIFF_LEN: usize = 512;
+
+/// A point in the window, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A dropped file or directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedItem {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_directory: bool,
+    pub size: u64,
+    /// Sniffed from the content, falling back to the extension.
+    pub mime: String,
+    /// The Finder `type`, as `getFileTypeFromExtension` gives it.
+    #[serde(rename = "type")]
+    pub file_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rejected {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Payload of [`DROP_EVENT`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileDrop {
+    pub items: Vec<DroppedItem>,
+    pub rejected: Vec<Rejected>,
+    pub position: Position,
+}
+
+/// Paths the user dropped this session. `drop_read` and `drop_import` only
+/// accept these, so the web app can't reach other host files through them.
+#[derive(Default)]
+pub struct Dropped {
+    paths: Mutex<HashSet<PathBuf>>,
+}
+
+impl Dropped {
+    fn check(&self, path: &Path) -> Result<(), String> {
+        if self.paths.lock().unwrap().contains(path) {
+            Ok(())
+        } else {
+            Err(format!("{} was not dropped", path.display()))
+        }
+    }
+}
+
+/// MIME type from a file's first bytes, or its extension when they aren't
+/// conclusive.
+pub fn sniff(head: &[u8], name: &str) -> &'static str {
+    let starts = |magic: &[u8]| head.starts_with(magic);
+    let riff = |form: &[u8]| starts(b"RIFF") && head.get(8..12) == Some(form);
+    let sniffed = if starts(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if starts(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if starts(b"GIF87a") || starts(b"GIF89a") {
+        Some("image/gif")
+    } else if riff(b"WEBP") {
+        Some("image/webp")
+    } else if starts(b"BM") && head.len() >= 14 {
+        Some("image/bmp")
+    } else if riff(b"WAVE") {
+        Some("audio/wav")
+    } else if starts(b"ID3") || (head.len() >= 2 && head[0] == 0xff && head[1] & 0xe0 == 0xe0) {
+        Some("audio/mpeg")
+    } else if starts(b"fLaC") {
+        Some("audio/flac")
+    } else if starts(b"OggS") {
+        Some("audio/ogg")
+    } else if head.get(4..8) == Some(b"ftyp") {
+        Some(match head.get(8..12) {
+            Some(b"M4A ") => "audio/mp4",
+            Some(b"qt  ") => "video/quicktime",
+            _ => "video/mp4",
+        })
+    } else if starts(b"\x1a\x45\xdf\xa3") {
+        Some("video/webm")
+    } else if starts(b"%PDF-") {
+        Some("application/pdf")
+    } else if starts(b"\x1f\x8b") {
+        Some("application/gzip")
+    } else if starts(b"PK\x03\x04") {
+        Some("application/zip")
+    } else {
+        None
+    };
+    sniffed.unwrap_or_else(|| by_extension(name, utf8_prefix(head)))
+}
+
+/// Whether `head` looks like text: UTF-8 (up to a character cut off at
+/// the end) without NULs.
+fn utf8_prefix(head: &[u8]) -> bool {
+    !head.contains(&0)
+        && match std::str::from_utf8(head) {
+            Ok(_) => true,
+            Err(err) => err.error_len().is_none(),
+        }
+}
+
+fn by_extension(name: &str, text: bool) -> &'static str {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "md" | "markdown" => "text/markdown",
+        "svg" => "image/svg+xml",
+        "json" => "application/json",
+        "rtf" => "application/rtf",
+        "csv" => "text/csv",
+        "app" => "text/html",
+        _ if text => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Total size of the files under `path`, stopping once it passes `limit`.
+fn tree_size(path: &Path, limit: u64) -> io::Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let kind = entry.file_type()?;
+        if kind.is_symlink() {
+            continue;
+        }
+        total += if kind.is_dir() {
+            tree_size(&entry.path(), limit - total.min(limit))?
+        } else {
+            entry.metadata()?.len()
+        };
+        if total > limit {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+/// Resolves and describes a dropped path, or says why it can't be used.
+pub fn inspect(path: &Path) -> Result<DroppedItem, String> {
+    let path = path.canonicalize().map_err(|err| err.to_string())?;
+    let metadata = fs::metadata(&path).map_err(|err| err.to_string())?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("the name is not valid UTF-8")?
+        .to_string();
+
+    let (size, mime) = if metadata.is_dir() {
+        let size = tree_size(&path, MAX_SIZE).map_err(|err| err.to_string())?;
+        (size, "inode/directory")
+    } else {
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        File::open(&path)
+            .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut head))
+            .map_err(|err| err.to_string())?;
+        (metadata.len(), sniff(&head, &name))
+    };
+    if size > MAX_SIZE {
+        return Err(format!(
+            "larger than the {} MB limit",
+            MAX_SIZE / 1024 / 1024
+        ));
+    }
+    Ok(DroppedItem {
+        file_type: if metadata.is_dir() {
+            "directory".to_string()
+        } else {
+            vfs::file_type(&name).to_string()
+        },
+        is_directory: metadata.is_dir(),
+        path,
+        name,
+        size,
+        mime: mime.to_string(),
+    })
+}
+
+/// Where `drop_import` puts items without an explicit destination.
+fn default_destination(item: &DroppedItem) -> &'static str {
+    if item.mime.starts_with("image/") {
+        "/Images"
+    } else {
+        "/Documents"
+    }
+}
+
+/// `name` in `directory`, numbered (`a 2.png`, `a 3.png`, ...) past
+/// anything already there.
+fn unique_path(vfs: &Vfs, directory: &str, name: &str) -> String {
+    let directory = directory.trim_end_matches('/');
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|counter| match counter {
+            1 => format!("{directory}/{name}"),
+            _ => format!("{directory}/{stem} {counter}{extension}"),
+        })
+        .find(|path| !vfs.exists(path))
+        .unwrap()
+}
+
+/// Copies the host file or directory `source` into the virtual `directory`,
+/// returning every item created.
+pub fn import(vfs: &Vfs, source: &Path, directory: &str) -> Result<Vec<FileSystemItem>, String> {
+    let name = source
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{} has no name", source.display()))?;
+    let path = unique_path(vfs, directory, name);
+    let is_directory = source.is_dir();
+    let mut extra = Map::new();
+    let file_type = if is_directory {
+        "directory"
+    } else {
+        vfs::file_type(name)
+    };
+    extra.insert("type".into(), file_type.into());
+
+    let content = if is_directory {
+        Vec::new()
+    } else {
+        fs::read(source).map_err(|err| format!("{}: {err}", source.display()))?
+    };
+    let item = vfs.write(
+        NewItem {
+            path: path.clone(),
+            is_directory,
+            extra,
+        },
+        &content,
+    )?;
+    let mut items = vec![item];
+    if is_directory {
+        let entries = fs::read_dir(source).map_err(|err| format!("{}: {err}", source.display()))?;
+        for entry in entries.flatten() {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if hidden || entry.file_type().is_ok_and(|kind| kind.is_symlink()) {
+                continue;
+            }
+            items.extend(import(vfs, &entry.path(), &path)?);
+        }
+    }
+    Ok(items)
+}
+
+/// Imports `items` into `destination`, or by default images into `/Images`
+/// and everything else into `/Documents` (creating them if needed).
+pub fn import_all(
+    vfs: &Vfs,
+    items: &[DroppedItem],
+    destination: Option<&str>,
+) -> Result<Vec<FileSystemItem>, String> {
+    let mut imported = Vec::new();
+    for item in items {
+        let directory = match destination {
+            Some(destination) => destination,
+            None => {
+                let directory = default_destination(item);
+                if !vfs.exists(directory) {
+                    let mut extra = Map::new();
+                    extra.insert("type".into(), "directory".into());
+                    vfs.write(
+                        NewItem {
+                            path: directory.to_string(),
+                            is_directory: true,
+                            extra,
+                        },
+                        &[],
+                    )?;
+                }
+                directory
+            }
+        };
+        imported.extend(import(vfs, &item.path, directory)?);
+    }
+    Ok(imported)
+}
+
+/// The paths of `imported` items that aren't inside another of them;
+/// crawling those covers everything an import made.
+fn import_roots(imported: &[FileSystemItem]) -> Vec<&str> {
+    let paths: HashSet<&str> = imported.iter().map(|item| item.path.as_str()).collect();
+    paths
+        .iter()
+        .filter(|path| !paths.contains(vfs::parent_path(path)))
+        .copied()
+        .collect()
+}
+
+/// Reports host files dragged onto `window` as [`DRAG_OVER_EVENT`],
+/// [`DRAG_LEAVE_EVENT`] and [`DROP_EVENT`]s.
+pub fn watch<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    let handle = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        let WindowEvent::DragDrop(event) = event else {
+            return;
+        };
+        let scale = tracked.scale_factor().unwrap_or(1.0);
+        let emitted = match event {
+            DragDropEvent::Enter { position, .. } | DragDropEvent::Over { position } => {
+                let position = position.to_logical::<f64>(scale);
+                handle.emit(
+                    DRAG_OVER_EVENT,
+                    Position {
+                        x: position.x,
+                        y: position.y,
+                    },
+                )
+            }
+            DragDropEvent::Drop { paths, position } => {
+                let position = position.to_logical::<f64>(scale);
+                let position = Position {
+                    x: position.x,
+                    y: position.y,
+                };
+                // Sizing a dropped directory walks its whole tree
+                let (handle, paths) = (handle.clone(), paths.clone());
+                thread::spawn(move || report_drop(&handle, &paths, position));
+                Ok(())
+            }
+            DragDropEvent::Leave => handle.emit(DRAG_LEAVE_EVENT, ()),
+            _ => Ok(()),
+        };
+        if let Err(err) = emitted {
+            eprintln!("failed to emit a drag-and-drop event: {err}");
+        }
+    });
+}
+
+/// Inspects dropped `paths`, lets the web app use the good ones and emits
+/// them as a [`DROP_EVENT`].
+fn report_drop<R: Runtime>(app: &AppHandle<R>, paths: &[PathBuf], position: Position) {
+    let mut drop = FileDrop {
+        items: Vec::new(),
+        rejected: Vec::new(),
+        position,
+    };
+    for path in paths {
+        match inspect(path) {
+            Ok(item) => drop.items.push(item),
+            Err(reason) => drop.rejected.push(Rejected {
+                path: path.clone(),
+                reason,
+            }),
+        }
+    }
+    if let Some(dropped) = app.try_state::<Dropped>() {
+        dropped
+            .paths
+            .lock()
+            .unwrap()
+            .extend(drop.items.iter().map(|item| item.path.clone()));
+    }
+    if let Err(err) = app.emit(DROP_EVENT, drop) {
+        eprintln!("failed to emit a drag-and-drop event: {err}");
+    }
+}
+
+/// Raw content of a dropped file.
+#[tauri::command]
+pub async fn drop_read(path: PathBuf, dropped: State<'_, Dropped>) -> Result<Response, String> {
+    dropped.check(&path)?;
+    if path.is_dir() {
+        return Err(format!("{} is a directory", path.display()));
+    }
+    fs::read(&path)
+        .map(Response::new)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Copies dropped items into syaOS Home (or a mounted volume), as
+/// [`import_all`] does, and indexes them for search. Async, so copying
+/// doesn't block the main thread.
+#[tauri::command]
+pub async fn drop_import(
+    paths: Vec<PathBuf>,
+    destination: Option<String>,
+    dropped: State<'_, Dropped>,
+    vfs: State<'_, Vfs>,
+    search: State<'_, SearchIndex>,
+) -> Result<Vec<FileSystemItem>, String> {
+    let items = paths
+        .iter()
+        .map(|path| {
+            dropped.check(path)?;
+            inspect(path)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let imported = import_all(&vfs, &items, destination.as_deref())?;
+    for path in import_roots(&imported) {
+        search.crawl(&vfs, path);
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mounts::Mounts;
+
+    use super::*;
+
+    #[test]
+    fn sniffs_content_before_extensions() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n....", "photo.jpg"), "image/png");
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0", "x"), "image/jpeg");
+        assert_eq!(sniff(b"RIFF\0\0\0\0WAVEfmt ", "a.bin"), "audio/wav");
+        assert_eq!(sniff(b"ID3\x04", "song"), "audio/mpeg");
+        assert_eq!(sniff(b"\0\0\0\x20ftypM4A ", "a"), "audio/mp4");
+        assert_eq!(sniff(b"\0\0\0\x20ftypisom", "a"), "video/mp4");
+        assert_eq!(sniff(b"\x1f\x8b\x08", "game.jsdos"), "application/gzip");
+        assert_eq!(sniff(b"# Notes\n", "notes.md"), "text/markdown");
+        assert_eq!(sniff(b"hello", "readme"), "text/plain");
+        // A multi-byte character cut off by the sniffing window
+        assert_eq!(
+            sniff("caf\u{e9}".as_bytes()[..4].as_ref(), "a"),
+            "text/plain"
+        );
+        assert_eq!(sniff(b"\0\x01\x02", "blob"), "application/octet-stream");
+    }
+
+    #[test]
+    fn inspects_files_and_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        fs::create_dir(dir.path().join("Album")).unwrap();
+        fs::write(dir.path().join("Album/one.mp3"), b"ID3....").unwrap();
+
+        let file = inspect(&dir.path().join("a.png")).unwrap();
+        assert_eq!(
+            (file.mime.as_str(), file.file_type.as_str(), file.size),
+            ("image/png", "png", 8)
+        );
+        let album = inspect(&dir.path().join("Album")).unwrap();
+        assert!(album.is_directory);
+        assert_eq!((album.mime.as_str(), album.size), ("inode/directory", 7));
+        assert!(inspect(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn imports_copies_without_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let vfs = Vfs::open(
+            dir.path().join("home"),
+            Mounts::load(dir.path().join("mounts.json")),
+            None,
+        )
+        .unwrap();
+        let host = dir.path().join("host");
+        fs::create_dir_all(host.join("Trip/.cache")).unwrap();
+        fs::write(host.join("a.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        fs::write(host.join("Trip/b.txt"), b"b").unwrap();
+
+        let png = inspect(&host.join("a.png")).unwrap();
+        for _ in 0..2 {
+            import_all(&vfs, std::slice::from_ref(&png), None).unwrap();
+        }
+        assert!(vfs.exists("/Images/a.png") && vfs.exists("/Images/a 2.png"));
+        assert_eq!(vfs.read("/Images/a 2.png").unwrap(), b"\x89PNG\r\n\x1a\n");
+
+        let trip = inspect(&host.join("Trip")).unwrap();
+        let items = import_all(&vfs, &[trip], Some("/Images")).unwrap();
+        let paths: Vec<_> = items.iter().map(|item| item.path.as_str()).collect();
+        assert_eq!(paths, ["/Images/Trip", "/Images/Trip/b.txt"]);
+        assert_eq!(import_roots(&items), ["/Images/Trip"]);
+        assert_eq!(items[1].extra["type"], "text");
+        assert!(host.join("Trip/b.txt").exists());
+    }
+
+    #[test]
+    fn only_dropped_paths_are_readable() {
+        let dropped = Dropped::default();
+        dropped.paths.lock().unwrap().insert(PathBuf::from("/a"));
+        assert!(dropped.check(Path::new("/a")).is_ok());
+        assert!(dropped.check(Path::new("/etc/passwd")).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+
+use crate::shell_info::BUILD_COMMIT;
+
+/// Name of the file startup failures are appended to, in the app log dir.
+pub const LOG_FILE_NAME: &str = "syaos.log";
+
+const RETRY: &str = "Retry";
+const OPEN_OFFLINE: &str = "Open Offline";
+const COPY_DIAGNOSTICS: &str = "Copy Diagnostics";
+
+/// Ways the shell can fail to start.
+#[derive(Debug)]
+pub enum ShellError {
+    /// The app data or config directory couldn't be resolved.
+    Path(tauri::Error),
+    /// Setting up the single-instance lock failed.
+    Instance(io::Error),
+    /// `--origin`, `SYAOS_ORIGIN` or `shell.json` named an unusable origin.
+    Origin(String),
+    /// The bundled app couldn't be served on localhost.
+    Localhost(io::Error),
+    /// The localhost origin the bundled app is served on didn't parse.
+    LocalhostUrl(String),
+    /// Granting the main window its capabilities failed.
+    Capability(tauri::Error),
+    /// `tauri.conf.json` has no usable `main` window.
+    WindowConfig(String),
+    /// Creating the main window failed.
+    Window(tauri::Error),
+    /// Tauri itself couldn't start, e.g. a plugin failed to initialize.
+    Runtime(tauri::Error),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::Path(err) => write!(f, "couldn't locate the app data directory: {err}"),
+            ShellError::Instance(err) => write!(f, "couldn't set up the instance lock: {err}"),
+            ShellError::Origin(err) => write!(f, "invalid origin: {err}"),
+            ShellError::Localhost(err) => {
+                write!(f, "couldn't serve the bundled app on localhost: {err}")
+            }
+            ShellError::LocalhostUrl(err) => {
+                write!(f, "invalid localhost origin for the bundled app: {err}")
+            }
+            ShellError::Capability(err) => {
+                write!(f, "couldn't grant the main window its permissions: {err}")
+            }
+            ShellError::WindowConfig(err) => write!(f, "invalid window configuration: {err}"),
+            ShellError::Window(err) => write!(f, "couldn't create the main window: {err}"),
+            ShellError::Runtime(err) => write!(f, "couldn't start: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+/// Report text for the dialog, the log file and the clipboard.
+fn diagnostics(err: &ShellError) -> String {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    format!(
+        "error: {err}\n\
+         detail: {err:?}\n\
+         version: {} ({BUILD_COMMIT})\n\
+         platform: {} {}\n\
+         arguments: {}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        args.join(" "),
+    )
+}
+
+/// Where [`LOG_FILE_NAME`] lives: the same directory as Tauri's
+/// `app_log_dir`, worked out without an `AppHandle` since startup may have
+/// failed before there is one.
+pub fn log_dir(identifier: &str) -> PathBuf {
+    let dir = if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|home| home.join("Library/Logs").join(identifier))
+    } else {
+        dirs::data_local_dir().map(|data| data.join(identifier).join("logs"))
+    };
+    dir.unwrap_or_else(std::env::temp_dir)
+}
+
+fn write_log(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(LOG_FILE_NAME);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "[{timestamp}] startup failed\n{report}\n")?;
+    Ok(path)
+}
+
+/// Starts a fresh copy of the shell with the same arguments plus `extra`.
+fn relaunch(extra: &[&str]) -> io::Result<()> {
+    Command::new(std::env::current_exe()?)
+        .args(std::env::args().skip(1))
+        .args(extra)
+        .spawn()
+        .map(drop)
+}
+
+fn copy_to_clipboard(text: &str) -> Result<(), arboard::Error> {
+    arboard::Clipboard::new()?.set_text(text)
+}
+
+/// Logs `err`, shows it in a native dialog offering to retry, open the
+/// bundled app instead or copy diagnostics, and exits.
+pub fn fail(err: &ShellError, log_dir: &Path) -> ! {
+    let report = diagnostics(err);
+    eprintln!("syaos: {err}");
+    let log_note = match write_log(log_dir, &report) {
+        Ok(path) => format!("Details were written to {}.", path.display()),
+        Err(log_err) => {
+            eprintln!("failed to write {LOG_FILE_NAME}: {log_err}");
+            String::new()
+        }
+    };
+
+    let mut description = format!("{err}.\n\n{log_note}");
+    // Bounded so a platform that maps closing the dialog to the last
+    // button can't keep it open forever
+    for _ in 0..3 {
+        let choice = MessageDialog::new()
+            .set_level(MessageLevel::Error)
+            .set_title("syaOS couldn't start")
+            .set_description(&description)
+            .set_buttons(MessageButtons::YesNoCancelCustom(
+                RETRY.to_string(),
+                OPEN_OFFLINE.to_string(),
+                COPY_DIAGNOSTICS.to_string(),
+            ))
+            .show();
+
+        let relaunched = match choice {
+            MessageDialogResult::Custom(choice) if choice == RETRY => relaunch(&[]),
+            MessageDialogResult::Custom(choice) if choice == OPEN_OFFLINE => {
+                relaunch(&["--offline"])
+            }
+            MessageDialogResult::Custom(choice) if choice == COPY_DIAGNOSTICS => {
+                // The dialog stays up so the clipboard owner (this process)
+                // is still around when the text is pasted
+                description = match copy_to_clipboard(&report) {
+                    Ok(()) => format!("{err}.\n\nDiagnostics were copied to the clipboard."),
+                    Err(copy_err) => format!("{err}.\n\nCouldn't copy diagnostics: {copy_err}"),
+                };
+                continue;
+            }
+            _ => Ok(()),
+        };
+        if let Err(relaunch_err) = relaunched {
+            eprintln!("failed to relaunch: {relaunch_err}");
+        }
+        break;
+    }
+    std::process::exit(1);
+}
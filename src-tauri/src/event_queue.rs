@@ -0,0 +1,46 @@
+use std::mem;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Holds shell-to-frontend messages until the frontend has loaded and asked
+/// for them, then emits later ones directly as `event`.
+pub struct EventQueue<T> {
+    event: &'static str,
+    state: Mutex<QueueState<T>>,
+}
+
+struct QueueState<T> {
+    pending: Vec<T>,
+    frontend_ready: bool,
+}
+
+impl<T: Serialize + Clone> EventQueue<T> {
+    pub fn new(event: &'static str) -> Self {
+        Self {
+            event,
+            state: Mutex::new(QueueState {
+                pending: Vec::new(),
+                frontend_ready: false,
+            }),
+        }
+    }
+
+    pub fn push<R: Runtime>(&self, app: &AppHandle<R>, item: T) {
+        let mut state = self.state.lock().unwrap();
+        if !state.frontend_ready {
+            state.pending.push(item);
+        } else if let Err(err) = app.emit_to("main", self.event, &item) {
+            eprintln!("failed to emit {}: {err}", self.event);
+        }
+    }
+
+    /// Called once the frontend can handle `T`s: returns the ones queued
+    /// during startup and emits later ones directly.
+    pub fn take(&self) -> Vec<T> {
+        let mut state = self.state.lock().unwrap();
+        state.frontend_ready = true;
+        mem::take(&mut state.pending)
+    }
+}
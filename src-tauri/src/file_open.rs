@@ -0,0 +1,235 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Manager, Runtime, State, Url};
+
+use crate::event_queue::EventQueue;
+use crate::host_files::{FileKind, HostFile, HostFiles};
+use crate::kiosk;
+
+/// Event carrying an [`OpenedFile`] once the frontend is listening.
+pub const OPEN_FILE_EVENT: &str = "shell://open-file";
+
+/// Largest file handed to an app; `.jsdos` bundles are the big ones.
+pub const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Extensions the bundles register, with the app that opens them.
+const APPS: &[(&str, &str)] = &[
+    ("app", "applet-viewer"),
+    ("gz", "applet-viewer"),
+    ("md", "textedit"),
+    ("txt", "textedit"),
+    ("jsdos", "pc"),
+];
+
+/// A host file opened with syaOS from the OS, delivered as its app id and a
+/// path the frontend reads through `opened_file_read`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedFile {
+    pub app_id: String,
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    pub new_window: bool,
+    /// For TextEdit documents, a handle saves are written back through.
+    pub host_file: Option<HostFile>,
+}
+
+/// Paths opened from the OS this session. `opened_file_read` only accepts
+/// these, so the web app can't reach other host files through it.
+#[derive(Default)]
+pub struct Opened {
+    paths: Mutex<HashSet<PathBuf>>,
+}
+
+impl Opened {
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.lock().unwrap().contains(path)
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    Some(path.extension()?.to_str()?.to_ascii_lowercase())
+}
+
+/// Whether `path` has an extension the shell opens.
+pub fn accepts(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| APPS.iter().any(|(known, _)| *known == ext))
+}
+
+/// A file argument as the OS passes it: a path, or a `file://` URL from
+/// launchers that use `%U`.
+pub fn parse_arg(arg: &str) -> Option<PathBuf> {
+    if arg.starts_with("file:") {
+        return Url::parse(arg).ok()?.to_file_path().ok();
+    }
+    let path = PathBuf::from(arg);
+    accepts(&path).then_some(path)
+}
+
+/// The app that opens a file with `name`, given its first bytes. `.gz`
+/// is shared with ordinary archives, so only gzip data is taken as an
+/// applet.
+pub fn app_for(name: &Path, head: &[u8]) -> Option<&'static str> {
+    let ext = extension(name)?;
+    if ext == "gz" && !head.starts_with(&[0x1f, 0x8b]) {
+        return None;
+    }
+    APPS.iter()
+        .find(|(known, _)| *known == ext)
+        .map(|(_, app_id)| *app_id)
+}
+
+fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(2);
+    File::open(path)?.take(2).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Checks `path` (relative ones against `cwd`) and picks its app.
+pub fn inspect(path: &Path, cwd: Option<&Path>, new_window: bool) -> Result<OpenedFile, String> {
+    let path = match cwd {
+        Some(cwd) if path.is_relative() => cwd.join(path),
+        _ => path.to_path_buf(),
+    };
+    let path = path
+        .canonicalize()
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    let metadata = fs::metadata(&path).map_err(|err| err.to_string())?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    if metadata.len() > MAX_SIZE {
+        return Err(format!(
+            "{} is larger than {} MiB",
+            path.display(),
+            MAX_SIZE / 1024 / 1024
+        ));
+    }
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("the name is not valid UTF-8")?
+        .to_string();
+    let head = read_head(&path).map_err(|err| err.to_string())?;
+    let app_id = app_for(&path, &head).ok_or_else(|| format!("no app opens {name}"))?;
+
+    Ok(OpenedFile {
+        app_id: app_id.to_string(),
+        size: metadata.len(),
+        path,
+        name,
+        new_window,
+        host_file: None,
+    })
+}
+
+/// Grants the frontend access to each of `paths` and queues it for its
+/// app, skipping (with a warning) ones that can't be opened.
+pub fn open<R: Runtime>(
+    app: &AppHandle<R>,
+    paths: &[PathBuf],
+    cwd: Option<&Path>,
+    new_window: bool,
+) {
+    for path in paths {
+        let mut file = match inspect(path, cwd, new_window) {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("ignoring {}: {err}", path.display());
+                continue;
+            }
+        };
+        if !kiosk::allows(app, &file.app_id) {
+            eprintln!(
+                "ignoring {}: {} not allowed in kiosk mode",
+                file.name, file.app_id
+            );
+            continue;
+        }
+        if file.app_id == "textedit" {
+            file.host_file = app.try_state::<HostFiles>().and_then(|host_files| {
+                host_files
+                    .grant(&file.path, FileKind::Text)
+                    .map_err(|err| eprintln!("can't save back to {}: {err}", file.name))
+                    .ok()
+            });
+        }
+        app.state::<Opened>()
+            .paths
+            .lock()
+            .unwrap()
+            .insert(file.path.clone());
+        app.state::<EventQueue<OpenedFile>>().push(app, file);
+    }
+}
+
+/// Raw content of a file opened from the OS.
+#[tauri::command]
+pub async fn opened_file_read(
+    path: PathBuf,
+    opened: State<'_, Opened>,
+) -> Result<Response, String> {
+    if !opened.contains(&path) {
+        return Err(format!("{} was not opened", path.display()));
+    }
+    fs::read(&path)
+        .map(Response::new)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Same as [`crate::cli::take_launch_requests`], for host files opened
+/// from the OS.
+#[tauri::command]
+pub fn take_opened_files(queue: State<'_, EventQueue<OpenedFile>>) -> Vec<OpenedFile> {
+    queue.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_apps_by_extension() {
+        let gzip = [0x1f, 0x8b];
+        assert_eq!(app_for(Path::new("Notes.MD"), b"# "), Some("textedit"));
+        assert_eq!(app_for(Path::new("doom.jsdos"), b"PK"), Some("pc"));
+        assert_eq!(
+            app_for(Path::new("Clock.app"), &gzip),
+            Some("applet-viewer")
+        );
+        assert_eq!(app_for(Path::new("clock.gz"), &gzip), Some("applet-viewer"));
+        assert_eq!(app_for(Path::new("clock.gz"), b"BZ"), None);
+        assert_eq!(app_for(Path::new("photo.png"), b""), None);
+    }
+
+    #[test]
+    fn parses_paths_and_file_urls() {
+        assert_eq!(parse_arg("notes.txt"), Some(PathBuf::from("notes.txt")));
+        assert_eq!(parse_arg("README"), None);
+        if cfg!(unix) {
+            assert_eq!(
+                parse_arg("file:///home/me/My%20Notes.md"),
+                Some(PathBuf::from("/home/me/My Notes.md"))
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_relative_paths_against_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.md"), "# Hi").unwrap();
+
+        let file = inspect(Path::new("notes.md"), Some(dir.path()), false).unwrap();
+        assert_eq!(file.app_id, "textedit");
+        assert_eq!(file.name, "notes.md");
+        assert_eq!(file.size, 4);
+        assert!(inspect(Path::new("missing.md"), Some(dir.path()), false).is_err());
+    }
+}
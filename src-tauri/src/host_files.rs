@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{Request, Response};
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::atomic;
+use crate::ipc_body;
+
+/// Name of the file recent host files are kept in, per profile.
+pub const FILE_NAME: &str = "recent-files.json";
+
+/// Header naming the open file a write goes to.
+pub const HANDLE_HEADER: &str = "x-syaos-handle";
+
+/// Header carrying a [`SaveAs`] as URI-encoded JSON.
+pub const SAVE_AS_HEADER: &str = "x-syaos-save-as";
+
+/// How many recent files are remembered.
+const MAX_RECENT: usize = 10;
+
+/// What an app opens and saves, which picks the dialog filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    /// TextEdit documents.
+    Text,
+    /// Paint images.
+    Image,
+}
+
+impl FileKind {
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Text => &["md", "txt", "html", "rtf"],
+            Self::Image => &["png", "jpg", "jpeg", "bmp"],
+        }
+    }
+
+    fn filter_name(self) -> &'static str {
+        match self {
+            Self::Text => "Documents",
+            Self::Image => "Images",
+        }
+    }
+
+    /// Whether `path` has one of this kind's extensions.
+    fn accepts(self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                self.extensions()
+                    .contains(&extension.to_ascii_lowercase().as_str())
+            })
+    }
+
+    /// [`FileKind::accepts`], as an error naming the extensions.
+    fn check(self, path: &Path) -> Result<(), String> {
+        if self.accepts(path) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} is not one of {}",
+                path.display(),
+                self.extensions().join(", ")
+            ))
+        }
+    }
+}
+
+/// A host file the user picked, which the web app reads and writes
+/// through `handle`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostFile {
+    pub handle: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: FileKind,
+    pub size: u64,
+    /// Milliseconds since the epoch.
+    pub modified_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: FileKind,
+    /// Milliseconds since the epoch.
+    pub opened_at: u64,
+}
+
+/// Where a new file is saved: the dialog filters and suggested name.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveAs {
+    pub kind: FileKind,
+    pub name: Option<String>,
+}
+
+/// The host files the web app may touch. Only paths the user picked in a
+/// dialog (or picked before, through the recent list) get a handle, and
+/// the commands take handles rather than paths.
+pub struct HostFiles {
+    path: PathBuf,
+    handles: Mutex<HashMap<String, (PathBuf, FileKind)>>,
+    recent: Mutex<Vec<RecentFile>>,
+}
+
+fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+impl HostFiles {
+    /// Reads the recent list; missing or invalid files give none.
+    pub fn load(path: PathBuf) -> Self {
+        let recent = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| {
+                serde_json::from_str(&contents)
+                    .map_err(|err| eprintln!("ignoring invalid {}: {err}", path.display()))
+                    .ok()
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            handles: Mutex::new(HashMap::new()),
+            recent: Mutex::new(recent),
+        }
+    }
+
+    /// Hands out a handle for `path` and moves it to the top of the
+    /// recent list.
+    pub fn grant(&self, path: &Path, kind: FileKind) -> Result<HostFile, String> {
+        kind.check(path)?;
+        let mut handles = self.handles.lock().unwrap();
+        let handle = handles
+            .iter()
+            .find(|(_, (granted, _))| granted == path)
+            .map(|(handle, _)| handle.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let file = describe(handle.clone(), path, kind)?;
+        handles.insert(handle, (path.to_path_buf(), kind));
+        drop(handles);
+
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|entry| entry.path != path);
+        recent.insert(
+            0,
+            RecentFile {
+                name: file.name.clone(),
+                path: path.to_path_buf(),
+                kind,
+                opened_at: now_millis(),
+            },
+        );
+        recent.truncate(MAX_RECENT);
+        if let Err(err) = self.save(&recent) {
+            eprintln!("failed to save {}: {err}", self.path.display());
+        }
+        Ok(file)
+    }
+
+    fn resolve(&self, handle: &str) -> Result<(PathBuf, FileKind), String> {
+        self.handles
+            .lock()
+            .unwrap()
+            .get(handle)
+            .cloned()
+            .ok_or_else(|| format!("no open file `{handle}`"))
+    }
+
+    pub fn read(&self, handle: &str) -> Result<Vec<u8>, String> {
+        let (path, _) = self.resolve(handle)?;
+        fs::read(&path).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    /// Replaces the file behind `handle` with `contents`; see
+    /// [`atomic::replace`].
+    pub fn write(&self, handle: &str, contents: &[u8]) -> Result<HostFile, String> {
+        let (path, kind) = self.resolve(handle)?;
+        atomic::replace(&path, contents).map_err(|err| format!("{}: {err}", path.display()))?;
+        describe(handle.to_string(), &path, kind)
+    }
+
+    /// Writes `contents` to `path`, picked in a save dialog, and hands out a
+    /// handle for it. The user confirmed that exact path, so one without
+    /// the kind's extensions is refused rather than renamed.
+    pub fn create(&self, path: &Path, kind: FileKind, contents: &[u8]) -> Result<HostFile, String> {
+        kind.check(path)?;
+        atomic::replace(path, contents).map_err(|err| format!("{}: {err}", path.display()))?;
+        self.grant(path, kind)
+    }
+
+    /// Stops the web app from using `handle`.
+    pub fn close(&self, handle: &str) {
+        self.handles.lock().unwrap().remove(handle);
+    }
+
+    /// Recent files of `kind` (all kinds for `None`) that still exist.
+    pub fn recent(&self, kind: Option<FileKind>) -> Vec<RecentFile> {
+        self.recent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| kind.is_none_or(|kind| entry.kind == kind))
+            .filter(|entry| entry.path.is_file())
+            .cloned()
+            .collect()
+    }
+
+    /// Reopens a file from the recent list; other paths are refused, as
+    /// the user never picked them.
+    pub fn reopen(&self, path: &Path) -> Result<HostFile, String> {
+        let kind = self
+            .recent
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.path == path)
+            .map(|entry| entry.kind)
+            .ok_or_else(|| format!("{} is not a recent file", path.display()))?;
+        self.grant(path, kind)
+    }
+
+    fn save(&self, recent: &[RecentFile]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let contents = serde_json::to_vec_pretty(recent).map_err(|err| err.to_string())?;
+        atomic::write(&self.path, &contents).map_err(|err| err.to_string())
+    }
+}
+
+fn describe(handle: String, path: &Path, kind: FileKind) -> Result<HostFile, String> {
+    let metadata = fs::metadata(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let modified_at = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_millis() as u64);
+    Ok(HostFile {
+        handle,
+        name: path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        path: path.to_path_buf(),
+        kind,
+        size: metadata.len(),
+        modified_at,
+    })
+}
+
+/// `name` with the kind's first extension when it has none of its own.
+fn with_extension(name: &str, kind: FileKind) -> String {
+    if kind.accepts(Path::new(name)) {
+        name.to_string()
+    } else {
+        format!("{name}.{}", kind.extensions()[0])
+    }
+}
+
+/// Asks for a file of `kind` to open. Returns `None` when the dialog is
+/// cancelled.
+#[tauri::command]
+pub async fn host_file_open<R: Runtime>(
+    app: AppHandle<R>,
+    kind: FileKind,
+    host_files: State<'_, HostFiles>,
+) -> Result<Option<HostFile>, String> {
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter(kind.filter_name(), kind.extensions())
+        .blocking_pick_file();
+    match picked {
+        Some(picked) => {
+            let path = picked.into_path().map_err(|err| err.to_string())?;
+            host_files.grant(&path, kind).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+pub async fn host_file_read(
+    handle: String,
+    host_files: State<'_, HostFiles>,
+) -> Result<Response, String> {
+    host_files.read(&handle).map(Response::new)
+}
+
+/// Saves the raw request body. With a [`HANDLE_HEADER`] it replaces that
+/// file; otherwise it asks where to save, using the [`SAVE_AS_HEADER`].
+/// Returns `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn host_file_save<R: Runtime>(
+    app: AppHandle<R>,
+    request: Request<'_>,
+    host_files: State<'_, HostFiles>,
+) -> Result<Option<HostFile>, String> {
+    let contents = ipc_body::body(&request)?;
+    if let Some(handle) = ipc_body::header(&request, HANDLE_HEADER)? {
+        return host_files.write(handle, contents).map(Some);
+    }
+
+    let SaveAs { kind, name } = ipc_body::json_header(&request, SAVE_AS_HEADER)?
+        .ok_or_else(|| format!("missing `{HANDLE_HEADER}` or `{SAVE_AS_HEADER}` header"))?;
+    let mut dialog = app
+        .dialog()
+        .file()
+        .add_filter(kind.filter_name(), kind.extensions());
+    if let Some(name) = name {
+        dialog = dialog.set_file_name(with_extension(&name, kind));
+    }
+    let Some(picked) = dialog.blocking_save_file() else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|err| err.to_string())?;
+    host_files.create(&path, kind, contents).map(Some)
+}
+
+#[tauri::command]
+pub fn host_file_close(handle: String, host_files: State<'_, HostFiles>) {
+    host_files.close(&handle);
+}
+
+#[tauri::command]
+pub fn host_file_recent(
+    kind: Option<FileKind>,
+    host_files: State<'_, HostFiles>,
+) -> Vec<RecentFile> {
+    host_files.recent(kind)
+}
+
+#[tauri::command]
+pub fn host_file_open_recent(
+    path: PathBuf,
+    host_files: State<'_, HostFiles>,
+) -> Result<HostFile, String> {
+    host_files.reopen(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_scope_reads_and_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let host_files = HostFiles::load(dir.path().join(FILE_NAME));
+        let note = dir.path().join("note.md");
+        fs::write(&note, "# one").unwrap();
+
+        assert!(host_files.read("unknown").is_err());
+        assert!(host_files
+            .grant(&dir.path().join("run.sh"), FileKind::Text)
+            .is_err());
+
+        let file = host_files.grant(&note, FileKind::Text).unwrap();
+        assert_eq!((file.name.as_str(), file.size), ("note.md", 5));
+        assert_eq!(host_files.read(&file.handle).unwrap(), b"# one");
+        // Picking the same file again keeps its handle
+        assert_eq!(
+            host_files.grant(&note, FileKind::Text).unwrap().handle,
+            file.handle
+        );
+
+        let saved = host_files.write(&file.handle, b"# two").unwrap();
+        assert_eq!(saved.size, 5);
+        assert_eq!(fs::read_to_string(&note).unwrap(), "# two");
+
+        host_files.close(&file.handle);
+        assert!(host_files.write(&file.handle, b"# three").is_err());
+    }
+
+    #[test]
+    fn saves_new_files_at_the_picked_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let host_files = HostFiles::load(dir.path().join(FILE_NAME));
+
+        let note = dir.path().join("notes.MD");
+        let file = host_files.create(&note, FileKind::Text, b"# new").unwrap();
+        assert_eq!(file.path, note);
+        assert_eq!(host_files.read(&file.handle).unwrap(), b"# new");
+
+        for (name, kind) in [("notes", FileKind::Text), ("photo.md", FileKind::Image)] {
+            let path = dir.path().join(name);
+            assert!(host_files.create(&path, kind, b"x").is_err());
+            assert!(!path.exists());
+        }
+    }
+
+    #[test]
+    fn remembers_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let list = dir.path().join("state").join(FILE_NAME);
+        let host_files = HostFiles::load(list.clone());
+        for index in 0..12 {
+            let path = dir.path().join(format!("{index}.png"));
+            fs::write(&path, [index]).unwrap();
+            host_files.grant(&path, FileKind::Image).unwrap();
+        }
+        let note = dir.path().join("a.txt");
+        fs::write(&note, "a").unwrap();
+        host_files.grant(&note, FileKind::Text).unwrap();
+
+        let reloaded = HostFiles::load(list);
+        let recent = reloaded.recent(None);
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent[0].path, note);
+        assert_eq!(reloaded.recent(Some(FileKind::Text)).len(), 1);
+
+        // Reopening needs the path to be on the list, and brings it to the top
+        assert!(reloaded.reopen(&dir.path().join("0.png")).is_err());
+        let reopened = reloaded.reopen(&dir.path().join("5.png")).unwrap();
+        assert_eq!(reloaded.read(&reopened.handle).unwrap(), [5]);
+        assert_eq!(reloaded.recent(None)[0].path, dir.path().join("5.png"));
+
+        fs::remove_file(&note).unwrap();
+        assert!(reloaded.recent(Some(FileKind::Text)).is_empty());
+    }
+
+    #[test]
+    fn adds_missing_extensions() {
+        assert_eq!(with_extension("Untitled", FileKind::Text), "Untitled.md");
+        assert_eq!(with_extension("notes.TXT", FileKind::Text), "notes.TXT");
+        assert_eq!(with_extension("photo.jpeg", FileKind::Image), "photo.jpeg");
+        assert_eq!(with_extension("photo.md", FileKind::Image), "photo.md.png");
+    }
+}
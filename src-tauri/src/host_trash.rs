@@ -0,0 +1,318 @@
+use std::cmp::Reverse;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::vfs::{copy_all, remove_all};
+
+/// Directories inside the trash, from the freedesktop.org Trash spec.
+const FILES_DIR: &str = "files";
+const INFO_DIR: &str = "info";
+const INFO_EXTENSION: &str = "trashinfo";
+const INFO_HEADER: &str = "[Trash Info]";
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Characters left unescaped in `Path=`, as in a URI path.
+const PATH_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// The user's home trash, `$XDG_DATA_HOME/Trash`.
+pub struct HostTrash {
+    dir: PathBuf,
+}
+
+/// An item in the trash: `files/<id>` with its `info/<id>.trashinfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub id: String,
+    /// Where the item was deleted from.
+    pub original: PathBuf,
+    /// `DeletionDate`, in milliseconds since the epoch.
+    pub deleted_at: u64,
+    /// The trashed file or directory.
+    pub file: PathBuf,
+}
+
+/// `$XDG_DATA_HOME` given its value `xdg_data_home`, falling back to
+/// `~/.local/share` when it's unset or relative.
+fn data_home(xdg_data_home: Option<OsString>, home: Option<PathBuf>) -> Option<PathBuf> {
+    xdg_data_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| Some(home?.join(".local/share")))
+}
+
+impl HostTrash {
+    /// The trash under `data_home` (what `$XDG_DATA_HOME` names).
+    pub fn at(data_home: &Path) -> Self {
+        Self {
+            dir: data_home.join("Trash"),
+        }
+    }
+
+    /// The home trash on platforms that follow the freedesktop.org spec;
+    /// `$XDG_DATA_HOME` if it is set to an absolute path, otherwise
+    /// `~/.local/share`.
+    pub fn locate() -> Option<Self> {
+        if cfg!(any(windows, target_os = "macos")) {
+            return None;
+        }
+        let data_home = data_home(std::env::var_os("XDG_DATA_HOME"), dirs::home_dir())?;
+        Some(Self::at(&data_home))
+    }
+
+    fn info_path(&self, id: &str) -> PathBuf {
+        self.dir
+            .join(INFO_DIR)
+            .join(format!("{id}.{INFO_EXTENSION}"))
+    }
+
+    /// Moves `host` (an absolute path) into the trash.
+    pub fn trash(&self, host: &Path) -> io::Result<Entry> {
+        let name = host
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no name"))?;
+        fs::create_dir_all(self.dir.join(FILES_DIR))?;
+        fs::create_dir_all(self.dir.join(INFO_DIR))?;
+
+        let now = Local::now();
+        let contents = format!(
+            "{INFO_HEADER}\nPath={}\nDeletionDate={}\n",
+            utf8_percent_encode(&host.to_string_lossy(), PATH_SET),
+            now.format(DATE_FORMAT),
+        );
+        // Creating the info file first reserves the id, as the spec asks
+        let (id, mut info) = (1..)
+            .map(|counter| numbered(name, counter))
+            .find_map(|id| {
+                if fs::symlink_metadata(self.dir.join(FILES_DIR).join(&id)).is_ok() {
+                    return None;
+                }
+                let info = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(self.info_path(&id));
+                match info {
+                    Ok(info) => Some(Ok((id, info))),
+                    Err(err) if err.kind() == io::ErrorKind::AlreadyExists => None,
+                    Err(err) => Some(Err(err)),
+                }
+            })
+            .unwrap()?;
+
+        let file = self.dir.join(FILES_DIR).join(&id);
+        let moved = info
+            .write_all(contents.as_bytes())
+            .and_then(|()| move_across(host, &file));
+        if let Err(err) = moved {
+            let _ = fs::remove_file(self.info_path(&id));
+            return Err(err);
+        }
+        Ok(Entry {
+            id,
+            original: host.to_path_buf(),
+            deleted_at: now.timestamp_millis() as u64,
+            file,
+        })
+    }
+
+    /// Everything in the trash with a valid info file, newest first.
+    pub fn entries(&self) -> Vec<Entry> {
+        let Ok(infos) = fs::read_dir(self.dir.join(INFO_DIR)) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<Entry> = infos
+            .flatten()
+            .filter_map(|info| {
+                let file_name = info.file_name().into_string().ok()?;
+                let id = file_name.strip_suffix(&format!(".{INFO_EXTENSION}"))?;
+                let file = self.dir.join(FILES_DIR).join(id);
+                fs::symlink_metadata(&file).ok()?;
+                let (original, deleted_at) = parse_info(&fs::read_to_string(info.path()).ok()?)?;
+                Some(Entry {
+                    id: id.to_string(),
+                    original,
+                    deleted_at,
+                    file,
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| Reverse(entry.deleted_at));
+        entries
+    }
+
+    /// Moves `entry` back to `destination`, which must not exist.
+    pub fn restore(&self, entry: &Entry, destination: &Path) -> io::Result<()> {
+        if fs::symlink_metadata(destination).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", destination.display()),
+            ));
+        }
+        move_across(&entry.file, destination)?;
+        fs::remove_file(self.info_path(&entry.id))
+    }
+
+    /// Deletes `entry` for good.
+    pub fn delete(&self, entry: &Entry) -> io::Result<()> {
+        remove_all(&entry.file)?;
+        fs::remove_file(self.info_path(&entry.id))
+    }
+}
+
+/// `a.md`, `a.2.md`, `a.3.md`, ...
+fn numbered(name: &str, counter: u32) -> String {
+    if counter == 1 {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{stem}.{counter}.{extension}"),
+        _ => format!("{name}.{counter}"),
+    }
+}
+
+/// Reads `Path` and `DeletionDate` from a `.trashinfo` file. Only absolute
+/// paths are valid in the home trash.
+fn parse_info(contents: &str) -> Option<(PathBuf, u64)> {
+    let mut lines = contents.lines().map(str::trim);
+    if lines.next()? != INFO_HEADER {
+        return None;
+    }
+    let (mut path, mut deleted_at) = (None, None);
+    for line in lines {
+        if line.starts_with('[') {
+            break;
+        }
+        match line.split_once('=') {
+            Some(("Path", value)) => {
+                path = Some(PathBuf::from(
+                    percent_decode_str(value).decode_utf8().ok()?.into_owned(),
+                ));
+            }
+            Some(("DeletionDate", value)) => {
+                deleted_at = NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+                    .ok()
+                    .and_then(|date| date.and_local_timezone(Local).earliest())
+                    .map(|date| date.timestamp_millis() as u64);
+            }
+            _ => {}
+        }
+    }
+    let path = path.filter(|path| path.is_absolute())?;
+    Some((path, deleted_at.unwrap_or_default()))
+}
+
+/// Renames `source` to `destination`, copying and deleting when they are on
+/// different filesystems.
+fn move_across(source: &Path, destination: &Path) -> io::Result<()> {
+    if fs::rename(source, destination).is_ok() {
+        return Ok(());
+    }
+    if let Err(err) = copy_all(source, destination) {
+        let _ = remove_all(destination);
+        return Err(err);
+    }
+    remove_all(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trashes_lists_and_restores() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = HostTrash::at(&dir.path().join("data"));
+        let shared = dir.path().join("Shared Folder");
+        fs::create_dir_all(shared.join("Docs")).unwrap();
+        fs::write(shared.join("a.md"), "one").unwrap();
+
+        let first = trash.trash(&shared.join("a.md")).unwrap();
+        assert!(!shared.join("a.md").exists());
+        fs::write(shared.join("a.md"), "two").unwrap();
+        let second = trash.trash(&shared.join("a.md")).unwrap();
+        assert_eq!((first.id.as_str(), second.id.as_str()), ("a.md", "a.2.md"));
+
+        let info = fs::read_to_string(trash.info_path("a.md")).unwrap();
+        assert!(info.starts_with("[Trash Info]\nPath=/"));
+        assert!(info.contains("Shared%20Folder/a.md\nDeletionDate="));
+
+        let entries = trash.entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.original == shared.join("a.md")));
+
+        trash.restore(&first, &shared.join("a.md")).unwrap();
+        assert_eq!(fs::read_to_string(shared.join("a.md")).unwrap(), "one");
+        assert!(trash.restore(&second, &shared.join("a.md")).is_err());
+        trash.delete(&second).unwrap();
+        assert!(trash.entries().is_empty());
+        assert!(!trash.info_path("a.2.md").exists());
+    }
+
+    #[test]
+    fn trashes_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = HostTrash::at(&dir.path().join("data"));
+        let folder = dir.path().join("Folder");
+        fs::create_dir(&folder).unwrap();
+        fs::write(folder.join("b.txt"), "b").unwrap();
+
+        let entry = trash.trash(&folder).unwrap();
+        assert!(entry.file.join("b.txt").is_file());
+        trash.restore(&entry, &folder).unwrap();
+        assert!(folder.join("b.txt").is_file());
+    }
+
+    #[test]
+    fn parses_and_skips_info_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = HostTrash::at(dir.path());
+        fs::create_dir_all(dir.path().join("Trash/files/x")).unwrap();
+        fs::create_dir_all(dir.path().join("Trash/files/rel")).unwrap();
+        let info = dir.path().join("Trash/info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(
+            info.join("x.trashinfo"),
+            "[Trash Info]\nPath=/home/u/caf%C3%A9\nDeletionDate=2004-08-31T22:32:08\n",
+        )
+        .unwrap();
+        fs::write(info.join("rel.trashinfo"), "[Trash Info]\nPath=rel\n").unwrap();
+        // Info without a trashed file
+        fs::write(info.join("gone.trashinfo"), "[Trash Info]\nPath=/gone\n").unwrap();
+
+        let entries = trash.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].original, Path::new("/home/u/café"));
+        assert!(entries[0].deleted_at > 1_090_000_000_000);
+    }
+
+    #[test]
+    fn locates_trash_in_xdg_data_home() {
+        let home = Some(PathBuf::from("/home/me"));
+        let data = std::env::temp_dir().join("data");
+        assert_eq!(
+            data_home(Some(data.clone().into_os_string()), home.clone()),
+            Some(data)
+        );
+        assert_eq!(
+            data_home(Some("relative".into()), home.clone()),
+            Some(PathBuf::from("/home/me/.local/share"))
+        );
+        assert_eq!(
+            data_home(None, home),
+            Some(PathBuf::from("/home/me/.local/share"))
+        );
+        assert_eq!(data_home(None, None), None);
+    }
+}
@@ -0,0 +1,383 @@
+use std::fs::{self, File, TryLockError};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::atomic;
+
+/// Name of the file the running instance advertises its socket in.
+pub const LOCK_FILE_NAME: &str = "instance.json";
+
+/// Extension of the file next to [`LOCK_FILE_NAME`] the running instance
+/// holds an OS lock on. The OS drops it when the process ends, however it
+/// ends, so there is never a stale owner to detect.
+const OWNER_EXTENSION: &str = "lock";
+
+/// How long a later launch waits for the instance that holds the lock to
+/// advertise its socket, e.g. when both were started at the same moment.
+const FORWARD_ATTEMPTS: u32 = 20;
+const FORWARD_RETRY: Duration = Duration::from_millis(100);
+
+/// Upper bound for a forwarded message, to keep a stray client from making
+/// the listener buffer without limit.
+const MAX_MESSAGE_LEN: u64 = 64 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What a second invocation hands to the running instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Forwarded {
+    /// Arguments after the program name, parsed again by the receiver.
+    pub args: Vec<String>,
+    /// Working directory of the second invocation, for relative host paths.
+    pub cwd: Option<PathBuf>,
+}
+
+impl Forwarded {
+    pub fn current() -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+            cwd: std::env::current_dir().ok(),
+        }
+    }
+}
+
+/// Contents of the lock file: where the running instance listens and the
+/// token clients must present.
+#[derive(Debug, Serialize, Deserialize)]
+struct Lock {
+    port: u16,
+    token: String,
+}
+
+/// One line of JSON per connection, answered with `ok`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+    token: String,
+    #[serde(flatten)]
+    forwarded: Forwarded,
+}
+
+/// Hands `forwarded` to the instance advertised in `lock_path`. Returns
+/// `false` when there is no running instance (missing or stale lock file).
+fn try_forward(lock_path: &Path, forwarded: &Forwarded) -> bool {
+    let Ok(contents) = fs::read_to_string(lock_path) else {
+        return false;
+    };
+    let Ok(lock) = serde_json::from_str::<Lock>(&contents) else {
+        return false;
+    };
+    match send(lock.port, &lock.token, forwarded) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("ignoring stale {}: {err}", lock_path.display());
+            false
+        }
+    }
+}
+
+/// What [`claim`] made of this launch.
+#[derive(Debug)]
+pub enum Claim {
+    /// This is the running instance now, for as long as the [`Owner`]
+    /// is kept.
+    Primary(Owner),
+    /// Another instance is running and took the arguments.
+    Forwarded,
+}
+
+/// The running instance's hold on its profile.
+#[derive(Debug)]
+pub struct Owner {
+    lock_path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl Owner {
+    /// Stops advertising this instance and lets go of its lock, so the
+    /// next launch starts fresh instead of forwarding to it.
+    pub fn release(&self) {
+        let _ = fs::remove_file(&self.lock_path);
+        self.file.lock().unwrap().take();
+    }
+}
+
+/// Becomes the running instance for `lock_path`, calling `on_forward` for
+/// every later launch, or hands `forwarded` to the instance that already
+/// is. Taking the OS lock decides it, so of two launches at the same
+/// moment only one can win.
+pub fn claim<F>(lock_path: &Path, forwarded: &Forwarded, on_forward: F) -> io::Result<Claim>
+where
+    F: Fn(Forwarded) + Send + 'static,
+{
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let owner = File::options()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path.with_extension(OWNER_EXTENSION))?;
+    match owner.try_lock() {
+        Ok(()) => {
+            listen(lock_path, on_forward)?;
+            Ok(Claim::Primary(Owner {
+                lock_path: lock_path.to_path_buf(),
+                file: Mutex::new(Some(owner)),
+            }))
+        }
+        Err(TryLockError::WouldBlock) => {
+            // The owner may still be starting up
+            for _ in 0..FORWARD_ATTEMPTS {
+                if try_forward(lock_path, forwarded) {
+                    return Ok(Claim::Forwarded);
+                }
+                thread::sleep(FORWARD_RETRY);
+            }
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "another instance is running but doesn't respond",
+            ))
+        }
+        Err(TryLockError::Error(err)) => Err(err),
+    }
+}
+
+fn send(port: u16, token: &str, forwarded: &Forwarded) -> io::Result<()> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let message = Message {
+        token: token.to_string(),
+        forwarded: forwarded.clone(),
+    };
+    let mut line = serde_json::to_vec(&message)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim_end() == "ok" {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "running instance rejected the message",
+        ))
+    }
+}
+
+/// Listens on a loopback port, advertises it in `lock_path` and calls
+/// `on_forward` for every message from a later launch.
+fn listen<F>(lock_path: &Path, on_forward: F) -> io::Result<()>
+where
+    F: Fn(Forwarded) + Send + 'static,
+{
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let lock = Lock {
+        port: listener.local_addr()?.port(),
+        token: new_token(),
+    };
+    write_lock(lock_path, &lock)?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            match receive(stream, &lock.token) {
+                Ok(forwarded) => on_forward(forwarded),
+                Err(err) => eprintln!("ignoring forwarded launch: {err}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn receive(mut stream: TcpStream, token: &str) -> io::Result<Forwarded> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new((&stream).take(MAX_MESSAGE_LEN)).read_line(&mut line)?;
+    let message: Message = serde_json::from_str(&line)?;
+    if message.token != token {
+        stream.write_all(b"denied\n")?;
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "wrong instance token",
+        ));
+    }
+    stream.write_all(b"ok\n")?;
+    Ok(message.forwarded)
+}
+
+/// Writes the lock file whole, by renaming, so a launch reading it never
+/// sees half of it.
+fn write_lock(path: &Path, lock: &Lock) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = atomic::temp_path(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // The token is what keeps other local users out, so only we may read it
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let written = options
+        .open(&temp)
+        .and_then(|mut file| file.write_all(&serde_json::to_vec(lock)?))
+        .and_then(|()| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+fn new_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    fn forwarded(args: &[&str]) -> Forwarded {
+        Forwarded {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cwd: Some(PathBuf::from("/home/user")),
+        }
+    }
+
+    #[test]
+    fn forwards_args_to_running_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        let (tx, rx) = mpsc::channel();
+        listen(&lock_path, move |forwarded| tx.send(forwarded).unwrap()).unwrap();
+
+        let sent = forwarded(&["open", "textedit", "/Documents/notes.md"]);
+        assert!(try_forward(&lock_path, &sent));
+        assert_eq!(rx.recv_timeout(IO_TIMEOUT).unwrap(), sent);
+    }
+
+    #[test]
+    fn only_one_launch_claims_the_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        let (tx, rx) = mpsc::channel();
+        let launches: Vec<_> = (0..8)
+            .map(|n| {
+                let lock_path = lock_path.clone();
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    let sent = forwarded(&["open", "ipod", &n.to_string()]);
+                    claim(&lock_path, &sent, move |forwarded| {
+                        tx.send(forwarded).unwrap()
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+        let claims: Vec<_> = launches
+            .into_iter()
+            .map(|launch| launch.join().unwrap())
+            .collect();
+
+        let primaries = claims
+            .iter()
+            .filter(|claim| matches!(claim, Claim::Primary(_)))
+            .count();
+        assert_eq!(primaries, 1);
+        for _ in 0..7 {
+            rx.recv_timeout(IO_TIMEOUT).unwrap();
+        }
+    }
+
+    #[test]
+    fn released_profile_can_be_claimed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        let Claim::Primary(owner) = claim(&lock_path, &forwarded(&[]), |_| {}).unwrap() else {
+            panic!("nothing else holds the profile");
+        };
+        owner.release();
+        assert!(!lock_path.exists());
+        assert!(matches!(
+            claim(&lock_path, &forwarded(&[]), |_| {}).unwrap(),
+            Claim::Primary(_)
+        ));
+    }
+
+    #[test]
+    fn no_lock_file_means_no_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!try_forward(
+            &dir.path().join(LOCK_FILE_NAME),
+            &forwarded(&[])
+        ));
+    }
+
+    #[test]
+    fn stale_lock_file_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        // Grab a port and release it so nothing is listening there
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let lock = Lock {
+            port,
+            token: new_token(),
+        };
+        write_lock(&lock_path, &lock).unwrap();
+
+        assert!(!try_forward(&lock_path, &forwarded(&["open", "ipod"])));
+    }
+
+    #[test]
+    fn wrong_token_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        let (tx, rx) = mpsc::channel();
+        listen(&lock_path, move |forwarded| tx.send(forwarded).unwrap()).unwrap();
+
+        let contents = fs::read_to_string(&lock_path).unwrap();
+        let lock: Lock = serde_json::from_str(&contents).unwrap();
+        let err = send(
+            lock.port,
+            "not-the-token",
+            &forwarded(&["open", "terminal"]),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn oversized_message_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        let (tx, rx) = mpsc::channel();
+        listen(&lock_path, move |forwarded| tx.send(forwarded).unwrap()).unwrap();
+
+        let huge = "x".repeat(MAX_MESSAGE_LEN as usize);
+        assert!(!try_forward(
+            &lock_path,
+            &forwarded(&["open", "textedit", &huge])
+        ));
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+}
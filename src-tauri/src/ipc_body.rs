@@ -0,0 +1,62 @@
+use percent_encoding::percent_decode_str;
+use serde::de::DeserializeOwned;
+use tauri::ipc::{InvokeBody, Request};
+
+/// The header `name`, or `None` without one.
+pub fn header<'a>(request: &'a Request<'_>, name: &str) -> Result<Option<&'a str>, String> {
+    request
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().map_err(|err| err.to_string()))
+        .transpose()
+}
+
+/// The URI-encoded JSON in the header `name`, or `None` without one.
+pub fn json_header<T: DeserializeOwned>(
+    request: &Request<'_>,
+    name: &str,
+) -> Result<Option<T>, String> {
+    header(request, name)?.map(decode_json).transpose()
+}
+
+/// The raw request body.
+pub fn body<'a>(request: &'a Request<'_>) -> Result<&'a [u8], String> {
+    match request.body() {
+        InvokeBody::Raw(bytes) => Ok(bytes),
+        InvokeBody::Json(_) => Err("expected a raw request body".to_string()),
+    }
+}
+
+/// The arguments of a command that takes bytes: the URI-encoded JSON in
+/// the header `name`, which is required, and the raw request body. Headers
+/// only carry ASCII, hence the encoding.
+pub fn decode<'a, T: DeserializeOwned>(
+    request: &'a Request<'_>,
+    name: &str,
+) -> Result<(T, &'a [u8]), String> {
+    let value = json_header(request, name)?.ok_or_else(|| format!("missing `{name}` header"))?;
+    Ok((value, body(request)?))
+}
+
+fn decode_json<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+    let json = percent_decode_str(value)
+        .decode_utf8()
+        .map_err(|err| err.to_string())?;
+    serde_json::from_str(&json).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn decodes_uri_encoded_json() {
+        let value: HashMap<String, String> =
+            decode_json("%7B%22name%22%3A%22caf%C3%A9%20%25%22%7D").unwrap();
+        assert_eq!(value["name"], "café %");
+        assert!(decode_json::<HashMap<String, String>>("%FF").is_err());
+        assert!(decode_json::<HashMap<String, String>>("%7B").is_err());
+    }
+}
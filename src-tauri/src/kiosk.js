@@ -0,0 +1,47 @@
+(options) => {
+  window.__SYAOS_SHELL__ = window.__SYAOS_SHELL__ || {};
+  window.__SYAOS_SHELL__.kiosk = { allowedApps: options.allowedApps };
+
+  // Reload, quit, close, print and devtools shortcuts
+  const blocked = (e) => {
+    const key = e.key.toLowerCase();
+    const mod = e.ctrlKey || e.metaKey;
+    return (
+      key === "f5" ||
+      key === "f12" ||
+      (e.altKey && key === "f4") ||
+      (mod && ["r", "q", "w", "p"].includes(key)) ||
+      (mod && e.shiftKey && ["i", "j", "c"].includes(key))
+    );
+  };
+  window.addEventListener(
+    "keydown",
+    (e) => {
+      if (blocked(e)) {
+        e.preventDefault();
+        e.stopImmediatePropagation();
+      }
+    },
+    true
+  );
+
+  setInterval(() => {
+    window.__TAURI_INTERNALS__?.invoke("kiosk_heartbeat").catch(() => {});
+  }, options.heartbeatMs);
+
+  if (options.idleResetSecs > 0) {
+    let lastInput = Date.now();
+    const markActive = () => {
+      lastInput = Date.now();
+    };
+    for (const type of ["pointerdown", "pointermove", "keydown", "wheel", "touchstart"]) {
+      window.addEventListener(type, markActive, { capture: true, passive: true });
+    }
+    setInterval(() => {
+      if (Date.now() - lastInput >= options.idleResetSecs * 1000) {
+        lastInput = Date.now();
+        window.dispatchEvent(new CustomEvent("kioskIdleReset"));
+      }
+    }, 1000);
+  }
+}
@@ -0,0 +1,182 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime, State, WebviewWindow};
+
+/// How often the injected script reports that the webview is alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Seconds without input before the desktop is reset, unless configured.
+const DEFAULT_IDLE_RESET_SECS: u64 = 300;
+
+/// Seconds without a heartbeat before the webview is reloaded, unless configured.
+const DEFAULT_WATCHDOG_SECS: u64 = 30;
+
+/// The `kiosk` section of `shell.json`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KioskConfig {
+    /// Start in kiosk mode without `--kiosk`.
+    pub enabled: bool,
+    /// App ids that may be opened; empty allows every app.
+    pub allowed_apps: Vec<String>,
+    /// Seconds without input before open apps are closed; 0 disables it.
+    pub idle_reset_secs: Option<u64>,
+    /// Seconds without a heartbeat before the webview is reloaded.
+    pub watchdog_secs: Option<u64>,
+}
+
+/// Managed only while kiosk mode is on.
+pub struct Kiosk {
+    config: KioskConfig,
+    last_heartbeat: Mutex<Instant>,
+}
+
+impl Kiosk {
+    pub fn new(config: KioskConfig) -> Self {
+        Self {
+            config,
+            last_heartbeat: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn allows(&self, app_id: &str) -> bool {
+        self.config.allowed_apps.is_empty()
+            || self.config.allowed_apps.iter().any(|id| id == app_id)
+    }
+
+    /// Script that blocks shortcuts leading out of the kiosk, drives the
+    /// watchdog heartbeat and fires `kioskIdleReset` after inactivity.
+    pub fn init_script(&self) -> String {
+        let options = serde_json::json!({
+            "allowedApps": self.config.allowed_apps,
+            "idleResetSecs": self.config.idle_reset_secs.unwrap_or(DEFAULT_IDLE_RESET_SECS),
+            "heartbeatMs": HEARTBEAT_INTERVAL.as_millis() as u64,
+        });
+        format!("({})({options});", include_str!("kiosk.js"))
+    }
+}
+
+/// Whether `app_id` may be opened. Always true outside kiosk mode.
+pub fn allows<R: Runtime>(app: &AppHandle<R>, app_id: &str) -> bool {
+    app.try_state::<Kiosk>()
+        .is_none_or(|kiosk| kiosk.allows(app_id))
+}
+
+/// Reloads `window` whenever the injected heartbeat stops, which covers both
+/// a hung page and a crashed web process.
+pub fn start_watchdog<R: Runtime>(app: &AppHandle<R>, window: WebviewWindow<R>) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(HEARTBEAT_INTERVAL);
+        let kiosk = app.state::<Kiosk>();
+        let timeout =
+            Duration::from_secs(kiosk.config.watchdog_secs.unwrap_or(DEFAULT_WATCHDOG_SECS));
+        let mut last_heartbeat = kiosk.last_heartbeat.lock().unwrap();
+        if last_heartbeat.elapsed() < timeout {
+            continue;
+        }
+
+        eprintln!(
+            "no heartbeat for {}s, reloading the webview",
+            timeout.as_secs()
+        );
+        // Give the reloaded page a full timeout to report back
+        *last_heartbeat = Instant::now();
+        if window.reload().is_err() {
+            break;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn kiosk_heartbeat(kiosk: State<'_, Kiosk>) {
+    *kiosk.last_heartbeat.lock().unwrap() = Instant::now();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::config::ShellConfig;
+
+    fn kiosk(json: &str) -> Kiosk {
+        Kiosk::new(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn allows_listed_apps_only() {
+        let open = kiosk("{}");
+        assert!(open.allows("finder"));
+        assert!(open.allows("terminal"));
+
+        let locked = kiosk(r#"{ "allowedApps": ["ipod", "paint"] }"#);
+        assert!(locked.allows("ipod"));
+        assert!(locked.allows("paint"));
+        assert!(!locked.allows("terminal"));
+        assert!(!locked.allows("iPod"));
+        assert!(!locked.allows(""));
+    }
+
+    #[test]
+    fn parses_the_config() {
+        let config: KioskConfig = serde_json::from_str(
+            r#"{
+                "enabled": true,
+                "allowedApps": ["ipod"],
+                "idleResetSecs": 0,
+                "watchdogSecs": 10
+            }"#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.allowed_apps, ["ipod"]);
+        assert_eq!(config.idle_reset_secs, Some(0));
+        assert_eq!(config.watchdog_secs, Some(10));
+
+        let defaults: KioskConfig = serde_json::from_str("{}").unwrap();
+        assert!(!defaults.enabled);
+        assert!(defaults.allowed_apps.is_empty());
+        assert_eq!(defaults.idle_reset_secs, None);
+        assert!(serde_json::from_str::<KioskConfig>(r#"{ "allowedApps": "ipod" }"#).is_err());
+    }
+
+    #[test]
+    fn reads_the_kiosk_section_of_shell_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ShellConfig::FILE_NAME);
+        fs::write(
+            &path,
+            r#"{ "kiosk": { "enabled": true, "allowedApps": ["paint"] } }"#,
+        )
+        .unwrap();
+        let config = ShellConfig::load_from(&path).kiosk;
+        assert!(config.enabled);
+        assert!(Kiosk::new(config).allows("paint"));
+
+        // A malformed file leaves kiosk mode off rather than half-configured
+        fs::write(&path, r#"{ "kiosk": { "allowedApps": 3 } }"#).unwrap();
+        let config = ShellConfig::load_from(&path).kiosk;
+        assert!(!config.enabled);
+        assert!(config.allowed_apps.is_empty());
+        assert!(
+            !ShellConfig::load_from(&dir.path().join("missing.json"))
+                .kiosk
+                .enabled
+        );
+    }
+
+    #[test]
+    fn passes_options_to_the_script() {
+        let script = kiosk(r#"{ "allowedApps": ["ipod"] }"#).init_script();
+        assert!(script.contains(r#""allowedApps":["ipod"]"#));
+        assert!(script.contains(&format!(r#""idleResetSecs":{DEFAULT_IDLE_RESET_SECS}"#)));
+        assert!(script.contains(r#""heartbeatMs":5000"#));
+
+        let script = kiosk(r#"{ "idleResetSecs": 0 }"#).init_script();
+        assert!(script.contains(r#""idleResetSecs":0"#));
+    }
+}
@@ -0,0 +1,132 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tauri::Url;
+
+/// Hosted deployment used as the remote origin unless one is configured.
+pub const DEFAULT_ORIGIN: &str = "https://sya-os.vercel.app";
+
+/// Environment variable that overrides the configured launch mode.
+pub const LAUNCH_MODE_ENV: &str = "SYAOS_LAUNCH_MODE";
+
+/// Environment variable that overrides the configured remote origin.
+pub const ORIGIN_ENV: &str = "SYAOS_ORIGIN";
+
+/// Where the main webview loads the frontend from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchMode {
+    /// The `dist` bundled into the binary (works without network).
+    #[default]
+    Bundled,
+    /// The hosted site at the configured remote origin.
+    Remote,
+}
+
+impl FromStr for LaunchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bundled" | "offline" | "local" => Ok(LaunchMode::Bundled),
+            "remote" | "hosted" | "online" => Ok(LaunchMode::Remote),
+            other => Err(format!("unknown launch mode `{other}`")),
+        }
+    }
+}
+
+/// Picks the launch mode: CLI flags win over the environment, which wins
+/// over the config file. Falls back to [`LaunchMode::Bundled`].
+pub fn resolve_mode(
+    cli: Option<LaunchMode>,
+    env: Option<&str>,
+    config: Option<LaunchMode>,
+) -> LaunchMode {
+    let from_env = env.and_then(|value| match value.parse() {
+        Ok(mode) => Some(mode),
+        Err(err) => {
+            eprintln!("ignoring {LAUNCH_MODE_ENV}: {err}");
+            None
+        }
+    });
+
+    cli.or(from_env).or(config).unwrap_or_default()
+}
+
+/// Picks the remote origin with the same precedence as [`resolve_mode`]
+/// (`--origin <url>`, then the environment, then the config file).
+///
+/// Unlike the launch mode, an invalid origin is an error rather than silently
+/// falling back to the default deployment.
+pub fn resolve_origin(
+    cli: Option<&str>,
+    env: Option<&str>,
+    config: Option<&str>,
+) -> Result<Url, String> {
+    let (source, value) = match (cli, env, config) {
+        (Some(value), _, _) => ("--origin", value),
+        (None, Some(value), _) => (ORIGIN_ENV, value),
+        (None, None, Some(value)) => ("shell.json", value),
+        (None, None, None) => ("default", DEFAULT_ORIGIN),
+    };
+    parse_origin(value).map_err(|err| format!("invalid origin from {source}: {err}"))
+}
+
+/// [`resolve_origin`] for a launch in `mode`. The bundled app only passes
+/// the origin on to the frontend, so there an invalid one falls back to
+/// [`DEFAULT_ORIGIN`] instead of stopping the launch.
+pub fn resolve_origin_for(
+    mode: LaunchMode,
+    cli: Option<&str>,
+    env: Option<&str>,
+    config: Option<&str>,
+) -> Result<Url, String> {
+    match resolve_origin(cli, env, config) {
+        Err(err) if mode == LaunchMode::Bundled => {
+            eprintln!("{err}; using {DEFAULT_ORIGIN}");
+            resolve_origin(None, None, None)
+        }
+        result => result,
+    }
+}
+
+/// Accepts bare origins only: https (or http on loopback), a host, no
+/// credentials and nothing past the path root.
+pub fn parse_origin(value: &str) -> Result<Url, String> {
+    let url = Url::parse(value.trim()).map_err(|err| format!("`{value}`: {err}"))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("`{value}` has no host"))?;
+
+    match url.scheme() {
+        "https" => {}
+        "http" if is_loopback(host) => {}
+        scheme => return Err(format!("`{value}` must use https (got {scheme})")),
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(format!("`{value}` must not contain credentials"));
+    }
+    if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+        return Err(format!("`{value}` must be a bare origin without a path"));
+    }
+    Ok(url)
+}
+
+fn is_loopback(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Origin without the trailing slash, as used by capabilities and the frontend.
+pub fn origin_str(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+/// Script run before the frontend loads so it knows how the shell launched it.
+pub fn init_script(mode: LaunchMode, remote_url: &str, offline_fallback: bool) -> String {
+    let info = serde_json::json!({
+        "launchMode": mode,
+        "remoteUrl": remote_url,
+        "offlineFallback": offline_fallback,
+    });
+    format!("window.__SYAOS_SHELL__ = {info};")
+}
@@ -0,0 +1,36 @@
+use std::io;
+use std::net::TcpListener;
+
+/// Port the bundled `dist` is served on. It is kept fixed because the
+/// webview's storage (localStorage, IndexedDB) is keyed by origin.
+pub const DEFAULT_PORT: u16 = 14_380;
+
+/// How many ports above the preferred one to try before asking the OS.
+const FALLBACK_RANGE: u16 = 10;
+
+/// Returns `preferred` when it is free, otherwise the next free port close to
+/// it, otherwise whatever port the OS hands out.
+pub fn pick_port(preferred: u16) -> io::Result<u16> {
+    let candidates =
+        (preferred..=preferred.saturating_add(FALLBACK_RANGE)).filter(|port| *port != 0);
+    for port in candidates {
+        if TcpListener::bind(("localhost", port)).is_ok() {
+            if port != preferred {
+                eprintln!("port {preferred} is in use, serving the bundled app on {port}");
+            }
+            return Ok(port);
+        }
+    }
+
+    let listener = TcpListener::bind(("localhost", 0))?;
+    let port = listener.local_addr()?.port();
+    eprintln!(
+        "ports {preferred}-{} are in use, serving the bundled app on {port}",
+        preferred.saturating_add(FALLBACK_RANGE)
+    );
+    Ok(port)
+}
+
+pub fn origin(port: u16) -> String {
+    format!("http://localhost:{port}")
+}
@@ -1,22 +1,469 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::{Manager, Url};
+mod applet;
+mod atomic;
+mod backup;
+mod blob;
+mod capability;
+mod cli;
+mod config;
+mod deep_link;
+mod drag_drop;
+mod error;
+mod event_queue;
+mod file_open;
+mod host_files;
+mod host_trash;
+mod instance;
+mod ipc_body;
+mod kiosk;
+mod launch;
+mod localhost;
+mod mounts;
+mod probe;
+mod profile;
+mod search;
+mod shell_info;
+mod splash;
+mod storage;
+mod thumbnail;
+mod vfs;
+mod watcher;
+mod window_state;
+
+use std::path::Path;
+
+use tauri::{
+    App, AppHandle, Manager, RunEvent, Url, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+use cli::{LaunchRequest, Parsed};
+use config::ShellConfig;
+use deep_link::DeepLink;
+use error::ShellError;
+use event_queue::EventQueue;
+use file_open::OpenedFile;
+use instance::{Claim, Forwarded};
+use kiosk::Kiosk;
+use launch::LaunchMode;
+use shell_info::ShellInfo;
+use tauri_plugin_deep_link::DeepLinkExt;
+use window_state::Tracker;
 
 fn main() {
-    // Always load the hosted app so Tauri uses a stable origin
+    let cli = match cli::parse(std::env::args().skip(1)) {
+        Ok(Parsed::Run(cli)) => cli,
+        Ok(Parsed::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(Parsed::Version) => {
+            println!("syaos {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Ok(Parsed::InspectApplet(path)) => match applet::read(&path) {
+            Ok(package) => {
+                println!("{}", applet::describe(&package));
+                return;
+            }
+            Err(err) => {
+                eprintln!("syaos: {err}");
+                std::process::exit(1);
+            }
+        },
+        Err(err) => {
+            eprintln!("syaos: {err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let context = tauri::generate_context!();
+    let log_dir = error::log_dir(&context.config().identifier);
+    let setup_log_dir = log_dir.clone();
+
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .setup(|app| {
-            if let Some(window) = app.get_webview_window("main") {
-                let url = Url::parse("https://sya-os.vercel.app")?;
-                window.set_title("")?;
-                window.navigate(url)?;
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
+        .manage(EventQueue::<LaunchRequest>::new(cli::LAUNCH_EVENT))
+        .manage(EventQueue::<DeepLink>::new(deep_link::DEEP_LINK_EVENT))
+        .manage(EventQueue::<OpenedFile>::new(file_open::OPEN_FILE_EVENT))
+        .manage(file_open::Opened::default())
+        .manage(watcher::Watchers::default())
+        .manage(drag_drop::Dropped::default())
+        .invoke_handler(tauri::generate_handler![
+            shell_info::get_shell_info,
+            shell_info::check_shell_compatibility,
+            cli::take_launch_requests,
+            deep_link::take_deep_links,
+            file_open::take_opened_files,
+            kiosk::kiosk_heartbeat,
+            splash::app_ready,
+            splash::load_failed,
+            splash::remember_theme,
+            vfs::fs_list,
+            vfs::fs_read,
+            vfs::fs_write,
+            vfs::fs_rename,
+            vfs::fs_move,
+            vfs::fs_trash,
+            vfs::fs_restore,
+            vfs::fs_empty_trash,
+            mounts::mount_list,
+            mounts::mount_add,
+            mounts::mount_remove,
+            applet::applet_import,
+            applet::applet_export,
+            backup::profile_export,
+            backup::profile_import,
+            host_files::host_file_open,
+            host_files::host_file_read,
+            host_files::host_file_save,
+            host_files::host_file_close,
+            host_files::host_file_recent,
+            host_files::host_file_open_recent,
+            drag_drop::drop_read,
+            drag_drop::drop_import,
+            file_open::opened_file_read,
+            search::search,
+            search::search_sync,
+            search::search_index_document,
+            thumbnail::thumbnail_cached,
+            thumbnail::thumbnail_put,
+            blob::blob_put,
+            blob::blob_get,
+            blob::blob_stat,
+            blob::blob_delete,
+            blob::blob_gc,
+            storage::storage_get,
+            storage::storage_set,
+            storage::storage_remove,
+            storage::storage_list,
+            storage::storage_import,
+        ])
+        .register_uri_scheme_protocol(splash::SCHEME, splash::protocol)
+        .register_asynchronous_uri_scheme_protocol(thumbnail::SCHEME, thumbnail::protocol)
+        .register_asynchronous_uri_scheme_protocol(blob::SCHEME, blob::protocol)
+        .setup(move |app| {
+            if let Err(err) = setup(app, &cli) {
+                // Let a retry become the running instance instead of
+                // forwarding to this one
+                if let Some(owner) = app.try_state::<instance::Owner>() {
+                    owner.release();
+                }
+                error::fail(&err, &setup_log_dir);
             }
             Ok(())
         });
 
     builder
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .unwrap_or_else(|err| error::fail(&ShellError::Runtime(err), &log_dir))
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                if let Some(tracker) = app.try_state::<Tracker>() {
+                    tracker.persist();
+                }
+                if let Some(index) = app.try_state::<search::SearchIndex>() {
+                    index.save();
+                }
+                if let Some(store) = app.try_state::<blob::BlobStore>() {
+                    store.save();
+                }
+            }
+            // Quit shortcuts and menu items end up here; only an explicit
+            // exit code (e.g. from a signal handler) may stop a kiosk
+            if let RunEvent::ExitRequested {
+                code: None, api, ..
+            } = event
+            {
+                if app.try_state::<Kiosk>().is_some() {
+                    api.prevent_exit();
+                }
+            }
+        });
+}
+
+/// Everything `main` does once Tauri is up: hands off to a running instance,
+/// resolves where to load the app from and creates the main window.
+fn setup(app: &mut App, cli: &cli::Cli) -> Result<(), ShellError> {
+    // Profiles have separate storage, so each one gets its own instance
+    let state_dir =
+        profile::state_dir(app.handle(), cli.profile.as_deref()).map_err(ShellError::Path)?;
+    let lock_path = state_dir.join(instance::LOCK_FILE_NAME);
+    let handle = app.handle().clone();
+    let claim = instance::claim(&lock_path, &Forwarded::current(), move |forwarded| {
+        handle_forwarded(&handle, forwarded)
+    })
+    .map_err(ShellError::Instance)?;
+    match claim {
+        Claim::Primary(owner) => {
+            app.manage(owner);
+        }
+        Claim::Forwarded => std::process::exit(0),
+    }
+
+    let splash_path = state_dir.join(splash::FILE_NAME);
+    if let Err(err) = splash::open(app, splash::Splash::load(splash_path)) {
+        eprintln!("failed to open the splash window: {err}");
+    }
+
+    let config = ShellConfig::load(app.handle());
+    let home_dir = config
+        .home_dir
+        .clone()
+        .or_else(|| vfs::default_root(cli.profile.as_deref()))
+        .unwrap_or_else(|| state_dir.join(vfs::HOME_DIR_NAME));
+    app.manage(search::SearchIndex::load(state_dir.join(search::FILE_NAME)));
+    let cache_dir = profile::cache_dir(app.handle(), cli.profile.as_deref())
+        .unwrap_or_else(|_| state_dir.clone());
+    app.manage(thumbnail::Thumbnails::open(
+        cache_dir.join(thumbnail::DIR_NAME),
+    ));
+    app.manage(blob::BlobStore::open(state_dir.join(blob::DIR_NAME)));
+    blob::start(app.handle());
+    // Without it the stores the web app keeps here can't be loaded or saved
+    match storage::Storage::open(&state_dir.join(storage::FILE_NAME)) {
+        Ok(storage) => {
+            app.manage(storage);
+        }
+        Err(err) => eprintln!("failed to open {}: {err}", storage::FILE_NAME),
+    }
+    // The web app keeps working from browser storage without it
+    let mounts = mounts::Mounts::load(state_dir.join(mounts::FILE_NAME));
+    match vfs::Vfs::open(home_dir, mounts, host_trash::HostTrash::locate()) {
+        Ok(vfs) => {
+            for mount in vfs.mounts().list() {
+                watcher::watch(app.handle(), &mount);
+            }
+            app.manage(vfs);
+        }
+        Err(err) => eprintln!("failed to open {}: {err}", vfs::HOME_DIR_NAME),
+    }
+    search::start(app.handle());
+    app.manage(backup::PickedArchives::default());
+    app.manage(host_files::HostFiles::load(
+        state_dir.join(host_files::FILE_NAME),
+    ));
+    let env_mode = std::env::var(launch::LAUNCH_MODE_ENV).ok();
+    let env_origin = std::env::var(launch::ORIGIN_ENV).ok();
+    let mode = launch::resolve_mode(cli.launch_mode, env_mode.as_deref(), config.launch_mode);
+    let origin = launch::resolve_origin_for(
+        mode,
+        cli.origin.as_deref(),
+        env_origin.as_deref(),
+        config.origin.as_deref(),
+    )
+    .map_err(ShellError::Origin)?;
+
+    let offline_fallback =
+        mode == LaunchMode::Remote && !probe::is_reachable(&origin, probe::TIMEOUT);
+    let mode = if offline_fallback {
+        eprintln!("{origin} is unreachable, falling back to the bundled app");
+        LaunchMode::Bundled
+    } else {
+        mode
+    };
+
+    let url = match mode {
+        LaunchMode::Remote => {
+            app.add_capability(capability::main_window(
+                "remote",
+                &launch::origin_str(&origin),
+            ))
+            .map_err(ShellError::Capability)?;
+            Some(origin.clone())
+        }
+        // The dev server already gives a stable http origin
+        LaunchMode::Bundled if tauri::is_dev() => None,
+        LaunchMode::Bundled => Some(serve_bundled(app, &config)?),
+    };
+    let info = ShellInfo::new(
+        app.package_info().version.to_string(),
+        mode,
+        launch::origin_str(&origin),
+        offline_fallback,
+        cli.profile.clone(),
+    );
+
+    let kiosk = (cli.kiosk || config.kiosk.enabled).then(|| Kiosk::new(config.kiosk));
+    // Kiosk windows are always fullscreen, so their geometry isn't kept
+    let saved_state = kiosk.is_none().then(|| {
+        let path = state_dir.join(window_state::FILE_NAME);
+        (window_state::load(&path), path)
+    });
+    let window = create_main_window(
+        app,
+        &info,
+        kiosk.as_ref(),
+        saved_state.as_ref().and_then(|(state, _)| state.as_ref()),
+        url,
+    )?;
+    app.manage(info);
+    if let Some((state, path)) = saved_state {
+        app.manage(Tracker::new(path, state));
+        window_state::track(app.handle(), &window);
+    }
+    if let Some(kiosk) = kiosk {
+        app.manage(kiosk);
+        kiosk::start_watchdog(app.handle(), window.clone());
+    }
+    // Without a splash there is nothing to wait for
+    if app.get_webview_window(splash::LABEL).is_none() {
+        splash::finish(app.handle());
+    }
+    if offline_fallback {
+        probe::watch_for_reconnect(window, origin);
+    }
+
+    let cwd = std::env::current_dir().ok();
+    handle_cli(app.handle(), cli, cwd.as_deref());
+
+    // Registers the scheme for installs the bundler didn't set up
+    // (AppImage, dev builds); macOS only knows it from the bundle
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(err) = app.deep_link().register_all() {
+        eprintln!("failed to register {}:// links: {err}", deep_link::SCHEME);
+    }
+    // Links opened while running: macOS delivers them here, other
+    // platforms start a second instance that forwards them
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            deep_link::dispatch(&handle, url.as_str());
+        }
+    });
+    Ok(())
+}
+
+/// Brings the main window forward for a second launch and opens whatever it
+/// asked for.
+fn handle_forwarded(app: &AppHandle, forwarded: Forwarded) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    match cli::parse(forwarded.args) {
+        Ok(Parsed::Run(cli)) => handle_cli(app, &cli, forwarded.cwd.as_deref()),
+        Ok(_) => {}
+        Err(err) => eprintln!("ignoring forwarded arguments: {err}"),
+    }
+}
+
+/// Queues whatever the command line asked to open for the frontend.
+/// Relative file paths are resolved against `cwd`.
+fn handle_cli(app: &AppHandle, cli: &cli::Cli, cwd: Option<&Path>) {
+    if let Some(request) = cli.launch_request() {
+        if !kiosk::allows(app, &request.app_id) {
+            eprintln!(
+                "ignoring `open {}`: app not allowed in kiosk mode",
+                request.app_id
+            );
+            return;
+        }
+        app.state::<EventQueue<LaunchRequest>>().push(app, request);
+    }
+    if let Some(link) = &cli.deep_link {
+        deep_link::dispatch(app, link);
+    }
+    file_open::open(app, &cli.files, cwd, cli.new_window);
+}
+
+/// Serves the bundled `dist` on `http://localhost:<port>` so embeds that
+/// reject custom schemes (YouTube) keep working, and returns that origin.
+fn serve_bundled(app: &App, config: &ShellConfig) -> Result<Url, ShellError> {
+    let port = localhost::pick_port(config.localhost_port.unwrap_or(localhost::DEFAULT_PORT))
+        .map_err(ShellError::Localhost)?;
+    app.handle()
+        .plugin(tauri_plugin_localhost::Builder::new(port).build())
+        .map_err(ShellError::Runtime)?;
+
+    let origin = localhost::origin(port);
+    app.add_capability(capability::main_window("localhost", &origin))
+        .map_err(ShellError::Capability)?;
+    Url::parse(&origin).map_err(|err| ShellError::LocalhostUrl(err.to_string()))
+}
+
+/// Builds the `main` window from `tauri.conf.json`, optionally pointing it at
+/// `url` instead of the configured frontend, locking it down for `kiosk` and
+/// moving it to the geometry saved by the last session. The window stays
+/// hidden behind the splash until the web app is ready.
+fn create_main_window(
+    app: &App,
+    info: &ShellInfo,
+    kiosk: Option<&Kiosk>,
+    saved_state: Option<&window_state::WindowState>,
+    url: Option<Url>,
+) -> Result<WebviewWindow, ShellError> {
+    let mut window_config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|window| window.label == "main")
+        .cloned()
+        .ok_or_else(|| ShellError::WindowConfig("no `main` window in tauri.conf.json".into()))?;
+
+    if let Some(url) = url {
+        window_config.url = WebviewUrl::External(url);
+    }
+
+    let mut builder = WebviewWindowBuilder::from_config(app.handle(), &window_config)
+        .map_err(ShellError::Window)?
+        .visible(false)
+        .initialization_script(launch::init_script(
+            info.launch_mode,
+            &info.origin,
+            info.offline_fallback,
+        ))
+        .initialization_script(splash::init_script())
+        .on_page_load(|window, payload| splash::on_page_load(window.app_handle(), payload.event()));
+    if let Some(name) = &info.profile {
+        builder = builder
+            .data_directory(
+                profile::dir(app.handle(), name)
+                    .map_err(ShellError::Path)?
+                    .join("webview"),
+            )
+            .data_store_identifier(profile::data_store_id(name));
+    }
+
+    if let Some(kiosk) = kiosk {
+        builder = builder
+            .fullscreen(true)
+            .decorations(false)
+            .resizable(false)
+            .closable(false)
+            .devtools(false)
+            .zoom_hotkeys_enabled(false)
+            .initialization_script(kiosk.init_script());
+    }
+
+    // WebView2 stops delivering HTML5 drag events to the page while the
+    // native handler is on, which would break dragging inside Finder
+    if cfg!(windows) {
+        builder = builder.disable_drag_drop_handler();
+    }
+
+    let window = builder.build().map_err(ShellError::Window)?;
+    window.set_title("").map_err(ShellError::Window)?;
+    drag_drop::watch(app.handle(), &window);
+    if let Some(state) = saved_state {
+        if let Err(err) = window_state::restore(&window, state) {
+            eprintln!("failed to restore window geometry: {err}");
+        }
+    }
+    if kiosk.is_some() {
+        // Alt+F4 and the window manager's close button
+        window.on_window_event(|event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+            }
+        });
+    }
+    Ok(window)
 }
@@ -0,0 +1,298 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::atomic;
+use crate::search::{self, SearchIndex};
+use crate::vfs::Vfs;
+use crate::watcher;
+
+/// Virtual directory the mounts appear under.
+pub const VOLUMES_PATH: &str = "/Volumes";
+
+/// Name of the file the mount list is kept in, per profile.
+pub const FILE_NAME: &str = "mounts.json";
+
+/// A host directory shown at `/Volumes/<name>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mount {
+    pub name: String,
+    /// Canonical path of the mounted directory.
+    pub host_path: PathBuf,
+}
+
+impl Mount {
+    /// Virtual path of `host`, or `None` for paths outside the mount or
+    /// hidden from the tree.
+    pub fn virtual_path(&self, host: &Path) -> Option<String> {
+        let mut path = format!("{VOLUMES_PATH}/{}", self.name);
+        for component in host.strip_prefix(&self.host_path).ok()? {
+            let component = component.to_str()?;
+            if component.starts_with('.') {
+                return None;
+            }
+            path.push('/');
+            path.push_str(component);
+        }
+        Some(path)
+    }
+}
+
+/// Mounts in the order they were added, saved to [`FILE_NAME`].
+pub struct Mounts {
+    path: PathBuf,
+    mounts: Mutex<Vec<Mount>>,
+}
+
+impl Mounts {
+    /// Reads the saved mounts; missing or invalid files give none.
+    pub fn load(path: PathBuf) -> Self {
+        let mounts = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| {
+                serde_json::from_str(&contents)
+                    .map_err(|err| eprintln!("ignoring invalid {}: {err}", path.display()))
+                    .ok()
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            mounts: Mutex::new(mounts),
+        }
+    }
+
+    pub fn list(&self) -> Vec<Mount> {
+        self.mounts.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Mount> {
+        self.mounts
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|mount| mount.name == name)
+            .cloned()
+    }
+
+    /// Mounts `host_path` as `name`, or as the directory's own name (made
+    /// unique) when `name` is `None`.
+    pub fn add(&self, host_path: &Path, name: Option<&str>) -> Result<Mount, String> {
+        let host_path = host_path
+            .canonicalize()
+            .map_err(|err| format!("{}: {err}", host_path.display()))?;
+        if !host_path.is_dir() {
+            return Err(format!("{} is not a directory", host_path.display()));
+        }
+
+        let mut mounts = self.mounts.lock().unwrap();
+        if let Some(existing) = mounts.iter().find(|mount| mount.host_path == host_path) {
+            return Ok(existing.clone());
+        }
+        let name = match name {
+            Some(name) => {
+                validate_name(name)?;
+                if mounts.iter().any(|mount| mount.name == name) {
+                    return Err(format!("a volume named `{name}` already exists"));
+                }
+                name.to_string()
+            }
+            None => unique_name(&mounts, &host_path),
+        };
+
+        let mount = Mount { name, host_path };
+        mounts.push(mount.clone());
+        self.save(&mounts)?;
+        Ok(mount)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<(), String> {
+        let mut mounts = self.mounts.lock().unwrap();
+        let before = mounts.len();
+        mounts.retain(|mount| mount.name != name);
+        if mounts.len() == before {
+            return Err(format!("no volume named `{name}`"));
+        }
+        self.save(&mounts)
+    }
+
+    fn save(&self, mounts: &[Mount]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let contents = serde_json::to_vec_pretty(mounts).map_err(|err| err.to_string())?;
+        atomic::write(&self.path, &contents).map_err(|err| err.to_string())
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', '\0'])
+        && name.len() <= 255;
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid volume name `{name}`"))
+    }
+}
+
+fn unique_name(mounts: &[Mount], host_path: &Path) -> String {
+    let base = host_path
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches('.').to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Volume".to_string());
+    let taken = |name: &str| mounts.iter().any(|mount| mount.name == name);
+    let mut name = base.clone();
+    let mut counter = 2;
+    while taken(&name) {
+        name = format!("{base} {counter}");
+        counter += 1;
+    }
+    name
+}
+
+/// Checks that `host`, once symlinks are resolved, is still inside `root`
+/// (which must be canonical). `host` itself may not exist yet, e.g. for a
+/// file about to be written, in which case its parent is checked.
+pub fn confine(root: &Path, host: &Path) -> Result<(), String> {
+    let resolved = match host.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let parent = host.parent().ok_or("path has no parent")?;
+            parent
+                .canonicalize()
+                .map_err(|err| format!("{}: {err}", parent.display()))?
+        }
+        Err(err) => return Err(format!("{}: {err}", host.display())),
+    };
+    if resolved.starts_with(root) {
+        Ok(())
+    } else {
+        Err(format!("{} is outside its volume", host.display()))
+    }
+}
+
+#[tauri::command]
+pub fn mount_list(vfs: State<'_, Vfs>) -> Vec<Mount> {
+    vfs.mounts().list()
+}
+
+/// Mounts the folder the user picks, so the web app can only reach host
+/// folders the user chose. Returns `None` when the picker is cancelled.
+#[tauri::command]
+pub async fn mount_add<R: Runtime>(
+    app: AppHandle<R>,
+    vfs: State<'_, Vfs>,
+    name: Option<String>,
+) -> Result<Option<Mount>, String> {
+    let picked = app
+        .dialog()
+        .file()
+        .set_title("Choose a folder to mount")
+        .blocking_pick_folder()
+        .map(|picked| picked.into_path())
+        .transpose()
+        .map_err(|err| err.to_string())?;
+    let Some(mount) = mount_picked(vfs.mounts(), picked, name.as_deref())? else {
+        return Ok(None);
+    };
+    watcher::watch(&app, &mount);
+    search::crawl_in_background(&app, format!("{VOLUMES_PATH}/{}", mount.name));
+    Ok(Some(mount))
+}
+
+/// Adds the folder picked for [`mount_add`], if there is one.
+fn mount_picked(
+    mounts: &Mounts,
+    picked: Option<PathBuf>,
+    name: Option<&str>,
+) -> Result<Option<Mount>, String> {
+    picked.map(|path| mounts.add(&path, name)).transpose()
+}
+
+#[tauri::command]
+pub fn mount_remove<R: Runtime>(
+    app: AppHandle<R>,
+    name: String,
+    vfs: State<'_, Vfs>,
+) -> Result<(), String> {
+    vfs.mounts().remove(&name)?;
+    watcher::unwatch(&app, &name);
+    app.state::<SearchIndex>()
+        .remove(&format!("{VOLUMES_PATH}/{name}"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_persists_and_removes_mounts() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("Shared");
+        fs::create_dir(&shared).unwrap();
+        let path = dir.path().join(FILE_NAME);
+
+        let mounts = Mounts::load(path.clone());
+        let first = mounts.add(&shared, None).unwrap();
+        assert_eq!(first.name, "Shared");
+        // Same directory again is the same mount
+        assert_eq!(mounts.add(&shared, Some("Other")).unwrap(), first);
+
+        let nested = shared.join("Shared");
+        fs::create_dir(&nested).unwrap();
+        assert_eq!(mounts.add(&nested, None).unwrap().name, "Shared 2");
+
+        let reloaded = Mounts::load(path);
+        assert_eq!(reloaded.list().len(), 2);
+        reloaded.remove("Shared").unwrap();
+        assert!(reloaded.get("Shared").is_none());
+        assert!(reloaded.remove("Shared").is_err());
+    }
+
+    #[test]
+    fn mounts_nothing_without_a_picked_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let mounts = Mounts::load(dir.path().join(FILE_NAME));
+        assert_eq!(mount_picked(&mounts, None, Some("Shared")), Ok(None));
+        assert!(mounts.list().is_empty());
+
+        let picked = mount_picked(&mounts, Some(dir.path().to_path_buf()), Some("Shared"));
+        assert_eq!(picked.unwrap().unwrap().name, "Shared");
+    }
+
+    #[test]
+    fn rejects_files_and_bad_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "x").unwrap();
+        let mounts = Mounts::load(dir.path().join(FILE_NAME));
+        assert!(mounts.add(&file, None).is_err());
+        assert!(mounts.add(dir.path(), Some("../up")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn confine_rejects_symlink_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(root.join("inner")).unwrap();
+        fs::create_dir(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert!(confine(&root, &root.join("inner")).is_ok());
+        assert!(confine(&root, &root.join("inner/new.txt")).is_ok());
+        assert!(confine(&root, &root.join("link")).is_err());
+        assert!(confine(&root, &root.join("link/new.txt")).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use tauri::{Runtime, Url, WebviewWindow};
+
+/// How long to wait for the remote origin before falling back to the bundled app.
+pub const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often to re-probe the remote origin after a fallback.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Whether a TCP connection to `url`'s host can be opened within `timeout`.
+///
+/// Name resolution has no timeout of its own, so the whole probe runs on a
+/// separate thread and is abandoned once `timeout` passes.
+pub fn is_reachable(url: &Url, timeout: Duration) -> bool {
+    let url = url.clone();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let reachable = url
+            .socket_addrs(|| None)
+            .map(|addrs| {
+                addrs
+                    .iter()
+                    .any(|addr| TcpStream::connect_timeout(addr, timeout).is_ok())
+            })
+            .unwrap_or(false);
+        let _ = tx.send(reachable);
+    });
+    rx.recv_timeout(timeout).unwrap_or(false)
+}
+
+/// Keeps probing `url` after a fallback and clears the frontend's offline
+/// flag once it answers. The window stays on the bundled app until the next
+/// launch, which goes back to the remote origin.
+pub fn watch_for_reconnect<R: Runtime>(window: WebviewWindow<R>, url: Url) {
+    thread::spawn(move || loop {
+        thread::sleep(RETRY_INTERVAL);
+        if !is_reachable(&url, TIMEOUT) {
+            continue;
+        }
+        // Ignore the error: it only fails once the window is gone
+        let _ = window.eval("window.__SYAOS_SHELL__.offlineFallback = false;");
+        break;
+    });
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Directory holding a named profile's state, under the app data dir.
+pub fn dir<R: Runtime>(app: &AppHandle<R>, name: &str) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join("profiles").join(name))
+}
+
+/// Where per-profile shell state lives; the default profile uses the app data
+/// dir itself.
+pub fn state_dir<R: Runtime>(app: &AppHandle<R>, name: Option<&str>) -> tauri::Result<PathBuf> {
+    match name {
+        Some(name) => dir(app, name),
+        None => app.path().app_data_dir(),
+    }
+}
+
+/// Where per-profile caches live, under the app cache dir, which the OS may
+/// clear.
+pub fn cache_dir<R: Runtime>(app: &AppHandle<R>, name: Option<&str>) -> tauri::Result<PathBuf> {
+    let dir = app.path().app_cache_dir()?;
+    Ok(match name {
+        Some(name) => dir.join("profiles").join(name),
+        None => dir,
+    })
+}
+
+/// WKWebView has no data directory, so on macOS the profile's webview data
+/// is keyed by an identifier derived from its name instead.
+pub fn data_store_id(name: &str) -> [u8; 16] {
+    let mut id = [0; 16];
+    id[..8].copy_from_slice(&fnv1a(name, 0xcbf2_9ce4_8422_2325).to_be_bytes());
+    id[8..].copy_from_slice(&fnv1a(name, 0x6c62_272e_07bb_0142).to_be_bytes());
+    id
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is the same in every build.
+pub(crate) fn fnv1a(value: &str, offset_basis: u64) -> u64 {
+    value.bytes().fold(offset_basis, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
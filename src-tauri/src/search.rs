@@ -0,0 +1,1121 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::ipc::Request;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::atomic;
+use crate::ipc_body;
+use crate::vfs::{self, FileSystemItem, Vfs};
+use crate::watcher::{Change, ChangeKind};
+
+/// Where the index is kept between launches, in the state directory.
+pub const FILE_NAME: &str = "search-index.json";
+
+/// Header carrying the URI-encoded [`BrowserDocument`] JSON for
+/// `search_index_document`, whose body is the document's content.
+pub const DOCUMENT_HEADER: &str = "x-syaos-search-document";
+
+/// Bumped when the saved format or the tokenizer changes, which drops
+/// older saved indexes.
+const VERSION: u32 = 1;
+
+/// Files on disk with these extensions have their content indexed.
+const TEXT_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "txt", "text", "html", "htm", "app", "csv", "json", "log", "xml",
+];
+/// Extensions whose content is markup; applets are saved as HTML.
+const HTML_EXTENSIONS: &[&str] = &["html", "htm", "app", "xml"];
+
+/// Larger files on disk aren't read.
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+/// How much of a document's text is indexed and kept for snippets.
+const MAX_TEXT_LEN: usize = 256 * 1024;
+/// Longer words (hashes, base64) aren't indexed.
+const MAX_WORD_LEN: usize = 40;
+/// How deep folders are crawled, which also stops symlink loops.
+const MAX_DEPTH: usize = 32;
+
+/// Length of a snippet, in characters.
+const SNIPPET_LEN: usize = 160;
+/// How much text a snippet shows before its first match, in characters.
+const SNIPPET_LEAD: usize = 40;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+
+/// BM25 parameters.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+/// Weight of a term found in a document's name, relative to its content.
+const NAME_WEIGHT: f64 = 2.0;
+
+/// How often a changed index is saved.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// A TextEdit document in `/Documents`.
+    Document,
+    /// An applet in `/Applets`.
+    Applet,
+    /// A text file anywhere else, e.g. on a mounted volume.
+    File,
+}
+
+impl Kind {
+    fn of(path: &str) -> Kind {
+        if path.starts_with("/Documents/") {
+            Kind::Document
+        } else if path.starts_with("/Applets/") {
+            Kind::Applet
+        } else {
+            Kind::File
+        }
+    }
+}
+
+/// Where a document's content came from. Browser documents live in the web
+/// app's IndexedDB, which the shell only sees when it is sent them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Origin {
+    Disk,
+    Browser,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    name: String,
+    kind: Kind,
+    origin: Origin,
+    modified_at: u64,
+    size: u64,
+    /// Extracted, whitespace-collapsed text.
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Saved {
+    version: u32,
+    documents: HashMap<String, Document>,
+}
+
+/// A document in the web app's IndexedDB, as `search_sync` and
+/// `search_index_document` take it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserDocument {
+    pub path: String,
+    #[serde(default)]
+    pub modified_at: u64,
+}
+
+/// A document matching a query.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hit {
+    pub path: String,
+    pub name: String,
+    pub kind: Kind,
+    pub modified_at: u64,
+    pub score: f64,
+    pub snippet: String,
+    /// `[start, end)` ranges of `snippet` that matched, in UTF-16 code
+    /// units like JavaScript string indices.
+    pub highlights: Vec<[usize; 2]>,
+}
+
+/// A term and where it is in the text it came from, in bytes.
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    term: String,
+    start: usize,
+    end: usize,
+}
+
+/// What a document contributes to the postings.
+struct Terms {
+    /// How often each term is in the text.
+    counts: HashMap<String, u32>,
+    /// Number of terms in the text.
+    len: u32,
+    name: HashSet<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// By virtual path.
+    documents: HashMap<String, Document>,
+    terms: HashMap<String, Terms>,
+    /// Paths of the documents whose text or name has each term.
+    postings: HashMap<String, HashSet<String>>,
+    total_len: u64,
+    /// Counts changes, so a save knows whether it has everything.
+    revision: u64,
+    /// The revision last written to disk.
+    saved: u64,
+}
+
+impl Inner {
+    fn insert(&mut self, path: String, document: Document, stemmer: &Stemmer) {
+        self.remove(&path);
+        let tokens = tokenize(&document.text, stemmer);
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *counts.entry(token.term.clone()).or_default() += 1;
+        }
+        let name: HashSet<String> = tokenize(&document.name, stemmer)
+            .into_iter()
+            .map(|token| token.term)
+            .collect();
+        for term in counts.keys().chain(&name) {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(path.clone());
+        }
+        let len = tokens.len() as u32;
+        self.total_len += u64::from(len);
+        self.terms.insert(path.clone(), Terms { counts, len, name });
+        self.documents.insert(path, document);
+        self.revision += 1;
+    }
+
+    fn remove(&mut self, path: &str) -> Option<Document> {
+        let document = self.documents.remove(path)?;
+        if let Some(terms) = self.terms.remove(path) {
+            for term in terms.counts.keys().chain(&terms.name) {
+                if let Some(paths) = self.postings.get_mut(term) {
+                    paths.remove(path);
+                    if paths.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+            self.total_len -= u64::from(terms.len);
+        }
+        self.revision += 1;
+        Some(document)
+    }
+
+    fn paths_within(&self, path: &str) -> Vec<String> {
+        self.documents
+            .keys()
+            .filter(|key| vfs::is_within(key, path))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether content from `origin` modified at `modified_at` should
+    /// replace what is indexed for `path`. Disk and browser copies of a
+    /// path are compared by age so neither keeps overwriting the other.
+    fn is_stale(&self, path: &str, origin: Origin, modified_at: u64, size: u64) -> bool {
+        match self.documents.get(path) {
+            None => true,
+            Some(document) if document.origin == origin => {
+                document.modified_at != modified_at
+                    || (origin == Origin::Disk && document.size != size)
+            }
+            Some(document) => modified_at > document.modified_at,
+        }
+    }
+}
+
+/// Full-text index of TextEdit documents, applets and text files on
+/// mounted volumes, persisted to [`FILE_NAME`].
+pub struct SearchIndex {
+    path: PathBuf,
+    inner: Mutex<Inner>,
+    stemmer: Stemmer,
+}
+
+fn extension(name: &str) -> Option<String> {
+    let (_, ext) = name.rsplit_once('.')?;
+    Some(ext.to_ascii_lowercase())
+}
+
+fn is_text(name: &str) -> bool {
+    extension(name).is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext.as_str()))
+}
+
+fn name_of(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF // Hiragana and Katakana
+            | 0x31F0..=0x31FF // Katakana extensions
+            | 0x3400..=0x4DBF // CJK extension A
+            | 0x4E00..=0x9FFF // CJK unified ideographs
+            | 0xAC00..=0xD7AF // Hangul syllables
+            | 0xF900..=0xFAFF // CJK compatibility ideographs
+            | 0xFF66..=0xFF9F // Halfwidth Katakana
+            | 0x20000..=0x2FA1F // CJK extensions B and later
+    )
+}
+
+/// Lowercases `word`, stemming it when it is English.
+fn normalize_word(word: &str, stemmer: &Stemmer) -> String {
+    let lower = word.to_lowercase();
+    if lower.bytes().all(|byte| byte.is_ascii_alphabetic()) {
+        stemmer.stem(&lower).into_owned()
+    } else {
+        lower
+    }
+}
+
+/// Splits `text` into terms. Words are lowercased and stemmed; CJK text
+/// has no spaces between words, so its runs become overlapping bigrams
+/// (or a single character when the run is one long).
+fn tokenize(text: &str, stemmer: &Stemmer) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if is_cjk(c) {
+            let mut run = Vec::new();
+            while let Some(&(index, c)) = chars.peek().filter(|(_, c)| is_cjk(*c)) {
+                run.push((index, c));
+                chars.next();
+            }
+            if let [(start, c)] = run.as_slice() {
+                tokens.push(Token {
+                    term: c.to_string(),
+                    start: *start,
+                    end: start + c.len_utf8(),
+                });
+            }
+            for pair in run.windows(2) {
+                let [(start, first), (second_start, second)] = pair else {
+                    continue;
+                };
+                tokens.push(Token {
+                    term: format!("{first}{second}"),
+                    start: *start,
+                    end: second_start + second.len_utf8(),
+                });
+            }
+        } else if c.is_alphanumeric() {
+            let mut end = start;
+            while let Some(&(index, c)) = chars
+                .peek()
+                .filter(|(_, c)| c.is_alphanumeric() && !is_cjk(*c))
+            {
+                end = index + c.len_utf8();
+                chars.next();
+            }
+            let word = &text[start..end];
+            if word.chars().count() <= MAX_WORD_LEN {
+                tokens.push(Token {
+                    term: normalize_word(word, stemmer),
+                    start,
+                    end,
+                });
+            }
+        } else {
+            chars.next();
+        }
+    }
+    tokens
+}
+
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((entity(&rest[1..=end])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// The visible text of an HTML page, without tags, comments, scripts or
+/// styles.
+fn strip_html(html: &str) -> String {
+    // ASCII lowercasing keeps byte offsets, so both can be indexed alike
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut rest = 0;
+    let mut from = 0;
+    while let Some(open) = lower[from..].find('<').map(|open| from + open) {
+        let tag = &lower[open..];
+        let is_tag = tag[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        if !is_tag {
+            from = open + 1;
+            continue;
+        }
+        let close = if tag.starts_with("<!--") {
+            "-->"
+        } else if tag.starts_with("<script") {
+            "</script>"
+        } else if tag.starts_with("<style") {
+            "</style>"
+        } else {
+            ">"
+        };
+        text.push_str(&decode_entities(&html[rest..open]));
+        text.push(' ');
+        rest = tag
+            .find(close)
+            .map_or(html.len(), |end| open + end + close.len());
+        from = rest;
+    }
+    text.push_str(&decode_entities(&html[rest..]));
+    text
+}
+
+/// The text of a TipTap document, which TextEdit saves as JSON.
+fn tiptap_text(content: &str) -> Option<String> {
+    fn collect(node: &Value, text: &mut String) {
+        if let Some(value) = node.get("text").and_then(Value::as_str) {
+            text.push_str(value);
+        }
+        if let Some(children) = node.get("content").and_then(Value::as_array) {
+            for child in children {
+                collect(child, text);
+            }
+            text.push(' ');
+        }
+    }
+
+    if !content.trim_start().starts_with('{') {
+        return None;
+    }
+    let doc: Value = serde_json::from_str(content).ok()?;
+    if doc.get("type").and_then(Value::as_str) != Some("doc") {
+        return None;
+    }
+    let mut text = String::new();
+    collect(&doc, &mut text);
+    Some(text)
+}
+
+/// Collapses whitespace so snippets read as one line, and caps the length.
+fn collapse(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len().min(MAX_TEXT_LEN));
+    for word in text.split_whitespace() {
+        if collapsed.len() + word.len() + 1 > MAX_TEXT_LEN {
+            break;
+        }
+        if !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+    }
+    collapsed
+}
+
+/// The searchable text of a file named `name`: markup and TipTap JSON are
+/// reduced to what the user reads.
+pub fn extract_text(name: &str, content: &str) -> String {
+    let is_html = extension(name).is_some_and(|ext| HTML_EXTENSIONS.contains(&ext.as_str()));
+    let text = if is_html {
+        strip_html(content)
+    } else {
+        tiptap_text(content).unwrap_or_else(|| content.to_string())
+    };
+    collapse(&text)
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// Byte offset `chars` characters before (negative) or after `index`.
+fn offset_by(text: &str, index: usize, chars: isize) -> usize {
+    if chars < 0 {
+        text[..index]
+            .char_indices()
+            .rev()
+            .nth(chars.unsigned_abs() - 1)
+            .map_or(0, |(offset, _)| offset)
+    } else {
+        text[index..]
+            .char_indices()
+            .nth(chars as usize)
+            .map_or(text.len(), |(offset, _)| index + offset)
+    }
+}
+
+/// About [`SNIPPET_LEN`] characters of `text` around where most of `terms`
+/// are, with the matches as highlight ranges.
+fn snippet(text: &str, terms: &HashSet<&str>, stemmer: &Stemmer) -> (String, Vec<[usize; 2]>) {
+    let matches: Vec<Token> = tokenize(text, stemmer)
+        .into_iter()
+        .filter(|token| terms.contains(token.term.as_str()))
+        .collect();
+    // The window starting at the match that covers the most distinct terms
+    let covered = |first: usize| {
+        let end = offset_by(
+            text,
+            matches[first].start,
+            SNIPPET_LEN as isize - SNIPPET_LEAD as isize,
+        );
+        matches[first..]
+            .iter()
+            .take_while(|token| token.end <= end)
+            .map(|token| token.term.as_str())
+            .collect::<HashSet<_>>()
+            .len()
+    };
+    let mut best: Option<(usize, usize)> = None;
+    for first in 0..matches.len() {
+        let count = covered(first);
+        if best.is_none_or(|(_, most)| count > most) {
+            best = Some((first, count));
+        }
+    }
+
+    let mut start = match best {
+        Some((first, _)) => offset_by(text, matches[first].start, -(SNIPPET_LEAD as isize)),
+        None => 0,
+    };
+    // Start on a word when one begins shortly before
+    if start > 0 {
+        if let Some(space) = text[start..].find(' ').filter(|&space| space < 16) {
+            let word = start + space + 1;
+            if best.is_none_or(|(first, _)| word <= matches[first].start) {
+                start = word;
+            }
+        }
+    }
+    let mut end = offset_by(text, start, SNIPPET_LEN as isize);
+    if end < text.len() {
+        if let Some(space) = text[start..end]
+            .rfind(' ')
+            .filter(|&space| space > SNIPPET_LEN / 2)
+        {
+            end = start + space;
+        }
+    }
+
+    let lead = if start > 0 { "…" } else { "" };
+    let trail = if end < text.len() { "…" } else { "" };
+    let mut highlights: Vec<[usize; 2]> = Vec::new();
+    for token in matches
+        .iter()
+        .filter(|token| token.start >= start && token.end <= end)
+    {
+        let from = utf16_len(lead) + utf16_len(&text[start..token.start]);
+        let to = from + utf16_len(&text[token.start..token.end]);
+        match highlights.last_mut() {
+            // Overlapping CJK bigrams highlight as one range
+            Some(last) if from <= last[1] => last[1] = last[1].max(to),
+            _ => highlights.push([from, to]),
+        }
+    }
+    (format!("{lead}{}{trail}", &text[start..end]), highlights)
+}
+
+impl SearchIndex {
+    /// Reads the saved index; a missing, invalid or outdated file gives an
+    /// empty one that the crawl fills.
+    pub fn load(path: PathBuf) -> Self {
+        let index = Self {
+            path,
+            inner: Mutex::default(),
+            stemmer: Stemmer::create(Algorithm::English),
+        };
+        let saved = fs::read(&index.path).ok().and_then(|contents| {
+            serde_json::from_slice::<Saved>(&contents)
+                .map_err(|err| eprintln!("ignoring invalid {}: {err}", index.path.display()))
+                .ok()
+        });
+        if let Some(saved) = saved.filter(|saved| saved.version == VERSION) {
+            let mut inner = index.inner.lock().unwrap();
+            for (path, document) in saved.documents {
+                inner.insert(path, document, &index.stemmer);
+            }
+            inner.saved = inner.revision;
+        }
+        index
+    }
+
+    /// Writes the index if it changed since it was last saved. It only
+    /// counts as saved once the write succeeds, so a failed save is retried
+    /// next time.
+    pub fn save(&self) {
+        let (revision, contents) = {
+            let inner = self.inner.lock().unwrap();
+            if inner.saved == inner.revision {
+                return;
+            }
+            let contents =
+                serde_json::to_vec(&json!({ "version": VERSION, "documents": &inner.documents }));
+            (inner.revision, contents)
+        };
+        let result = contents
+            .map_err(io::Error::from)
+            .and_then(|contents| atomic::write(&self.path, &contents));
+        match result {
+            Ok(()) => {
+                let mut inner = self.inner.lock().unwrap();
+                inner.saved = inner.saved.max(revision);
+            }
+            Err(err) => eprintln!("failed to save {}: {err}", self.path.display()),
+        }
+    }
+
+    fn put(&self, path: &str, origin: Origin, modified_at: u64, content: &str) {
+        let name = name_of(path).to_string();
+        let document = Document {
+            text: extract_text(&name, content),
+            kind: Kind::of(path),
+            size: content.len() as u64,
+            name,
+            origin,
+            modified_at,
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), document, &self.stemmer);
+    }
+
+    /// Drops `path` and everything under it.
+    pub fn remove(&self, path: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        for path in inner.paths_within(path) {
+            inner.remove(&path);
+        }
+    }
+
+    /// Moves what is indexed under `from` to `to`, without reading it
+    /// again.
+    pub fn relocate(&self, from: &str, to: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        for path in inner.paths_within(from) {
+            let Some(mut document) = inner.remove(&path) else {
+                continue;
+            };
+            let path = format!("{to}{}", &path[from.len()..]);
+            document.name = name_of(&path).to_string();
+            document.kind = Kind::of(&path);
+            inner.insert(path, document, &self.stemmer);
+        }
+    }
+
+    /// Indexes the text files at and under `path` that changed since they
+    /// were last read, and drops the ones that are gone.
+    pub fn crawl(&self, vfs: &Vfs, path: &str) {
+        let mut seen = HashSet::new();
+        if path == "/" {
+            self.crawl_dir(vfs, path, 0, &mut seen);
+        } else {
+            let item = vfs
+                .list(vfs::parent_path(path))
+                .ok()
+                .and_then(|items| items.into_iter().find(|item| item.path == path));
+            if let Some(item) = item {
+                self.crawl_item(vfs, &item, 0, &mut seen);
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        for path in inner.paths_within(path) {
+            let gone = !seen.contains(&path) && inner.documents[&path].origin == Origin::Disk;
+            if gone {
+                inner.remove(&path);
+            }
+        }
+    }
+
+    fn crawl_dir(&self, vfs: &Vfs, path: &str, depth: usize, seen: &mut HashSet<String>) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        match vfs.list(path) {
+            Ok(items) => {
+                for item in items {
+                    self.crawl_item(vfs, &item, depth + 1, seen);
+                }
+            }
+            Err(err) => eprintln!("failed to index {err}"),
+        }
+    }
+
+    fn crawl_item(
+        &self,
+        vfs: &Vfs,
+        item: &FileSystemItem,
+        depth: usize,
+        seen: &mut HashSet<String>,
+    ) {
+        if item.is_directory {
+            return self.crawl_dir(vfs, &item.path, depth, seen);
+        }
+        let size = item.size.unwrap_or_default();
+        if !is_text(&item.name) || size > MAX_FILE_SIZE {
+            return;
+        }
+        seen.insert(item.path.clone());
+        let stale =
+            self.inner
+                .lock()
+                .unwrap()
+                .is_stale(&item.path, Origin::Disk, item.modified_at, size);
+        if !stale {
+            return;
+        }
+        match vfs.read(&item.path) {
+            // Binary files with a text extension (e.g. packaged applets)
+            // are found by name only
+            Ok(content) => {
+                let content = String::from_utf8(content).unwrap_or_default();
+                self.put(&item.path, Origin::Disk, item.modified_at, &content);
+            }
+            Err(err) => eprintln!("failed to index {err}"),
+        }
+    }
+
+    /// Takes the list of browser documents, dropping the ones no longer
+    /// in it, and returns the paths whose content needs to be sent.
+    pub fn sync(&self, documents: &[BrowserDocument]) -> Vec<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let listed: HashSet<&str> = documents.iter().map(|doc| doc.path.as_str()).collect();
+        let gone: Vec<String> = inner
+            .documents
+            .iter()
+            .filter(|(path, doc)| doc.origin == Origin::Browser && !listed.contains(path.as_str()))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in gone {
+            inner.remove(&path);
+        }
+        documents
+            .iter()
+            .filter(|doc| inner.is_stale(&doc.path, Origin::Browser, doc.modified_at, 0))
+            .map(|doc| doc.path.clone())
+            .collect()
+    }
+
+    /// The terms a document must have one of, for each word of `query`. A
+    /// word the user may still be typing, and a lone CJK character, match
+    /// as prefixes.
+    fn query_terms(&self, inner: &Inner, query: &str) -> Vec<HashSet<String>> {
+        let tokens = tokenize(query, &self.stemmer);
+        let typing = !query.ends_with(char::is_whitespace);
+        let mut groups: Vec<HashSet<String>> = Vec::new();
+        for (index, token) in tokens.iter().enumerate() {
+            let word = query[token.start..token.end].to_lowercase();
+            let is_last = index + 1 == tokens.len() && token.end == query.len();
+            let single_cjk = word.chars().count() == 1 && word.chars().all(is_cjk);
+            let mut group = HashSet::from([token.term.clone()]);
+            if (is_last && typing) || single_cjk {
+                group.extend(
+                    inner
+                        .postings
+                        .keys()
+                        .filter(|term| term.starts_with(&word) || term.starts_with(&token.term))
+                        .cloned(),
+                );
+            }
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    /// Documents under `scope` that have every word of `query`, best
+    /// first, ranked with BM25 plus a boost for matches in the name.
+    pub fn query(&self, query: &str, scope: Option<&str>, limit: usize) -> Vec<Hit> {
+        let inner = self.inner.lock().unwrap();
+        let groups = self.query_terms(&inner, query);
+        if groups.is_empty() {
+            return Vec::new();
+        }
+
+        let count = inner.documents.len() as f64;
+        let avg_len = (inner.total_len as f64 / count.max(1.0)).max(1.0);
+        let idf = |term: &str| {
+            let frequency = inner.postings.get(term).map_or(0, HashSet::len) as f64;
+            (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln()
+        };
+        let matching = |group: &HashSet<String>| -> HashSet<&String> {
+            group
+                .iter()
+                .filter_map(|term| inner.postings.get(term))
+                .flatten()
+                .collect()
+        };
+
+        let mut candidates = matching(&groups[0]);
+        for group in &groups[1..] {
+            let paths = matching(group);
+            candidates.retain(|path| paths.contains(path));
+        }
+        let mut scored: Vec<(&String, f64)> = candidates
+            .into_iter()
+            .filter(|path| scope.is_none_or(|scope| vfs::is_within(path, scope)))
+            .map(|path| {
+                let terms = &inner.terms[path];
+                let norm = K1 * (1.0 - B + B * f64::from(terms.len) / avg_len);
+                let score = groups
+                    .iter()
+                    .map(|group| {
+                        group
+                            .iter()
+                            .map(|term| {
+                                let frequency =
+                                    f64::from(terms.counts.get(term).copied().unwrap_or(0));
+                                let name = if terms.name.contains(term) {
+                                    NAME_WEIGHT
+                                } else {
+                                    0.0
+                                };
+                                idf(term) * (frequency * (K1 + 1.0) / (frequency + norm) + name)
+                            })
+                            .fold(0.0, f64::max)
+                    })
+                    .sum();
+                (path, score)
+            })
+            .collect();
+        scored.sort_by(|(a_path, a), (b_path, b)| {
+            b.total_cmp(a).then_with(|| {
+                let modified = |path: &str| inner.documents[path].modified_at;
+                modified(b_path).cmp(&modified(a_path))
+            })
+        });
+        scored.truncate(limit);
+
+        scored
+            .into_iter()
+            .map(|(path, score)| {
+                let document = &inner.documents[path];
+                let counts = &inner.terms[path].counts;
+                let terms: HashSet<&str> = groups
+                    .iter()
+                    .flatten()
+                    .filter(|term| counts.contains_key(*term))
+                    .map(String::as_str)
+                    .collect();
+                let (snippet, highlights) = snippet(&document.text, &terms, &self.stemmer);
+                Hit {
+                    path: path.clone(),
+                    name: document.name.clone(),
+                    kind: document.kind,
+                    modified_at: document.modified_at,
+                    score,
+                    snippet,
+                    highlights,
+                }
+            })
+            .collect()
+    }
+}
+
+fn with_index<R: Runtime>(app: &AppHandle<R>, f: impl FnOnce(&SearchIndex, &Vfs)) {
+    if let (Some(index), Some(vfs)) = (app.try_state::<SearchIndex>(), app.try_state::<Vfs>()) {
+        f(&index, &vfs);
+    }
+}
+
+/// Crawls `path` on a background thread, e.g. a volume that was just
+/// mounted.
+pub fn crawl_in_background<R: Runtime>(app: &AppHandle<R>, path: String) {
+    let app = app.clone();
+    thread::spawn(move || with_index(&app, |index, vfs| index.crawl(vfs, &path)));
+}
+
+/// Brings the index up to date with the whole tree, then saves it
+/// whenever it changed.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    thread::spawn(move || {
+        with_index(&app, |index, vfs| index.crawl(vfs, "/"));
+        loop {
+            if let Some(index) = app.try_state::<SearchIndex>() {
+                index.save();
+            }
+            thread::sleep(SAVE_INTERVAL);
+        }
+    });
+}
+
+/// Applies changes reported by a volume's watcher.
+pub fn apply<R: Runtime>(app: &AppHandle<R>, changes: &[Change]) {
+    with_index(app, |index, vfs| {
+        for change in changes {
+            match (change.kind, &change.old_path) {
+                (ChangeKind::Delete, _) => index.remove(&change.path),
+                (ChangeKind::Rename, Some(old_path)) => index.relocate(old_path, &change.path),
+                _ => index.crawl(vfs, &change.path),
+            }
+        }
+    });
+}
+
+/// Ranked matches for `query`, optionally only under the path `scope`.
+#[tauri::command]
+pub async fn search(
+    query: String,
+    scope: Option<String>,
+    limit: Option<usize>,
+    index: State<'_, SearchIndex>,
+) -> Result<Vec<Hit>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    Ok(index.query(&query, scope.as_deref(), limit))
+}
+
+/// Takes every document in the web app's IndexedDB and returns the paths
+/// that need `search_index_document`.
+#[tauri::command]
+pub fn search_sync(documents: Vec<BrowserDocument>, index: State<'_, SearchIndex>) -> Vec<String> {
+    index.sync(&documents)
+}
+
+/// Takes the content as the raw request body and the document as
+/// URI-encoded JSON in the [`DOCUMENT_HEADER`] header.
+#[tauri::command]
+pub async fn search_index_document(
+    request: Request<'_>,
+    index: State<'_, SearchIndex>,
+) -> Result<(), String> {
+    let (document, content): (BrowserDocument, _) = ipc_body::decode(&request, DOCUMENT_HEADER)?;
+    index.put(
+        &document.path,
+        Origin::Browser,
+        document.modified_at,
+        &String::from_utf8_lossy(content),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> (tempfile::TempDir, SearchIndex) {
+        let dir = tempfile::tempdir().unwrap();
+        let index = SearchIndex::load(dir.path().join(FILE_NAME));
+        (dir, index)
+    }
+
+    fn terms(text: &str) -> Vec<String> {
+        tokenize(text, &Stemmer::create(Algorithm::English))
+            .into_iter()
+            .map(|token| token.term)
+            .collect()
+    }
+
+    fn paths(hits: &[Hit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.path.as_str()).collect()
+    }
+
+    #[test]
+    fn stems_words_and_splits_cjk_into_bigrams() {
+        assert_eq!(terms("Running, runs!"), ["run", "run"]);
+        assert_eq!(terms("東京タワー"), ["東京", "京タ", "タワ", "ワー"]);
+        assert_eq!(terms("the 猫 café"), ["the", "猫", "café"]);
+        assert_eq!(terms("한국어 문서"), ["한국", "국어", "문서"]);
+    }
+
+    #[test]
+    fn extracts_text_from_html_and_tiptap() {
+        let html = "<html><style>p{}</style><script>let a = 1 < 2;</script>\
+                    <p>Fish &amp; chips<br>a &lt; b &#x263A;</p><!-- note --></html>";
+        assert_eq!(extract_text("Menu.app", html), "Fish & chips a < b ☺");
+        let doc = r#"{"type":"doc","content":[{"type":"paragraph","content":[
+            {"type":"text","text":"Hello"},{"type":"text","text":" world"}]},
+            {"type":"paragraph","content":[{"type":"text","text":"again"}]}]}"#;
+        assert_eq!(extract_text("Notes.md", doc), "Hello world again");
+        assert_eq!(extract_text("a.txt", "  two\n\nlines "), "two lines");
+    }
+
+    #[test]
+    fn ranks_matches_and_highlights_snippets() {
+        let (_dir, index) = index();
+        index.put(
+            "/Documents/Garden.md",
+            Origin::Browser,
+            1,
+            "Tomatoes need sun. Water the tomatoes daily.",
+        );
+        index.put(
+            "/Documents/Shopping.md",
+            Origin::Browser,
+            2,
+            "Buy bread, milk and a tomato.",
+        );
+        index.put(
+            "/Applets/Timer.app",
+            Origin::Browser,
+            3,
+            "<h1>Kitchen timer</h1>",
+        );
+
+        let hits = index.query("tomato", None, 10);
+        assert_eq!(
+            paths(&hits),
+            ["/Documents/Garden.md", "/Documents/Shopping.md"]
+        );
+        let hit = &hits[1];
+        assert_eq!(hit.snippet, "Buy bread, milk and a tomato.");
+        assert_eq!(hit.highlights, [[22, 28]]);
+
+        // Every word must match; the last one may be a prefix
+        assert_eq!(
+            paths(&index.query("water tomato", None, 10)),
+            ["/Documents/Garden.md"]
+        );
+        assert_eq!(
+            paths(&index.query("kitch", None, 10)),
+            ["/Applets/Timer.app"]
+        );
+        assert!(index.query("kitch ", None, 10).is_empty());
+        // Names match too
+        assert_eq!(
+            paths(&index.query("shopping", None, 10)),
+            ["/Documents/Shopping.md"]
+        );
+        assert!(index.query("tomato", Some("/Applets"), 10).is_empty());
+    }
+
+    #[test]
+    fn matches_cjk_words_and_characters() {
+        let (_dir, index) = index();
+        index.put(
+            "/Documents/旅行.md",
+            Origin::Browser,
+            1,
+            "明日は東京タワーに行きます。",
+        );
+        let hits = index.query("東京", None, 10);
+        assert_eq!(paths(&hits), ["/Documents/旅行.md"]);
+        assert_eq!(hits[0].highlights, [[3, 5]]);
+        assert_eq!(index.query("東京タワー", None, 10)[0].highlights, [[3, 8]]);
+        assert_eq!(index.query("旅", None, 10).len(), 1);
+    }
+
+    #[test]
+    fn centers_snippets_on_matches() {
+        let (_dir, index) = index();
+        let text = format!("{} needle and more", "filler ".repeat(60));
+        index.put("/Documents/Long.txt", Origin::Browser, 1, &text);
+        let hit = &index.query("needle", None, 10)[0];
+        assert!(hit.snippet.starts_with('…'));
+        let [start, end] = hit.highlights[0];
+        let snippet: Vec<u16> = hit.snippet.encode_utf16().collect();
+        assert_eq!(String::from_utf16(&snippet[start..end]).unwrap(), "needle");
+    }
+
+    #[test]
+    fn syncs_browser_documents_and_relocates() {
+        let (_dir, index) = index();
+        index.put("/Documents/a.md", Origin::Browser, 1, "alpha");
+        index.put("/Documents/b.md", Origin::Browser, 1, "beta");
+        let listed = |path: &str, modified_at| BrowserDocument {
+            path: path.to_string(),
+            modified_at,
+        };
+        let stale = index.sync(&[listed("/Documents/a.md", 2), listed("/Documents/c.md", 1)]);
+        assert_eq!(stale, ["/Documents/a.md", "/Documents/c.md"]);
+        assert!(index.query("beta", None, 10).is_empty());
+
+        index.relocate("/Documents", "/Archive");
+        let hits = index.query("alpha", None, 10);
+        assert_eq!(paths(&hits), ["/Archive/a.md"]);
+        assert_eq!(hits[0].kind, Kind::File);
+        index.remove("/Archive");
+        assert!(index.query("alpha", None, 10).is_empty());
+    }
+
+    #[test]
+    fn retries_failed_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join(FILE_NAME);
+        let index = SearchIndex::load(path.clone());
+        index.put("/Documents/a.md", Origin::Browser, 1, "fjords");
+        index.save();
+        assert!(!path.exists());
+
+        fs::create_dir(dir.path().join("state")).unwrap();
+        index.save();
+        let reloaded = SearchIndex::load(path);
+        assert_eq!(
+            paths(&reloaded.query("fjord", None, 10)),
+            ["/Documents/a.md"]
+        );
+    }
+
+    #[test]
+    fn crawls_the_tree_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("home");
+        fs::create_dir_all(root.join("Documents/Trips")).unwrap();
+        fs::write(
+            root.join("Documents/Trips/Oslo.md"),
+            "# Oslo\nFjords and ferries",
+        )
+        .unwrap();
+        fs::write(root.join("Documents/photo.png"), "fjords").unwrap();
+        let mounts = crate::mounts::Mounts::load(dir.path().join("mounts.json"));
+        let vfs = Vfs::open(root.clone(), mounts, None).unwrap();
+
+        let path = dir.path().join(FILE_NAME);
+        let index = SearchIndex::load(path.clone());
+        index.crawl(&vfs, "/");
+        assert_eq!(
+            paths(&index.query("fjord", None, 10)),
+            ["/Documents/Trips/Oslo.md"]
+        );
+        index.save();
+
+        fs::remove_file(root.join("Documents/Trips/Oslo.md")).unwrap();
+        let index = SearchIndex::load(path);
+        assert_eq!(index.query("ferry", None, 10).len(), 1);
+        index.crawl(&vfs, "/Documents");
+        assert!(index.query("ferry", None, 10).is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::launch::LaunchMode;
+
+/// Version of the IPC surface the shell exposes to the web app. Bump it when
+/// commands are added or changed, and raise `REQUIRED_SHELL_API_VERSION` in
+/// `src/utils/shell.ts` once the web app depends on them.
+///
+/// The first version each command set is guaranteed in:
+///
+/// - 1: `get_shell_info`, `check_shell_compatibility`
+/// - 2: `take_launch_requests`, `take_deep_links`, `kiosk_heartbeat`,
+///   `app_ready`, `remember_theme`
+/// - 3: `fs_*`, `mount_*`
+/// - 4: `profile_export`, `profile_import`
+/// - 5: `host_file_*`
+/// - 6: `drop_read`, `drop_import`
+/// - 7: `take_opened_files`, `opened_file_read`
+/// - 8: `applet_import`, `applet_export`
+/// - 9: `search`, `search_sync`, `search_index_document`
+/// - 10: `thumbnail_cached`, `thumbnail_put`
+/// - 11: `blob_*`
+/// - 12: `storage_*`
+/// - 13: `mount_add` only mounts a folder the user picks
+/// - 14: opened TextEdit files carry a `hostFile` handle
+/// - 15: `profile_import` takes the `token` of a dry run's report, not a path
+pub const API_VERSION: u32 = 15;
+
+/// Commit the binary was built from, set by `build.rs`.
+pub const BUILD_COMMIT: &str = env!("SYAOS_BUILD_COMMIT");
+
+/// What the frontend can learn about the shell it is running in.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellInfo {
+    pub version: String,
+    pub api_version: u32,
+    pub build_commit: &'static str,
+    pub features: Vec<&'static str>,
+    pub launch_mode: LaunchMode,
+    pub origin: String,
+    pub offline_fallback: bool,
+    /// Named profile from `--profile`, or `None` for the default one.
+    pub profile: Option<String>,
+}
+
+impl ShellInfo {
+    pub fn new(
+        version: String,
+        launch_mode: LaunchMode,
+        origin: String,
+        offline_fallback: bool,
+        profile: Option<String>,
+    ) -> Self {
+        Self {
+            version,
+            api_version: API_VERSION,
+            build_commit: BUILD_COMMIT,
+            features: enabled_features(),
+            launch_mode,
+            origin,
+            offline_fallback,
+            profile,
+        }
+    }
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "custom-protocol") {
+        features.push("custom-protocol");
+    }
+    features
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum Compatibility {
+    Compatible,
+    /// The web build relies on commands this binary does not have.
+    #[serde(rename_all = "camelCase")]
+    ShellTooOld {
+        api_version: u32,
+        required_api_version: u32,
+    },
+}
+
+pub fn check(required_api_version: u32) -> Compatibility {
+    if required_api_version <= API_VERSION {
+        Compatibility::Compatible
+    } else {
+        Compatibility::ShellTooOld {
+            api_version: API_VERSION,
+            required_api_version,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_shell_info(info: State<'_, ShellInfo>) -> ShellInfo {
+    info.inner().clone()
+}
+
+/// Called by the web app at startup with the shell API version it was built
+/// against; the frontend decides how to surface an incompatible shell.
+#[tauri::command]
+pub fn check_shell_compatibility(required_api_version: u32) -> Compatibility {
+    let compatibility = check(required_api_version);
+    if compatibility != Compatibility::Compatible {
+        eprintln!(
+            "web build requires shell API {required_api_version}, this shell provides {API_VERSION}"
+        );
+    }
+    compatibility
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_the_required_api_version() {
+        assert_eq!(check(1), Compatibility::Compatible);
+        assert_eq!(check(API_VERSION), Compatibility::Compatible);
+        assert_eq!(
+            check(API_VERSION + 1),
+            Compatibility::ShellTooOld {
+                api_version: API_VERSION,
+                required_api_version: API_VERSION + 1,
+            }
+        );
+    }
+}
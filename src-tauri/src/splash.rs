@@ -0,0 +1,275 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::webview::PageLoadEvent;
+use tauri::{
+    App, AppHandle, Manager, Runtime, State, UriSchemeContext, Url, WebviewUrl,
+    WebviewWindowBuilder,
+};
+
+/// Label of the splash window.
+pub const LABEL: &str = "splash";
+
+/// Scheme the splash page and its image are served from.
+pub const SCHEME: &str = "syaos-splash";
+
+/// Name of the file the last-used theme is kept in, per profile.
+pub const FILE_NAME: &str = "splash.json";
+
+/// How long to wait for `app_ready` after the page has loaded, for web
+/// builds that predate it.
+const READY_GRACE: Duration = Duration::from_secs(10);
+
+/// How long loading may take before the splash reports a problem.
+const SLOW_LOAD: Duration = Duration::from_secs(30);
+
+/// Run in the main window: reports scripts or stylesheets that fail to
+/// load, and uncaught errors, so the splash doesn't wait for [`SLOW_LOAD`].
+const LOAD_ERROR_SCRIPT: &str = r#"
+window.addEventListener(
+  "error",
+  (event) => {
+    const target = event.target;
+    const message =
+      target instanceof HTMLScriptElement || target instanceof HTMLLinkElement
+        ? `Couldn't load ${target.src || target.href}`
+        : event.message;
+    if (message) {
+      window.__TAURI_INTERNALS__
+        ?.invoke("load_failed", { message })
+        .catch(() => {});
+    }
+  },
+  true
+);
+"#;
+
+/// OS themes of the web app (`OsThemeId` in `src/themes/types.ts`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    System7,
+    #[default]
+    Macosx,
+    Xp,
+    Win98,
+}
+
+impl Theme {
+    /// Background, text colour and boot image, matching `BootScreen.tsx`.
+    fn style(self) -> (&'static str, &'static str, &'static [u8], &'static str) {
+        match self {
+            Theme::System7 => (
+                "#ffffff",
+                "#000000",
+                include_bytes!("../../public/assets/splash/hello.svg"),
+                "image/svg+xml",
+            ),
+            Theme::Macosx => (
+                "#ececec",
+                "#333333",
+                include_bytes!("../../public/assets/splash/macos.svg"),
+                "image/svg+xml",
+            ),
+            Theme::Xp => (
+                "#000000",
+                "#ffffff",
+                include_bytes!("../../public/assets/splash/xp-boot.gif"),
+                "image/gif",
+            ),
+            Theme::Win98 => (
+                "#000000",
+                "#ffffff",
+                include_bytes!("../../public/assets/splash/win98.gif"),
+                "image/gif",
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Saved {
+    theme: Theme,
+}
+
+/// Managed state of the splash window.
+pub struct Splash {
+    path: PathBuf,
+    theme: Theme,
+    status: Mutex<(String, bool)>,
+    finished: Mutex<bool>,
+}
+
+impl Splash {
+    /// Reads the theme the web app last reported from `path`.
+    pub fn load(path: PathBuf) -> Self {
+        let theme = load_theme(&path).unwrap_or_default();
+        Self {
+            path,
+            theme,
+            status: Mutex::new(("Starting…".to_string(), false)),
+            finished: Mutex::new(false),
+        }
+    }
+
+    fn page(&self) -> String {
+        let (background, foreground, _, _) = self.theme.style();
+        let (status, failed) = self.status.lock().unwrap().clone();
+        include_str!("splash.html")
+            .replace("{background}", background)
+            .replace("{foreground}", foreground)
+            .replace("{status}", &serde_json::to_string(&status).unwrap())
+            .replace("{failed}", &failed.to_string())
+    }
+}
+
+fn load_theme(path: &Path) -> Option<Theme> {
+    let contents = fs::read_to_string(path).ok()?;
+    let saved: Saved = serde_json::from_str(&contents).ok()?;
+    Some(saved.theme)
+}
+
+/// Handler for [`SCHEME`]: `/` is the page, `/image` the theme's boot image.
+pub fn protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+) -> Response<Cow<'static, [u8]>> {
+    let Some(splash) = ctx.app_handle().try_state::<Splash>() else {
+        return not_found();
+    };
+    let (body, mime): (Cow<'static, [u8]>, _) = match request.uri().path() {
+        "/" => (splash.page().into_bytes().into(), "text/html"),
+        "/image" => {
+            let (_, _, image, mime) = splash.theme.style();
+            (image.into(), mime)
+        }
+        _ => return not_found(),
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, mime)
+        .body(body)
+        .unwrap()
+}
+
+fn not_found() -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap()
+}
+
+/// URL of the splash page; Windows serves custom schemes over http.
+fn url() -> Url {
+    let url = if cfg!(windows) {
+        format!("http://{SCHEME}.localhost/")
+    } else {
+        format!("{SCHEME}://localhost/")
+    };
+    Url::parse(&url).unwrap()
+}
+
+/// Shows the splash window and starts reporting slow loads.
+pub fn open(app: &App, splash: Splash) -> tauri::Result<()> {
+    app.manage(splash);
+    WebviewWindowBuilder::new(app, LABEL, WebviewUrl::CustomProtocol(url()))
+        .title("syaOS")
+        .inner_size(480.0, 320.0)
+        .center()
+        .resizable(false)
+        .decorations(false)
+        .focused(true)
+        .build()?;
+
+    let handle = app.handle().clone();
+    thread::spawn(move || {
+        thread::sleep(SLOW_LOAD);
+        set_status(
+            &handle,
+            "syaOS is taking a long time to load. Check your network connection.",
+            true,
+        );
+    });
+    Ok(())
+}
+
+/// Updates the text under the boot image.
+pub fn set_status<R: Runtime>(app: &AppHandle<R>, text: &str, failed: bool) {
+    let Some(splash) = app.try_state::<Splash>() else {
+        return;
+    };
+    if *splash.finished.lock().unwrap() {
+        return;
+    }
+    *splash.status.lock().unwrap() = (text.to_string(), failed);
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let text = serde_json::to_string(text).unwrap();
+        let _ = window.eval(format!("window.setStatus?.({text}, {failed});"));
+    }
+}
+
+/// Reports progress of the main window's page load.
+pub fn on_page_load<R: Runtime>(app: &AppHandle<R>, event: PageLoadEvent) {
+    match event {
+        PageLoadEvent::Started => set_status(app, "Loading…", false),
+        PageLoadEvent::Finished => {
+            set_status(app, "Starting desktop…", false);
+            let handle = app.clone();
+            thread::spawn(move || {
+                thread::sleep(READY_GRACE);
+                finish(&handle);
+            });
+        }
+    }
+}
+
+/// Script for the main window that reports load errors to the splash.
+pub fn init_script() -> &'static str {
+    LOAD_ERROR_SCRIPT
+}
+
+/// Swaps the splash for the main window. Safe to call more than once.
+pub fn finish<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(splash) = app.try_state::<Splash>() {
+        let mut finished = splash.finished.lock().unwrap();
+        if *finished {
+            return;
+        }
+        *finished = true;
+    }
+    if let Some(main) = app.get_webview_window("main") {
+        let _ = main.show();
+        let _ = main.set_focus();
+    }
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.close();
+    }
+}
+
+/// Called by the web app once the desktop has rendered.
+#[tauri::command]
+pub fn app_ready<R: Runtime>(app: AppHandle<R>) {
+    finish(&app);
+}
+
+/// Called from [`init_script`] when the page fails to load.
+#[tauri::command]
+pub fn load_failed<R: Runtime>(message: String, app: AppHandle<R>) {
+    set_status(&app, &format!("syaOS couldn't load: {message}"), true);
+}
+
+/// Called by the web app whenever the OS theme is loaded or changed, so the
+/// next launch's splash matches it.
+#[tauri::command]
+pub fn remember_theme(theme: Theme, splash: State<'_, Splash>) -> Result<(), String> {
+    if let Some(parent) = splash.path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let contents = serde_json::to_vec(&Saved { theme }).map_err(|err| err.to_string())?;
+    fs::write(&splash.path, contents).map_err(|err| err.to_string())
+}
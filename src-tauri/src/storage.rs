@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+/// Name of the database file, per profile.
+pub const FILE_NAME: &str = "storage.sqlite3";
+
+/// Key in the `meta` table recording when localStorage was imported.
+const IMPORTED_KEY: &str = "localStorageImportedAt";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        key TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL,
+        updated_at INTEGER NOT NULL
+    ) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL
+    ) WITHOUT ROWID;
+";
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// A key's size, as `storage_list` reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyStat {
+    pub key: String,
+    /// Of the value, in UTF-8 bytes.
+    pub size: u64,
+    /// Milliseconds since the epoch.
+    pub updated_at: i64,
+}
+
+/// String values by key, for the web app's zustand stores, in a SQLite
+/// database outside the webview's data so clearing that keeps them. Every
+/// write is its own transaction, or one for a whole import, so a crash
+/// leaves either the old value or the new one.
+pub struct Storage {
+    conn: Mutex<Connection>,
+}
+
+impl Storage {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        // WAL keeps reads from waiting on writes; a commit may be lost on
+        // power failure but the database stays consistent
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn get(&self, key: &str) -> rusqlite::Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT value FROM entries WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO entries (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, value, now_ms()],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM entries WHERE key = ?1", [key])?;
+        Ok(())
+    }
+
+    /// The keys starting with `prefix`, in order, with their sizes.
+    pub fn list(&self, prefix: &str) -> rusqlite::Result<Vec<KeyStat>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT key, length(CAST(value AS BLOB)), updated_at FROM entries
+             WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key",
+        )?;
+        let stats = statement
+            .query_map([prefix], |row| {
+                Ok(KeyStat {
+                    key: row.get(0)?,
+                    size: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            })?
+            .collect();
+        stats
+    }
+
+    /// Copies `entries` from localStorage the first time it's called for
+    /// this database, without overwriting keys that are already set.
+    /// Returns how many were copied, or `None` if it already ran.
+    pub fn import(&self, entries: &HashMap<String, String>) -> rusqlite::Result<Option<usize>> {
+        let mut conn = self.conn.lock().unwrap();
+        let transaction = conn.transaction()?;
+        let imported = transaction
+            .query_row("SELECT 1 FROM meta WHERE key = ?1", [IMPORTED_KEY], |_| {
+                Ok(())
+            })
+            .optional()?;
+        if imported.is_some() {
+            return Ok(None);
+        }
+        let now = now_ms();
+        let mut copied = 0;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT OR IGNORE INTO entries (key, value, updated_at) VALUES (?1, ?2, ?3)",
+            )?;
+            for (key, value) in entries {
+                copied += insert.execute(params![key, value, now])?;
+            }
+        }
+        transaction.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+            params![IMPORTED_KEY, now.to_string()],
+        )?;
+        transaction.commit()?;
+        Ok(Some(copied))
+    }
+}
+
+/// `getItem` of zustand's `StateStorage`: the value, or `null`.
+#[tauri::command]
+pub async fn storage_get(
+    name: String,
+    storage: State<'_, Storage>,
+) -> Result<Option<String>, String> {
+    storage.get(&name).map_err(|err| err.to_string())
+}
+
+/// `setItem` of zustand's `StateStorage`.
+#[tauri::command]
+pub async fn storage_set(
+    name: String,
+    value: String,
+    storage: State<'_, Storage>,
+) -> Result<(), String> {
+    storage.set(&name, &value).map_err(|err| err.to_string())
+}
+
+/// `removeItem` of zustand's `StateStorage`.
+#[tauri::command]
+pub async fn storage_remove(name: String, storage: State<'_, Storage>) -> Result<(), String> {
+    storage.remove(&name).map_err(|err| err.to_string())
+}
+
+/// Every key, or those starting with `prefix`, with its size.
+#[tauri::command]
+pub async fn storage_list(
+    prefix: Option<String>,
+    storage: State<'_, Storage>,
+) -> Result<Vec<KeyStat>, String> {
+    storage
+        .list(prefix.as_deref().unwrap_or_default())
+        .map_err(|err| err.to_string())
+}
+
+/// Takes all of localStorage once; see [`Storage::import`].
+#[tauri::command]
+pub async fn storage_import(
+    entries: HashMap<String, String>,
+    storage: State<'_, Storage>,
+) -> Result<Option<usize>, String> {
+    storage.import(&entries).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> (tempfile::TempDir, Storage) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(&dir.path().join(FILE_NAME)).unwrap();
+        (dir, storage)
+    }
+
+    #[test]
+    fn gets_sets_and_removes() {
+        let (_dir, storage) = open();
+        assert_eq!(storage.get("ryos:files").unwrap(), None);
+        storage.set("ryos:files", "{\"a\":1}").unwrap();
+        storage.set("ryos:files", "{\"a\":2}").unwrap();
+        assert_eq!(
+            storage.get("ryos:files").unwrap().as_deref(),
+            Some("{\"a\":2}")
+        );
+        storage.remove("ryos:files").unwrap();
+        storage.remove("ryos:files").unwrap();
+        assert_eq!(storage.get("ryos:files").unwrap(), None);
+    }
+
+    #[test]
+    fn lists_sizes_by_prefix() {
+        let (_dir, storage) = open();
+        storage.set("ryos:ipod", "ü").unwrap();
+        storage.set("ryos:chats", "abc").unwrap();
+        storage.set("other", "").unwrap();
+        storage.set("ryos_%", "x").unwrap();
+
+        let stats = storage.list("ryos:").unwrap();
+        let sizes: Vec<_> = stats
+            .iter()
+            .map(|stat| (stat.key.as_str(), stat.size))
+            .collect();
+        assert_eq!(sizes, [("ryos:chats", 3), ("ryos:ipod", 2)]);
+        assert!(stats[0].updated_at > 0);
+        // The prefix is matched literally, not as a pattern
+        assert_eq!(storage.list("ryos_").unwrap().len(), 1);
+        assert_eq!(storage.list("").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn imports_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        let storage = Storage::open(&path).unwrap();
+        storage.set("kept", "shell").unwrap();
+
+        let entries = HashMap::from([
+            ("kept".to_string(), "browser".to_string()),
+            ("new".to_string(), "browser".to_string()),
+        ]);
+        assert_eq!(storage.import(&entries).unwrap(), Some(1));
+        assert_eq!(storage.get("kept").unwrap().as_deref(), Some("shell"));
+        assert_eq!(storage.get("new").unwrap().as_deref(), Some("browser"));
+
+        storage.remove("new").unwrap();
+        drop(storage);
+        let reopened = Storage::open(&path).unwrap();
+        assert_eq!(reopened.import(&entries).unwrap(), None);
+        assert_eq!(reopened.get("new").unwrap(), None);
+    }
+}
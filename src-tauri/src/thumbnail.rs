@@ -0,0 +1,661 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::SystemTime;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::ipc;
+use tauri::{AppHandle, Manager, Runtime, State, UriSchemeContext, UriSchemeResponder};
+
+use crate::atomic;
+use crate::ipc_body;
+use crate::profile;
+use crate::vfs::Vfs;
+
+/// Scheme thumbnails are served from.
+pub const SCHEME: &str = "syaos-thumb";
+
+/// Directory in the profile's cache dir the thumbnails are kept in.
+pub const DIR_NAME: &str = "thumbnails";
+
+/// Header carrying the URI-encoded [`BlobSource`] JSON for
+/// `thumbnail_put`, whose body is the image.
+pub const SOURCE_HEADER: &str = "x-syaos-thumbnail";
+
+/// Longest edge of each size thumbnails are made in. A request gets the
+/// smallest one at least as large as it asks for.
+pub const BUCKETS: [u32; 4] = [64, 128, 256, 512];
+
+/// Size of the cache before the least recently used thumbnails go.
+const MAX_CACHE_SIZE: u64 = 128 * 1024 * 1024;
+/// Larger images aren't decoded.
+const MAX_SOURCE_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_DIMENSION: u32 = 16_384;
+const JPEG_QUALITY: u8 = 85;
+
+/// Extensions of the images on disk that get thumbnails.
+const EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+const HASH_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The bucket serving a request for `size` pixels.
+pub fn bucket_for(size: u32) -> u32 {
+    BUCKETS
+        .into_iter()
+        .find(|&bucket| bucket >= size)
+        .unwrap_or(BUCKETS[BUCKETS.len() - 1])
+}
+
+fn is_image(name: &str) -> bool {
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Png,
+    Jpeg,
+}
+
+impl Encoding {
+    fn extension(self) -> &'static str {
+        match self {
+            Encoding::Png => "png",
+            Encoding::Jpeg => "jpg",
+        }
+    }
+
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "png" => Some(Encoding::Png),
+            "jpg" => Some(Encoding::Jpeg),
+            _ => None,
+        }
+    }
+}
+
+/// An encoded thumbnail.
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    pub bytes: Vec<u8>,
+    encoding: Encoding,
+}
+
+impl Thumbnail {
+    pub fn mime(&self) -> &'static str {
+        match self.encoding {
+            Encoding::Png => "image/png",
+            Encoding::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+/// Decodes `source`, turned upright when it has an EXIF orientation.
+/// Only the first frame of an animation is kept.
+fn decode(source: &[u8]) -> Result<DynamicImage, String> {
+    let mut reader = ImageReader::new(Cursor::new(source))
+        .with_guessed_format()
+        .map_err(|err| err.to_string())?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    reader.limits(limits);
+    let mut decoder = reader.into_decoder().map_err(|err| err.to_string())?;
+    let orientation = decoder.orientation().map_err(|err| err.to_string())?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|err| err.to_string())?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Scales `image` down to fit `bucket` and encodes it: PNG when it has
+/// transparency, JPEG otherwise.
+fn encode(image: &DynamicImage, bucket: u32) -> Result<Thumbnail, String> {
+    let scaled;
+    let image = if image.width() > bucket || image.height() > bucket {
+        scaled = image.thumbnail(bucket, bucket);
+        &scaled
+    } else {
+        image
+    };
+    let mut bytes = Vec::new();
+    let encoding = if image.color().has_alpha() {
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .map_err(|err| err.to_string())?;
+        Encoding::Png
+    } else {
+        JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY)
+            .encode_image(&image.to_rgb8())
+            .map_err(|err| err.to_string())?;
+        Encoding::Jpeg
+    };
+    Ok(Thumbnail { bytes, encoding })
+}
+
+/// A thumbnail of `source` fitting `bucket`.
+pub fn render(source: &[u8], bucket: u32) -> Result<Thumbnail, String> {
+    encode(&decode(source)?, bucket)
+}
+
+struct Entry {
+    /// Of the source: its modification time, or what the web app gave.
+    version: u64,
+    encoding: Encoding,
+    size: u64,
+    /// When it was last served, on the cache's clock.
+    used: u64,
+}
+
+/// Thumbnails by source key hash and bucket. Each is one file, named
+/// `<hash>-<bucket>-<version>.<ext>`, so the cache is rebuilt from the
+/// directory without an index.
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<(u64, u32), Entry>,
+    total: u64,
+    clock: u64,
+}
+
+fn file_name(key: (u64, u32), entry: &Entry) -> String {
+    format!(
+        "{:016x}-{}-{}.{}",
+        key.0,
+        key.1,
+        entry.version,
+        entry.encoding.extension()
+    )
+}
+
+fn parse_file_name(name: &str) -> Option<((u64, u32), u64, Encoding)> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    let mut parts = stem.split('-');
+    let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let bucket = parts.next()?.parse().ok()?;
+    let version = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(((hash, bucket), version, Encoding::from_extension(ext)?))
+}
+
+/// The thumbnail cache, bounded to [`MAX_CACHE_SIZE`].
+pub struct Thumbnails {
+    dir: PathBuf,
+    max_size: u64,
+    cache: Mutex<Cache>,
+    /// Number of thumbnails being rendered. A folder of photos asks for
+    /// all of them at once, so only as many as there are cores are
+    /// rendered at a time.
+    rendering: Mutex<usize>,
+    rendered: Condvar,
+}
+
+impl Thumbnails {
+    /// Picks up the thumbnails already in `dir`, least recently used
+    /// first by their modification times.
+    pub fn open(dir: PathBuf) -> Self {
+        Self::with_max_size(dir, MAX_CACHE_SIZE)
+    }
+
+    fn with_max_size(dir: PathBuf, max_size: u64) -> Self {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let name = entry.file_name();
+            let parsed = name.to_str().and_then(parse_file_name);
+            let Some((key, version, encoding)) = parsed else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            found.push((used, key, version, encoding, metadata.len()));
+        }
+        found.sort_by_key(|(used, ..)| *used);
+
+        let thumbnails = Self {
+            dir,
+            max_size,
+            cache: Mutex::default(),
+            rendering: Mutex::new(0),
+            rendered: Condvar::new(),
+        };
+        {
+            let mut cache = thumbnails.cache.lock().unwrap();
+            for (_, key, version, encoding, size) in found {
+                cache.clock += 1;
+                let entry = Entry {
+                    version,
+                    encoding,
+                    size,
+                    used: cache.clock,
+                };
+                thumbnails.replace(&mut cache, key, Some(entry));
+            }
+            thumbnails.evict(&mut cache);
+        }
+        thumbnails
+    }
+
+    fn key(source: &str, bucket: u32) -> (u64, u32) {
+        (profile::fnv1a(source, HASH_BASIS), bucket)
+    }
+
+    /// Sets the entry for `key`, deleting the file of the one it replaces
+    /// unless the new entry was just written under the same name.
+    fn replace(&self, cache: &mut Cache, key: (u64, u32), entry: Option<Entry>) {
+        let new_name = entry.as_ref().map(|entry| file_name(key, entry));
+        if let Some(entry) = &entry {
+            cache.total += entry.size;
+        }
+        let old = match entry {
+            Some(entry) => cache.entries.insert(key, entry),
+            None => cache.entries.remove(&key),
+        };
+        if let Some(old) = old {
+            cache.total -= old.size;
+            let old_name = file_name(key, &old);
+            if new_name.as_ref() != Some(&old_name) {
+                let _ = fs::remove_file(self.dir.join(old_name));
+            }
+        }
+    }
+
+    /// Drops the least recently used thumbnails until the cache fits.
+    fn evict(&self, cache: &mut Cache) {
+        while cache.total > self.max_size {
+            let oldest = cache
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| *key);
+            match oldest {
+                Some(key) => self.replace(cache, key, None),
+                None => break,
+            }
+        }
+    }
+
+    /// The cached thumbnail of `source` at `version`. Older versions are
+    /// deleted.
+    fn get(&self, source: &str, version: u64, bucket: u32) -> Option<Thumbnail> {
+        let key = Self::key(source, bucket);
+        let mut cache = self.cache.lock().unwrap();
+        let entry = cache.entries.get(&key)?;
+        if entry.version != version {
+            self.replace(&mut cache, key, None);
+            return None;
+        }
+        let path = self.dir.join(file_name(key, entry));
+        let encoding = entry.encoding;
+        let Ok(bytes) = fs::read(&path) else {
+            self.replace(&mut cache, key, None);
+            return None;
+        };
+        cache.clock += 1;
+        let clock = cache.clock;
+        cache.entries.get_mut(&key).unwrap().used = clock;
+        // Keeps the order across launches; losing it only costs a re-render
+        let _ = File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        Some(Thumbnail { bytes, encoding })
+    }
+
+    fn insert(&self, source: &str, version: u64, bucket: u32, thumbnail: &Thumbnail) {
+        let key = Self::key(source, bucket);
+        let mut cache = self.cache.lock().unwrap();
+        cache.clock += 1;
+        let entry = Entry {
+            version,
+            encoding: thumbnail.encoding,
+            size: thumbnail.bytes.len() as u64,
+            used: cache.clock,
+        };
+        let path = self.dir.join(file_name(key, &entry));
+        let written =
+            fs::create_dir_all(&self.dir).and_then(|()| atomic::write(&path, &thumbnail.bytes));
+        if let Err(err) = written {
+            eprintln!("failed to cache {}: {err}", path.display());
+            return;
+        }
+        self.replace(&mut cache, key, Some(entry));
+        self.evict(&mut cache);
+    }
+
+    /// Waits for a free core, then runs `render`.
+    fn rendering<T>(&self, render: impl FnOnce() -> T) -> T {
+        let max = thread::available_parallelism().map_or(2, usize::from);
+        {
+            let mut rendering = self.rendering.lock().unwrap();
+            while *rendering >= max {
+                rendering = self.rendered.wait(rendering).unwrap();
+            }
+            *rendering += 1;
+        }
+        let result = render();
+        *self.rendering.lock().unwrap() -= 1;
+        self.rendered.notify_one();
+        result
+    }
+
+    /// The thumbnail of the image at the virtual `path`, rendered again
+    /// when the file was modified since it was cached.
+    pub fn file(&self, vfs: &Vfs, path: &str, bucket: u32) -> Result<Thumbnail, String> {
+        let item = vfs.stat(path)?;
+        if item.is_directory || !is_image(&item.name) {
+            return Err(format!("`{path}` is not an image"));
+        }
+        if item.size.unwrap_or_default() > MAX_SOURCE_SIZE {
+            return Err(format!("`{path}` is too large"));
+        }
+        let source = format!("fs:{path}");
+        if let Some(thumbnail) = self.get(&source, item.modified_at, bucket) {
+            return Ok(thumbnail);
+        }
+        let thumbnail = self.rendering(|| render(&vfs.read(path)?, bucket))?;
+        self.insert(&source, item.modified_at, bucket, &thumbnail);
+        Ok(thumbnail)
+    }
+
+    /// The cached thumbnail of a blob the web app sent with `thumbnail_put`.
+    pub fn blob(&self, key: &str, version: u64, bucket: u32) -> Option<Thumbnail> {
+        self.get(&format!("blob:{key}"), version, bucket)
+    }
+
+    /// Renders every bucket of a blob's thumbnail, since the shell can't
+    /// read the blob again later.
+    pub fn put_blob(&self, key: &str, version: u64, image: &[u8]) -> Result<(), String> {
+        if image.len() as u64 > MAX_SOURCE_SIZE {
+            return Err("the image is too large".to_string());
+        }
+        let source = format!("blob:{key}");
+        let thumbnails = self.rendering(|| {
+            let image = decode(image)?;
+            BUCKETS
+                .into_iter()
+                .map(|bucket| Ok((bucket, encode(&image, bucket)?)))
+                .collect::<Result<Vec<_>, String>>()
+        })?;
+        for (bucket, thumbnail) in thumbnails {
+            self.insert(&source, version, bucket, &thumbnail);
+        }
+        Ok(())
+    }
+}
+
+/// What a thumbnail URL asks for. `convertFileSrc` encodes its path as a
+/// single segment: `fs/<size>/<virtual path>` for files on disk, or
+/// `blob/<size>/<key>` with `?v=<version>` for blobs.
+#[derive(Debug, PartialEq, Eq)]
+enum Target {
+    File {
+        path: String,
+        size: u32,
+    },
+    Blob {
+        key: String,
+        version: u64,
+        size: u32,
+    },
+}
+
+fn parse_target(path: &str, query: Option<&str>) -> Option<Target> {
+    let decoded = percent_decode_str(path.strip_prefix('/')?)
+        .decode_utf8()
+        .ok()?;
+    let (kind, rest) = decoded.split_once('/')?;
+    let (size, rest) = rest.split_once('/')?;
+    let size = size.parse().ok()?;
+    match kind {
+        "fs" => Some(Target::File {
+            path: format!("/{rest}"),
+            size,
+        }),
+        "blob" if !rest.is_empty() => {
+            let version = query
+                .into_iter()
+                .flat_map(|query| query.split('&'))
+                .find_map(|pair| pair.strip_prefix("v="))
+                .map_or(Some(0), |version| version.parse().ok())?;
+            Some(Target::Blob {
+                key: rest.to_string(),
+                version,
+                size,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn serve<R: Runtime>(app: &AppHandle<R>, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let status = |status: StatusCode| Response::builder().status(status).body(Vec::new()).unwrap();
+    let Some(target) = parse_target(request.uri().path(), request.uri().query()) else {
+        return status(StatusCode::BAD_REQUEST);
+    };
+    let Some(thumbnails) = app.try_state::<Thumbnails>() else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    let (thumbnail, cache_control) = match target {
+        // The version is in the URL, so the webview may keep it
+        Target::Blob { key, version, size } => {
+            match thumbnails.blob(&key, version, bucket_for(size)) {
+                Some(thumbnail) => (thumbnail, "max-age=31536000, immutable"),
+                None => return status(StatusCode::NOT_FOUND),
+            }
+        }
+        Target::File { path, size } => {
+            let Some(vfs) = app.try_state::<Vfs>() else {
+                return status(StatusCode::NOT_FOUND);
+            };
+            match thumbnails.file(&vfs, &path, bucket_for(size)) {
+                Ok(thumbnail) => (thumbnail, "no-cache"),
+                Err(err) => {
+                    eprintln!("no thumbnail for {path}: {err}");
+                    return status(StatusCode::NOT_FOUND);
+                }
+            }
+        }
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, thumbnail.mime())
+        .header(header::CACHE_CONTROL, cache_control)
+        .body(thumbnail.bytes)
+        .unwrap()
+}
+
+/// Handler for [`SCHEME`]. Rendering is slow, so it runs off the webview's
+/// thread.
+pub fn protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    thread::spawn(move || responder.respond(serve(&app, &request)));
+}
+
+/// A blob from the web app's IndexedDB, as `thumbnail_put` takes it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobSource {
+    pub key: String,
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Whether a blob's thumbnail for `size` is cached at `version`.
+#[tauri::command]
+pub fn thumbnail_cached(
+    key: String,
+    version: u64,
+    size: u32,
+    thumbnails: State<'_, Thumbnails>,
+) -> bool {
+    let source = format!("blob:{key}");
+    let key = Thumbnails::key(&source, bucket_for(size));
+    let cache = thumbnails.cache.lock().unwrap();
+    cache
+        .entries
+        .get(&key)
+        .is_some_and(|entry| entry.version == version)
+}
+
+/// Takes the image as the raw request body and the [`BlobSource`] as
+/// URI-encoded JSON in the [`SOURCE_HEADER`] header.
+#[tauri::command]
+pub async fn thumbnail_put(
+    request: ipc::Request<'_>,
+    thumbnails: State<'_, Thumbnails>,
+) -> Result<(), String> {
+    let (source, image): (BlobSource, _) = ipc_body::decode(&request, SOURCE_HEADER)?;
+    thumbnails.put_blob(&source.key, source.version, image)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 128]));
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgba8(image)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, Rgb([0, 128, 255]));
+        let mut bytes = Vec::new();
+        JpegEncoder::new(&mut bytes).encode_image(&image).unwrap();
+        bytes
+    }
+
+    fn dimensions(thumbnail: &Thumbnail) -> (u32, u32) {
+        let image = image::load_from_memory(&thumbnail.bytes).unwrap();
+        (image.width(), image.height())
+    }
+
+    #[test]
+    fn picks_buckets() {
+        assert_eq!(bucket_for(1), 64);
+        assert_eq!(bucket_for(64), 64);
+        assert_eq!(bucket_for(96), 128);
+        assert_eq!(bucket_for(4000), 512);
+    }
+
+    #[test]
+    fn renders_within_the_bucket() {
+        let thumbnail = render(&png(1000, 500), 128).unwrap();
+        assert_eq!(thumbnail.mime(), "image/png");
+        assert_eq!(dimensions(&thumbnail), (128, 64));
+
+        let thumbnail = render(&jpeg(300, 600), 256).unwrap();
+        assert_eq!(thumbnail.mime(), "image/jpeg");
+        assert_eq!(dimensions(&thumbnail), (128, 256));
+        // Small images aren't scaled up
+        assert_eq!(dimensions(&render(&jpeg(40, 30), 512).unwrap()), (40, 30));
+        assert!(render(b"not an image", 64).is_err());
+    }
+
+    #[test]
+    fn parses_urls() {
+        assert_eq!(
+            parse_target("/fs%2F96%2FVolumes%2FPhotos%2Fa%20b.png", None),
+            Some(Target::File {
+                path: "/Volumes/Photos/a b.png".to_string(),
+                size: 96,
+            })
+        );
+        assert_eq!(
+            parse_target("/blob%2F256%2Fimage%3Aabc", Some("v=42")),
+            Some(Target::Blob {
+                key: "image:abc".to_string(),
+                version: 42,
+                size: 256,
+            })
+        );
+        assert_eq!(parse_target("/blob%2Fbig%2Fkey", None), None);
+        assert_eq!(parse_target("/other%2F64%2Fx", None), None);
+    }
+
+    #[test]
+    fn invalidates_on_modification() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        fs::create_dir_all(home.join("Images")).unwrap();
+        let photo = home.join("Images/photo.jpg");
+        fs::write(&photo, jpeg(400, 200)).unwrap();
+        let mounts = crate::mounts::Mounts::load(dir.path().join("mounts.json"));
+        let vfs = Vfs::open(home, mounts, None).unwrap();
+        let thumbnails = Thumbnails::open(dir.path().join(DIR_NAME));
+
+        let first = thumbnails.file(&vfs, "/Images/photo.jpg", 128).unwrap();
+        assert_eq!(dimensions(&first), (128, 64));
+        assert_eq!(fs::read_dir(dir.path().join(DIR_NAME)).unwrap().count(), 1);
+
+        fs::write(&photo, jpeg(100, 200)).unwrap();
+        File::options()
+            .write(true)
+            .open(&photo)
+            .unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+        let second = thumbnails.file(&vfs, "/Images/photo.jpg", 128).unwrap();
+        assert_eq!(dimensions(&second), (64, 128));
+        // The stale one was replaced, not kept alongside
+        assert_eq!(fs::read_dir(dir.path().join(DIR_NAME)).unwrap().count(), 1);
+        assert!(thumbnails.file(&vfs, "/Images", 128).is_err());
+    }
+
+    #[test]
+    fn putting_the_same_version_again_keeps_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let thumbnails = Thumbnails::open(dir.path().join(DIR_NAME));
+        let image = png(600, 600);
+        thumbnails.put_blob("a", 1, &image).unwrap();
+        thumbnails.put_blob("a", 1, &image).unwrap();
+        for bucket in BUCKETS {
+            assert!(thumbnails.blob("a", 1, bucket).is_some());
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used_and_reopens() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join(DIR_NAME);
+        let image = png(600, 600);
+        let all_buckets: u64 = BUCKETS
+            .into_iter()
+            .map(|bucket| render(&image, bucket).unwrap().bytes.len() as u64)
+            .sum();
+        // Room for two blobs' worth of thumbnails
+        let thumbnails = Thumbnails::with_max_size(cache_dir.clone(), all_buckets * 2);
+
+        thumbnails.put_blob("a", 1, &image).unwrap();
+        thumbnails.put_blob("b", 1, &image).unwrap();
+        assert!(thumbnails.blob("a", 1, 512).is_some());
+        assert!(thumbnails.blob("a", 2, 512).is_none());
+        thumbnails.put_blob("a", 2, &image).unwrap();
+        thumbnails.put_blob("c", 1, &image).unwrap();
+        // `b` was used least recently
+        assert!(thumbnails.blob("b", 1, 512).is_none());
+        assert!(thumbnails.blob("c", 1, 512).is_some());
+
+        let reopened = Thumbnails::with_max_size(cache_dir, all_buckets * 2);
+        let cache = reopened.cache.lock().unwrap();
+        assert_eq!(cache.entries.len(), BUCKETS.len() * 2);
+        assert_eq!(cache.total, all_buckets * 2);
+    }
+}
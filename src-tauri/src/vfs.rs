@@ -0,0 +1,1342 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::ipc::{Request, Response};
+use tauri::State;
+
+use crate::atomic;
+use crate::host_trash::{Entry, HostTrash};
+use crate::ipc_body;
+use crate::mounts::{self, Mounts, VOLUMES_PATH};
+use crate::search::SearchIndex;
+
+/// Name of the home directory created in the user's home folder.
+pub const HOME_DIR_NAME: &str = "syaOS Home";
+
+/// Virtual directory listing everything in the trash.
+pub const TRASH_PATH: &str = "/Trash";
+
+/// Header carrying the URI-encoded item JSON for `fs_write`, whose body is
+/// the raw file content.
+pub const ITEM_HEADER: &str = "x-syaos-item";
+
+/// Where trashed items are kept, mirroring their original location.
+const TRASH_DIR: &str = ".Trash";
+/// Holds the index of metadata the host filesystem can't store.
+const STATE_DIR: &str = ".syaos";
+const INDEX_FILE: &str = "index.json";
+
+/// Icon of `/Volumes` and the mounts in it.
+const VOLUME_ICON: &str = "/icons/default/disk.png";
+
+/// Fields derived from the host filesystem, never taken from the index.
+const DERIVED_FIELDS: &[&str] = &[
+    "path",
+    "name",
+    "isDirectory",
+    "size",
+    "modifiedAt",
+    "status",
+    "originalPath",
+    "deletedAt",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Active,
+    Trashed,
+}
+
+/// Mirror of `FileSystemItem` in `src/stores/useFilesStore.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSystemItem {
+    pub path: String,
+    pub name: String,
+    pub is_directory: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    pub created_at: u64,
+    pub modified_at: u64,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<u64>,
+    /// Everything the host can't store (`icon`, `type`, `appId`, `uuid`,
+    /// `shareId`, `aliasTarget`, ...), kept as the web app sent it.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An item as sent to `fs_write`, like `addItem` takes it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewItem {
+    pub path: String,
+    #[serde(default)]
+    pub is_directory: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Trashed {
+    #[serde(rename = "deletedAt")]
+    deleted_at: u64,
+    #[serde(flatten)]
+    meta: Map<String, Value>,
+}
+
+/// Metadata by virtual path. Active and trashed items are kept apart since
+/// a path can be reused while an older item with it sits in the trash.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    #[serde(default)]
+    items: BTreeMap<String, Map<String, Value>>,
+    #[serde(default)]
+    trash: BTreeMap<String, Trashed>,
+}
+
+/// The Finder tree on disk: virtual `/Documents/a.md` is
+/// `<root>/Documents/a.md`, and `/Volumes/<name>/a.md` is `a.md` in the
+/// host folder mounted as `<name>`.
+pub struct Vfs {
+    root: PathBuf,
+    index: Mutex<Index>,
+    mounts: Mounts,
+    /// Where items on mounted volumes are trashed, if the platform has one.
+    host_trash: Option<HostTrash>,
+}
+
+/// Where a virtual path lives on the host.
+enum Location {
+    /// Inside the home directory.
+    Home(PathBuf),
+    /// `/Volumes` itself, which only lists the mounts.
+    Volumes,
+    /// Inside the mount `name`, whose directory is `root`.
+    Mounted {
+        name: String,
+        root: PathBuf,
+        host: PathBuf,
+    },
+}
+
+impl Location {
+    /// Whether this is a fixed point of the tree that can't be written,
+    /// moved or deleted.
+    fn is_root(&self, home: &Path) -> bool {
+        match self {
+            Location::Home(host) => host == home,
+            Location::Volumes => true,
+            Location::Mounted { root, host, .. } => host == root,
+        }
+    }
+}
+
+/// `~/syaOS Home`, or `~/syaOS Home (<profile>)` for a named profile.
+pub fn default_root(profile: Option<&str>) -> Option<PathBuf> {
+    let name = match profile {
+        Some(profile) => format!("{HOME_DIR_NAME} ({profile})"),
+        None => HOME_DIR_NAME.to_string(),
+    };
+    Some(dirs::home_dir()?.join(name))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn millis(time: io::Result<SystemTime>) -> Option<u64> {
+    let elapsed = time.ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(elapsed.as_millis() as u64)
+}
+
+/// Splits a virtual path into its names, rejecting anything that could
+/// leave the root or collide with the shell's own files.
+fn components(path: &str) -> Result<Vec<&str>, String> {
+    let rest = path
+        .strip_prefix('/')
+        .ok_or_else(|| format!("`{path}` is not absolute"))?;
+    if rest.is_empty() {
+        return Ok(Vec::new());
+    }
+    rest.split('/')
+        .map(|name| {
+            let valid = !name.is_empty()
+                && !name.starts_with('.')
+                && !name.contains(['\\', '\0'])
+                && name.len() <= 255;
+            if valid {
+                Ok(name)
+            } else {
+                Err(format!("invalid name `{name}` in `{path}`"))
+            }
+        })
+        .collect()
+}
+
+/// `getParentPath` from the files store.
+pub fn parent_path(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(index) => &path[..index],
+    }
+}
+
+pub(crate) fn is_within(path: &str, ancestor: &str) -> bool {
+    path == ancestor || path.starts_with(&format!("{ancestor}/"))
+}
+
+/// Re-roots `path` from `from` to `to` when it lies within `from`.
+fn rebase(path: &str, from: &str, to: &str) -> Option<String> {
+    if path == from {
+        Some(to.to_string())
+    } else {
+        path.strip_prefix(&format!("{from}/"))
+            .map(|rest| format!("{to}/{rest}"))
+    }
+}
+
+/// Moves the entries for `from` and everything under it to `to`.
+fn rebase_keys<V>(map: &mut BTreeMap<String, V>, from: &str, to: &str) {
+    let keys: Vec<String> = map
+        .keys()
+        .filter(|key| is_within(key, from))
+        .cloned()
+        .collect();
+    let moved: Vec<(String, V)> = keys
+        .into_iter()
+        .filter_map(|key| map.remove(&key).map(|value| (key, value)))
+        .collect();
+    for (key, value) in moved {
+        map.insert(rebase(&key, from, to).unwrap(), value);
+    }
+}
+
+fn remove_keys<V>(map: &mut BTreeMap<String, V>, path: &str) -> Vec<(String, V)> {
+    let keys: Vec<String> = map
+        .keys()
+        .filter(|key| is_within(key, path))
+        .cloned()
+        .collect();
+    keys.into_iter()
+        .filter_map(|key| map.remove(&key).map(|value| (key, value)))
+        .collect()
+}
+
+pub(crate) fn remove_all(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// Copies a file or directory tree, skipping symlinks, for moves between
+/// volumes or filesystems.
+pub(crate) fn copy_all(source: &Path, destination: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.is_dir() {
+        fs::create_dir(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            if entry.file_type()?.is_symlink() {
+                continue;
+            }
+            copy_all(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(source, destination).map(drop)
+    }
+}
+
+/// Creates the directory or writes the file at `host`.
+fn write_host(path: &str, host: &Path, is_directory: bool, content: &[u8]) -> Result<(), String> {
+    if is_directory {
+        if host.is_file() {
+            return Err(format!("`{path}` is a file"));
+        }
+        fs::create_dir_all(host).map_err(|err| format!("{path}: {err}"))
+    } else {
+        if host.is_dir() {
+            return Err(format!("`{path}` is a directory"));
+        }
+        atomic::write(host, content).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+/// `getFileTypeFromExtension` from `useFileSystem.ts`, for items whose
+/// `type` wasn't stored.
+pub(crate) fn file_type(name: &str) -> &'static str {
+    let ext = name.rsplit('.').next().unwrap_or_default().to_lowercase();
+    match ext.as_str() {
+        "app" => "application",
+        "md" => "markdown",
+        "txt" => "text",
+        "png" => "png",
+        "gif" => "gif",
+        "webp" => "webp",
+        "bmp" => "bmp",
+        "jpg" | "jpeg" => "jpg",
+        _ => "unknown",
+    }
+}
+
+fn volume_meta() -> Map<String, Value> {
+    let mut meta = Map::new();
+    meta.insert("type".into(), "directory".into());
+    meta.insert("icon".into(), VOLUME_ICON.into());
+    meta
+}
+
+impl Vfs {
+    /// Opens the home directory at `root`, with `mounts` under
+    /// [`VOLUMES_PATH`] trashing to `host_trash`. `root` isn't created until
+    /// something is written to it.
+    pub fn open(root: PathBuf, mounts: Mounts, host_trash: Option<HostTrash>) -> io::Result<Self> {
+        let index = match fs::read_to_string(root.join(STATE_DIR).join(INDEX_FILE)) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+                eprintln!("ignoring invalid {INDEX_FILE} in {}: {err}", root.display());
+                Index::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Index::default(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            root,
+            index: Mutex::new(index),
+            mounts,
+            host_trash,
+        })
+    }
+
+    pub fn mounts(&self) -> &Mounts {
+        &self.mounts
+    }
+
+    /// Whether anything is at `path`; trashed items don't count.
+    pub fn exists(&self, path: &str) -> bool {
+        match self.locate(path) {
+            Ok(Location::Home(host) | Location::Mounted { host, .. }) => exists(&host),
+            Ok(Location::Volumes) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Host path of an item in the home directory.
+    fn host_path(&self, path: &str) -> Result<PathBuf, String> {
+        if is_within(path, TRASH_PATH) {
+            return Err(format!("`{path}` is inside the trash"));
+        }
+        if is_within(path, VOLUMES_PATH) {
+            return Err(format!("`{path}` is on a mounted volume"));
+        }
+        Ok(components(path)?
+            .into_iter()
+            .fold(self.root.clone(), |host, name| host.join(name)))
+    }
+
+    fn locate(&self, path: &str) -> Result<Location, String> {
+        if !is_within(path, VOLUMES_PATH) {
+            return self.host_path(path).map(Location::Home);
+        }
+        let names = components(path)?;
+        let Some(name) = names.get(1) else {
+            return Ok(Location::Volumes);
+        };
+        let mount = self
+            .mounts
+            .get(name)
+            .ok_or_else(|| format!("no volume named `{name}`"))?;
+        let host = names[2..]
+            .iter()
+            .fold(mount.host_path.clone(), |host, name| host.join(name));
+        mounts::confine(&mount.host_path, &host)?;
+        Ok(Location::Mounted {
+            name: mount.name,
+            root: mount.host_path,
+            host,
+        })
+    }
+
+    fn trash_path(&self, path: &str) -> Result<PathBuf, String> {
+        Ok(components(path)?
+            .into_iter()
+            .fold(self.root.join(TRASH_DIR), |host, name| host.join(name)))
+    }
+
+    fn save(&self, index: &Index) -> Result<(), String> {
+        let contents = serde_json::to_vec_pretty(index).map_err(|err| err.to_string())?;
+        let dir = self.root.join(STATE_DIR);
+        fs::create_dir_all(&dir)
+            .and_then(|()| atomic::write(&dir.join(INDEX_FILE), &contents))
+            .map_err(|err| err.to_string())
+    }
+
+    /// Creates the home directory, which [`Vfs::open`] leaves to the first
+    /// write.
+    fn create_root(&self) -> Result<(), String> {
+        fs::create_dir_all(&self.root).map_err(|err| format!("{}: {err}", self.root.display()))
+    }
+
+    fn item(
+        &self,
+        path: &str,
+        host: &Path,
+        meta: Option<&Map<String, Value>>,
+    ) -> Option<FileSystemItem> {
+        let metadata = fs::symlink_metadata(host).ok()?;
+        if metadata.file_type().is_symlink() {
+            return None;
+        }
+        let modified_at = millis(metadata.modified()).unwrap_or_default();
+        let mut extra = meta.cloned().unwrap_or_default();
+        let created_at = extra
+            .remove("createdAt")
+            .and_then(|value| value.as_u64())
+            .or_else(|| millis(metadata.created()))
+            .unwrap_or(modified_at);
+        for field in DERIVED_FIELDS {
+            extra.remove(*field);
+        }
+        let name = path.rsplit('/').next().unwrap_or_default();
+        if !extra.contains_key("type") {
+            let kind = if metadata.is_dir() {
+                "directory"
+            } else {
+                file_type(name)
+            };
+            extra.insert("type".into(), kind.into());
+        }
+        Some(FileSystemItem {
+            path: path.to_string(),
+            name: name.to_string(),
+            is_directory: metadata.is_dir(),
+            size: (!metadata.is_dir()).then_some(metadata.len()),
+            created_at,
+            modified_at,
+            status: Status::Active,
+            original_path: None,
+            deleted_at: None,
+            extra,
+        })
+    }
+
+    fn trashed_item(
+        &self,
+        path: &str,
+        host: &Path,
+        meta: Option<&Map<String, Value>>,
+        deleted_at: u64,
+    ) -> Option<FileSystemItem> {
+        let mut item = self.item(path, host, meta)?;
+        item.status = Status::Trashed;
+        item.original_path = Some(path.to_string());
+        item.deleted_at = Some(deleted_at);
+        Some(item)
+    }
+
+    /// Items in the host trash that were deleted from a mounted volume, with
+    /// their virtual paths, newest first.
+    fn host_trashed(&self) -> Vec<(String, Entry)> {
+        let Some(host_trash) = &self.host_trash else {
+            return Vec::new();
+        };
+        let mounts = self.mounts.list();
+        host_trash
+            .entries()
+            .into_iter()
+            .filter_map(|entry| {
+                let path = mounts
+                    .iter()
+                    .find_map(|mount| mount.virtual_path(&entry.original))?;
+                Some((path, entry))
+            })
+            .collect()
+    }
+
+    /// `getItemsInPath`: active children of `path`, or every trashed item
+    /// for [`TRASH_PATH`], including those in the host trash.
+    pub fn list(&self, path: &str) -> Result<Vec<FileSystemItem>, String> {
+        let index = self.index.lock().unwrap();
+        if path == TRASH_PATH {
+            let mut items: Vec<FileSystemItem> = index
+                .trash
+                .iter()
+                .filter_map(|(path, trashed)| {
+                    let host = self.trash_path(path).ok()?;
+                    self.trashed_item(path, &host, Some(&trashed.meta), trashed.deleted_at)
+                })
+                .collect();
+            items.extend(self.host_trashed().into_iter().filter_map(|(path, entry)| {
+                self.trashed_item(&path, &entry.file, None, entry.deleted_at)
+            }));
+            return Ok(items);
+        }
+
+        let (host, in_home) = match self.locate(path)? {
+            Location::Volumes => {
+                let volume = volume_meta();
+                return Ok(self
+                    .mounts
+                    .list()
+                    .into_iter()
+                    .filter_map(|mount| {
+                        let child = format!("{VOLUMES_PATH}/{}", mount.name);
+                        self.item(&child, &mount.host_path, Some(&volume))
+                    })
+                    .collect());
+            }
+            Location::Home(host) => {
+                // Volumes are listed in the home directory, so it's only
+                // worth creating once there are some
+                if path == "/" && !exists(&host) {
+                    if self.mounts.list().is_empty() {
+                        return Ok(Vec::new());
+                    }
+                    self.create_root()?;
+                }
+                (host, true)
+            }
+            Location::Mounted { host, .. } => (host, false),
+        };
+        let entries = fs::read_dir(&host).map_err(|err| format!("{path}: {err}"))?;
+        let mut items: Vec<FileSystemItem> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                // Trash, index and host dotfiles aren't part of the tree
+                if name.starts_with('.') {
+                    return None;
+                }
+                let child = if path == "/" {
+                    format!("/{name}")
+                } else {
+                    format!("{path}/{name}")
+                };
+                if child == VOLUMES_PATH {
+                    return None;
+                }
+                let meta = if in_home {
+                    index.items.get(&child)
+                } else {
+                    None
+                };
+                self.item(&child, &entry.path(), meta)
+            })
+            .collect();
+        if path == "/" && !self.mounts.list().is_empty() {
+            items.extend(self.item(VOLUMES_PATH, &self.root, Some(&volume_meta())));
+        }
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(items)
+    }
+
+    /// Content of the active file at `path`.
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, String> {
+        let host = match self.locate(path)? {
+            Location::Volumes => return Err(format!("`{path}` is a directory")),
+            Location::Home(host) | Location::Mounted { host, .. } => host,
+        };
+        if host.is_dir() {
+            return Err(format!("`{path}` is a directory"));
+        }
+        fs::read(&host).map_err(|err| format!("{path}: {err}"))
+    }
+
+    /// The active item at `path`, as [`Vfs::list`] lists it.
+    pub fn stat(&self, path: &str) -> Result<FileSystemItem, String> {
+        let (host, in_home) = match self.locate(path)? {
+            Location::Volumes => return Err(format!("`{path}` is not an item")),
+            Location::Home(host) => (host, true),
+            Location::Mounted { host, .. } => (host, false),
+        };
+        let index = self.index.lock().unwrap();
+        let meta = if in_home { index.items.get(path) } else { None };
+        self.item(path, &host, meta)
+            .ok_or_else(|| format!("`{path}` does not exist"))
+    }
+
+    /// `addItem` plus content: creates or updates the item at `item.path`,
+    /// keeping an existing item's `uuid` and `createdAt`. Items on mounted
+    /// volumes keep only what the host filesystem stores.
+    pub fn write(&self, item: NewItem, content: &[u8]) -> Result<FileSystemItem, String> {
+        let path = item.path.as_str();
+        let location = self.locate(path)?;
+        if location.is_root(&self.root) {
+            return Err(format!("cannot write `{path}`"));
+        }
+        let host = match location {
+            Location::Home(host) => host,
+            Location::Mounted { host, .. } => {
+                // `locate` already checked the parent exists
+                write_host(path, &host, item.is_directory, content)?;
+                return self
+                    .item(path, &host, None)
+                    .ok_or_else(|| format!("`{path}` vanished while writing"));
+            }
+            Location::Volumes => return Err(format!("cannot write `{path}`")),
+        };
+        let parent = parent_path(path);
+        if parent == "/" {
+            self.create_root()?;
+        } else if !self.host_path(parent)?.is_dir() {
+            return Err(format!(
+                "parent directory `{parent}` does not exist or is trashed"
+            ));
+        }
+
+        let mut index = self.index.lock().unwrap();
+        let existing = index.items.get(path).cloned().unwrap_or_default();
+        write_host(path, &host, item.is_directory, content)?;
+
+        let mut meta = existing.clone();
+        meta.extend(item.extra);
+        for field in DERIVED_FIELDS {
+            meta.remove(*field);
+        }
+        for preserved in ["uuid", "createdAt"] {
+            if let Some(value) = existing.get(preserved) {
+                meta.insert(preserved.to_string(), value.clone());
+            }
+        }
+        if !item.is_directory && !meta.contains_key("uuid") {
+            meta.insert("uuid".into(), uuid::Uuid::new_v4().to_string().into());
+        }
+        meta.entry("createdAt").or_insert_with(|| now().into());
+        index.items.insert(path.to_string(), meta);
+        self.save(&index)?;
+
+        self.item(path, &host, index.items.get(path))
+            .ok_or_else(|| format!("`{path}` vanished while writing"))
+    }
+
+    /// `renameItem`: renames an active item in place; `new_name` must be the
+    /// last component of `new_path`.
+    pub fn rename(&self, old_path: &str, new_path: &str, new_name: &str) -> Result<(), String> {
+        if new_path.rsplit('/').next() != Some(new_name) {
+            return Err(format!("`{new_name}` is not the name of `{new_path}`"));
+        }
+        if parent_path(old_path) != parent_path(new_path) {
+            return Err("rename can't change the parent directory; use fs_move".to_string());
+        }
+        self.relocate(old_path, new_path)
+    }
+
+    /// `moveItem`: moves an active item to `destination_path`, whose parent
+    /// must be an existing directory.
+    pub fn move_item(&self, source_path: &str, destination_path: &str) -> Result<(), String> {
+        let destination_parent = parent_path(destination_path);
+        if destination_parent == "/" {
+            self.create_root()?;
+        }
+        let parent_is_dir = match self.locate(destination_parent)? {
+            Location::Home(host) | Location::Mounted { host, .. } => host.is_dir(),
+            Location::Volumes => false,
+        };
+        if !parent_is_dir {
+            return Err(format!(
+                "destination parent `{destination_parent}` does not exist or is not a directory"
+            ));
+        }
+        if is_within(destination_path, source_path) && destination_path != source_path {
+            return Err("cannot move a directory into itself".to_string());
+        }
+        self.relocate(source_path, destination_path)
+    }
+
+    /// Moves `from` to `to`, copying when they are on different volumes.
+    fn relocate(&self, from: &str, to: &str) -> Result<(), String> {
+        let source = self.locate(from)?;
+        let destination = self.locate(to)?;
+        if source.is_root(&self.root) {
+            return Err(format!("cannot move `{from}`"));
+        }
+        if destination.is_root(&self.root) {
+            return Err(format!("cannot replace `{to}`"));
+        }
+        let (source, destination) = match (source, destination) {
+            (Location::Home(source), Location::Home(destination)) => (source, destination),
+            (source, destination) => return self.relocate_across(from, source, to, destination),
+        };
+        if !exists(&source) {
+            return Err(format!("`{from}` does not exist or is not active"));
+        }
+        if exists(&destination) {
+            return Err(format!("`{to}` already exists"));
+        }
+
+        let mut index = self.index.lock().unwrap();
+        fs::rename(&source, &destination).map_err(|err| format!("{from}: {err}"))?;
+        rebase_keys(&mut index.items, from, to);
+        // Trashed children follow their directory, as in the files store
+        let trashed_children = self.trash_path(from)?;
+        if trashed_children.is_dir() && !index.trash.contains_key(from) {
+            let target = self.trash_path(to)?;
+            if !exists(&target) {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+                }
+                fs::rename(&trashed_children, &target).map_err(|err| err.to_string())?;
+                rebase_keys(&mut index.trash, from, to);
+            }
+        }
+        self.save(&index)
+    }
+
+    /// Moves involving a mounted volume. Within one volume it's a rename;
+    /// between volumes the item is copied and the original deleted, and
+    /// anything it had in the index is dropped.
+    fn relocate_across(
+        &self,
+        from: &str,
+        source: Location,
+        to: &str,
+        destination: Location,
+    ) -> Result<(), String> {
+        let (same_volume, source, destination) = match (source, destination) {
+            (
+                Location::Mounted {
+                    name: source_volume,
+                    host: source,
+                    ..
+                },
+                Location::Mounted {
+                    name: destination_volume,
+                    host: destination,
+                    ..
+                },
+            ) => (source_volume == destination_volume, source, destination),
+            (
+                Location::Home(source) | Location::Mounted { host: source, .. },
+                Location::Home(destination)
+                | Location::Mounted {
+                    host: destination, ..
+                },
+            ) => (false, source, destination),
+            _ => return Err(format!("cannot move `{from}` to `{to}`")),
+        };
+        if !exists(&source) {
+            return Err(format!("`{from}` does not exist"));
+        }
+        if exists(&destination) {
+            return Err(format!("`{to}` already exists"));
+        }
+        if same_volume {
+            return fs::rename(&source, &destination).map_err(|err| format!("{from}: {err}"));
+        }
+
+        if let Err(err) = copy_all(&source, &destination) {
+            let _ = remove_all(&destination);
+            return Err(format!("{from}: {err}"));
+        }
+        remove_all(&source).map_err(|err| format!("{from}: {err}"))?;
+        if !is_within(from, VOLUMES_PATH) {
+            let mut index = self.index.lock().unwrap();
+            remove_keys(&mut index.items, from);
+            self.save(&index)?;
+        }
+        Ok(())
+    }
+
+    /// `removeItem`: moves an active item (and its children) to the trash,
+    /// or deletes it for good when `permanent` or already trashed. Returns
+    /// the `uuid`s of deleted files so their cached content can be dropped.
+    /// Items on mounted volumes go to the host trash, or can only be deleted
+    /// permanently where there is none.
+    pub fn trash(&self, path: &str, permanent: bool) -> Result<Vec<String>, String> {
+        let location = self.locate(path)?;
+        if location.is_root(&self.root) {
+            return Err(format!("cannot trash `{path}`"));
+        }
+        let host = match location {
+            Location::Home(host) => host,
+            Location::Mounted { host, .. } => return self.trash_mounted(path, &host, permanent),
+            Location::Volumes => return Err(format!("cannot trash `{path}`")),
+        };
+        let mut index = self.index.lock().unwrap();
+
+        if !exists(&host) {
+            if !index.trash.contains_key(path) {
+                return Err(format!("`{path}` does not exist"));
+            }
+            let removed = self.delete_trashed(&mut index, path)?;
+            self.save(&index)?;
+            return Ok(removed);
+        }
+
+        if permanent {
+            let removed = remove_keys(&mut index.items, path);
+            remove_all(&host).map_err(|err| format!("{path}: {err}"))?;
+            self.save(&index)?;
+            return Ok(uuids(removed.iter().map(|(_, meta)| meta)));
+        }
+
+        // An older item trashed from the same path makes way
+        if index.trash.contains_key(path) {
+            self.delete_trashed(&mut index, path)?;
+        }
+        let mut paths = vec![path.to_string()];
+        collect_descendants(&host, path, &mut paths);
+        let target = self.trash_path(path)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        if exists(&target) {
+            remove_all(&target).map_err(|err| err.to_string())?;
+        }
+        fs::rename(&host, &target).map_err(|err| format!("{path}: {err}"))?;
+
+        let deleted_at = now();
+        for trashed in paths {
+            let meta = index.items.remove(&trashed).unwrap_or_default();
+            index.trash.insert(trashed, Trashed { deleted_at, meta });
+        }
+        self.save(&index)?;
+        Ok(Vec::new())
+    }
+
+    fn trash_mounted(
+        &self,
+        path: &str,
+        host: &Path,
+        permanent: bool,
+    ) -> Result<Vec<String>, String> {
+        let Some(host_trash) = &self.host_trash else {
+            if !permanent {
+                return Err(format!(
+                    "`{path}` is on a mounted volume and can only be deleted permanently"
+                ));
+            }
+            remove_all(host).map_err(|err| format!("{path}: {err}"))?;
+            return Ok(Vec::new());
+        };
+        let result = if !exists(host) {
+            // Already trashed, so it goes for good like a trashed home item
+            let (_, entry) = self
+                .host_trashed()
+                .into_iter()
+                .find(|(trashed, _)| trashed == path)
+                .ok_or_else(|| format!("`{path}` does not exist"))?;
+            host_trash.delete(&entry)
+        } else if permanent {
+            remove_all(host)
+        } else {
+            host_trash.trash(host).map(drop)
+        };
+        result.map_err(|err| format!("{path}: {err}"))?;
+        Ok(Vec::new())
+    }
+
+    fn delete_trashed(&self, index: &mut Index, path: &str) -> Result<Vec<String>, String> {
+        let target = self.trash_path(path)?;
+        if exists(&target) {
+            remove_all(&target).map_err(|err| format!("{path}: {err}"))?;
+        }
+        self.prune_trash(&target);
+        let removed = remove_keys(&mut index.trash, path);
+        Ok(uuids(removed.iter().map(|(_, trashed)| &trashed.meta)))
+    }
+
+    /// Drops the now-empty directories that only held trashed children.
+    fn prune_trash(&self, removed: &Path) {
+        let trash_root = self.root.join(TRASH_DIR);
+        let mut dir = removed.parent();
+        while let Some(current) = dir {
+            if current == trash_root || fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+
+    /// `restoreItem`: puts a trashed item (and its trashed children) back at
+    /// its original path.
+    pub fn restore(&self, path: &str) -> Result<(), String> {
+        if is_within(path, VOLUMES_PATH) {
+            return self.restore_mounted(path);
+        }
+        let mut index = self.index.lock().unwrap();
+        if !index.trash.contains_key(path) {
+            return Err(format!("`{path}` is not in the trash"));
+        }
+        let source = self.trash_path(path)?;
+        let destination = self.host_path(path)?;
+        if exists(&destination) {
+            return Err(format!("`{path}` already exists"));
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::rename(&source, &destination).map_err(|err| format!("{path}: {err}"))?;
+        self.prune_trash(&source);
+
+        for (restored, trashed) in remove_keys(&mut index.trash, path) {
+            index.items.insert(restored, trashed.meta);
+        }
+        self.save(&index)
+    }
+
+    /// Puts the newest item trashed from `path` on a mounted volume back.
+    fn restore_mounted(&self, path: &str) -> Result<(), String> {
+        let not_trashed = || format!("`{path}` is not in the trash");
+        let host_trash = self.host_trash.as_ref().ok_or_else(not_trashed)?;
+        let (_, entry) = self
+            .host_trashed()
+            .into_iter()
+            .find(|(trashed, _)| trashed == path)
+            .ok_or_else(not_trashed)?;
+        // Also checks the parent directory is still there
+        let Location::Mounted { host, .. } = self.locate(path)? else {
+            return Err(not_trashed());
+        };
+        host_trash
+            .restore(&entry, &host)
+            .map_err(|err| format!("{path}: {err}"))
+    }
+
+    /// `emptyTrash`: deletes everything in the trash, including items from
+    /// mounted volumes in the host trash, and returns the `uuid`s of
+    /// deleted files.
+    pub fn empty_trash(&self) -> Result<Vec<String>, String> {
+        if let Some(host_trash) = &self.host_trash {
+            for (path, entry) in self.host_trashed() {
+                host_trash
+                    .delete(&entry)
+                    .map_err(|err| format!("{path}: {err}"))?;
+            }
+        }
+        let mut index = self.index.lock().unwrap();
+        let trash_root = self.root.join(TRASH_DIR);
+        if exists(&trash_root) {
+            fs::remove_dir_all(&trash_root).map_err(|err| err.to_string())?;
+        }
+        let removed = std::mem::take(&mut index.trash);
+        self.save(&index)?;
+        Ok(uuids(removed.values().map(|trashed| &trashed.meta)))
+    }
+}
+
+fn uuids<'a>(metas: impl Iterator<Item = &'a Map<String, Value>>) -> Vec<String> {
+    metas
+        .filter_map(|meta| meta.get("uuid")?.as_str().map(str::to_string))
+        .collect()
+}
+
+fn collect_descendants(host: &Path, path: &str, paths: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(host) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        let child = format!("{path}/{name}");
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            collect_descendants(&entry.path(), &child, paths);
+        }
+        paths.push(child);
+    }
+}
+
+#[tauri::command]
+pub async fn fs_list(path: String, vfs: State<'_, Vfs>) -> Result<Vec<FileSystemItem>, String> {
+    vfs.list(&path)
+}
+
+#[tauri::command]
+pub async fn fs_read(path: String, vfs: State<'_, Vfs>) -> Result<Response, String> {
+    vfs.read(&path).map(Response::new)
+}
+
+/// Takes the content as the raw request body and the item as
+/// URI-encoded JSON in the [`ITEM_HEADER`] header.
+#[tauri::command]
+pub async fn fs_write(
+    request: Request<'_>,
+    vfs: State<'_, Vfs>,
+    search: State<'_, SearchIndex>,
+) -> Result<FileSystemItem, String> {
+    let (item, content): (NewItem, _) = ipc_body::decode(&request, ITEM_HEADER)?;
+    let item = vfs.write(item, content)?;
+    search.crawl(&vfs, &item.path);
+    Ok(item)
+}
+
+#[tauri::command]
+pub async fn fs_rename(
+    old_path: String,
+    new_path: String,
+    new_name: String,
+    vfs: State<'_, Vfs>,
+    search: State<'_, SearchIndex>,
+) -> Result<(), String> {
+    vfs.rename(&old_path, &new_path, &new_name)?;
+    search.relocate(&old_path, &new_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn fs_move(
+    source_path: String,
+    destination_path: String,
+    vfs: State<'_, Vfs>,
+    search: State<'_, SearchIndex>,
+) -> Result<(), String> {
+    vfs.move_item(&source_path, &destination_path)?;
+    search.relocate(&source_path, &destination_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn fs_trash(
+    path: String,
+    permanent: Option<bool>,
+    vfs: State<'_, Vfs>,
+    search: State<'_, SearchIndex>,
+) -> Result<Vec<String>, String> {
+    let uuids = vfs.trash(&path, permanent.unwrap_or(false))?;
+    search.remove(&path);
+    Ok(uuids)
+}
+
+#[tauri::command]
+pub async fn fs_restore(
+    path: String,
+    vfs: State<'_, Vfs>,
+    search: State<'_, SearchIndex>,
+) -> Result<(), String> {
+    vfs.restore(&path)?;
+    search.crawl(&vfs, &path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn fs_empty_trash(vfs: State<'_, Vfs>) -> Result<Vec<String>, String> {
+    vfs.empty_trash()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn new_item(path: &str, is_directory: bool, extra: Value) -> NewItem {
+        NewItem {
+            path: path.to_string(),
+            is_directory,
+            extra: extra.as_object().cloned().unwrap_or_default(),
+        }
+    }
+
+    fn vfs() -> (tempfile::TempDir, Vfs) {
+        let dir = tempfile::tempdir().unwrap();
+        let mounts = Mounts::load(dir.path().join(mounts::FILE_NAME));
+        let vfs = Vfs::open(dir.path().join(HOME_DIR_NAME), mounts, None).unwrap();
+        vfs.write(
+            new_item("/Documents", true, json!({ "icon": "/icons/folder.png" })),
+            &[],
+        )
+        .unwrap();
+        (dir, vfs)
+    }
+
+    fn names(items: &[FileSystemItem]) -> Vec<&str> {
+        items.iter().map(|item| item.name.as_str()).collect()
+    }
+
+    #[test]
+    fn writes_and_lists_with_metadata() {
+        let (_dir, vfs) = vfs();
+        let item = vfs
+            .write(
+                new_item("/Documents/a.md", false, json!({ "type": "markdown" })),
+                b"# hi",
+            )
+            .unwrap();
+        assert_eq!(item.size, Some(4));
+        assert_eq!(item.status, Status::Active);
+        assert!(item.extra.contains_key("uuid"));
+
+        let listed = vfs.list("/Documents").unwrap();
+        assert_eq!(listed, vec![item]);
+        assert_eq!(vfs.read("/Documents/a.md").unwrap(), b"# hi");
+        assert!(vfs.root.join("Documents/a.md").is_file());
+        assert_eq!(names(&vfs.list("/").unwrap()), ["Documents"]);
+    }
+
+    #[test]
+    fn rewrite_keeps_uuid_and_created_at() {
+        let (_dir, vfs) = vfs();
+        let first = vfs
+            .write(
+                new_item("/Documents/a.md", false, json!({ "createdAt": 1 })),
+                b"a",
+            )
+            .unwrap();
+        let second = vfs
+            .write(
+                new_item(
+                    "/Documents/a.md",
+                    false,
+                    json!({ "uuid": "other", "createdAt": 2 }),
+                ),
+                b"b",
+            )
+            .unwrap();
+        assert_eq!(second.extra["uuid"], first.extra["uuid"]);
+        assert_eq!(second.created_at, 1);
+    }
+
+    #[test]
+    fn rejects_escapes_and_missing_parents() {
+        let (_dir, vfs) = vfs();
+        for path in [
+            "/../etc/passwd",
+            "/Documents/../../x",
+            "relative",
+            "/.syaos/index.json",
+        ] {
+            assert!(
+                vfs.write(new_item(path, false, json!({})), b"").is_err(),
+                "{path}"
+            );
+        }
+        assert!(vfs
+            .write(new_item("/Missing/a.md", false, json!({})), b"")
+            .is_err());
+        assert!(vfs
+            .write(new_item("/Trash/a.md", false, json!({})), b"")
+            .is_err());
+    }
+
+    #[test]
+    fn trash_and_restore_directory() {
+        let (_dir, vfs) = vfs();
+        vfs.write(new_item("/Documents/Folder", true, json!({})), &[])
+            .unwrap();
+        vfs.write(
+            new_item("/Documents/Folder/b.txt", false, json!({ "uuid": "b" })),
+            b"b",
+        )
+        .unwrap();
+
+        vfs.trash("/Documents/Folder", false).unwrap();
+        assert!(vfs.list("/Documents").unwrap().is_empty());
+        let trashed = vfs.list(TRASH_PATH).unwrap();
+        assert_eq!(names(&trashed), ["Folder", "b.txt"]);
+        assert!(trashed.iter().all(|item| item.status == Status::Trashed
+            && item.original_path.as_deref() == Some(item.path.as_str())
+            && item.deleted_at.is_some()));
+
+        vfs.restore("/Documents/Folder").unwrap();
+        assert!(vfs.list(TRASH_PATH).unwrap().is_empty());
+        let restored = vfs.list("/Documents/Folder").unwrap();
+        assert_eq!(restored[0].extra["uuid"], "b");
+    }
+
+    #[test]
+    fn trashing_twice_deletes_and_empty_trash_returns_uuids() {
+        let (_dir, vfs) = vfs();
+        vfs.write(
+            new_item("/Documents/a.md", false, json!({ "uuid": "a" })),
+            b"a",
+        )
+        .unwrap();
+        vfs.write(
+            new_item("/Documents/b.md", false, json!({ "uuid": "b" })),
+            b"b",
+        )
+        .unwrap();
+
+        vfs.trash("/Documents/a.md", false).unwrap();
+        assert_eq!(vfs.trash("/Documents/a.md", false).unwrap(), ["a"]);
+        vfs.trash("/Documents/b.md", false).unwrap();
+        assert_eq!(vfs.empty_trash().unwrap(), ["b"]);
+        assert!(vfs.list(TRASH_PATH).unwrap().is_empty());
+        assert!(!vfs.root.join(TRASH_DIR).exists());
+    }
+
+    #[test]
+    fn rename_and_move_carry_metadata() {
+        let (_dir, vfs) = vfs();
+        vfs.write(new_item("/Images", true, json!({})), &[])
+            .unwrap();
+        vfs.write(
+            new_item("/Documents/a.md", false, json!({ "uuid": "a" })),
+            b"a",
+        )
+        .unwrap();
+
+        vfs.rename("/Documents/a.md", "/Documents/b.md", "b.md")
+            .unwrap();
+        assert!(vfs
+            .rename("/Documents/b.md", "/Images/b.md", "b.md")
+            .is_err());
+        vfs.move_item("/Documents/b.md", "/Images/b.md").unwrap();
+        assert!(vfs.move_item("/Documents", "/Documents/Inner").is_err());
+
+        let moved = vfs.list("/Images").unwrap();
+        assert_eq!(moved[0].path, "/Images/b.md");
+        assert_eq!(moved[0].extra["uuid"], "a");
+    }
+
+    #[test]
+    fn mounted_volumes_map_to_host_folders() {
+        let (dir, vfs) = vfs();
+        let shared = dir.path().join("Shared");
+        fs::create_dir(&shared).unwrap();
+        fs::write(shared.join("photo.JPG"), b"jpeg").unwrap();
+        vfs.mounts().add(&shared, None).unwrap();
+
+        let root = vfs.list("/").unwrap();
+        assert_eq!(names(&root), ["Documents", "Volumes"]);
+        let volumes = vfs.list(VOLUMES_PATH).unwrap();
+        assert_eq!(volumes[0].path, "/Volumes/Shared");
+        assert_eq!(volumes[0].extra["icon"], VOLUME_ICON);
+
+        let listed = vfs.list("/Volumes/Shared").unwrap();
+        assert_eq!(listed[0].size, Some(4));
+        assert_eq!(listed[0].extra["type"], "jpg");
+        assert_eq!(vfs.read("/Volumes/Shared/photo.JPG").unwrap(), b"jpeg");
+
+        vfs.write(new_item("/Volumes/Shared/a.md", false, json!({})), b"a")
+            .unwrap();
+        assert!(shared.join("a.md").is_file());
+        assert!(vfs
+            .write(new_item("/Volumes/Shared", true, json!({})), &[])
+            .is_err());
+        assert!(vfs.trash("/Volumes/Shared/a.md", false).is_err());
+
+        // Between volumes the item is copied over
+        vfs.move_item("/Volumes/Shared/a.md", "/Documents/a.md")
+            .unwrap();
+        assert!(!shared.join("a.md").exists());
+        assert_eq!(vfs.read("/Documents/a.md").unwrap(), b"a");
+        vfs.move_item("/Documents", "/Volumes/Shared/Documents")
+            .unwrap();
+        assert!(shared.join("Documents/a.md").is_file());
+        assert!(vfs.move_item("/Volumes/Shared", "/Moved").is_err());
+
+        vfs.trash("/Volumes/Shared/photo.JPG", true).unwrap();
+        assert!(!shared.join("photo.JPG").exists());
+        assert!(vfs.list("/Volumes/Missing").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn mounted_volumes_reject_escapes() {
+        let (dir, vfs) = vfs();
+        let shared = dir.path().join("Shared");
+        fs::create_dir(&shared).unwrap();
+        std::os::unix::fs::symlink(dir.path(), shared.join("up")).unwrap();
+        vfs.mounts().add(&shared, None).unwrap();
+
+        assert!(vfs.list("/Volumes/Shared").unwrap().is_empty());
+        assert!(vfs.list("/Volumes/Shared/up").is_err());
+        assert!(vfs
+            .write(new_item("/Volumes/Shared/up/x", false, json!({})), b"")
+            .is_err());
+        assert!(vfs.read("/Volumes/Shared/../Shared").is_err());
+    }
+
+    #[test]
+    fn mounted_items_go_to_the_host_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_home = dir.path().join("data");
+        let mounts = Mounts::load(dir.path().join(mounts::FILE_NAME));
+        let vfs = Vfs::open(
+            dir.path().join(HOME_DIR_NAME),
+            mounts,
+            Some(HostTrash::at(&data_home)),
+        )
+        .unwrap();
+        let shared = dir.path().join("Shared");
+        fs::create_dir(&shared).unwrap();
+        fs::write(shared.join("a.md"), "a").unwrap();
+        fs::write(shared.join("b.md"), "b").unwrap();
+        vfs.mounts().add(&shared, None).unwrap();
+        // Trashed by another app, so not part of the syaOS trash
+        fs::write(dir.path().join("other.txt"), "x").unwrap();
+        let other = HostTrash::at(&data_home)
+            .trash(&dir.path().join("other.txt"))
+            .unwrap();
+
+        vfs.trash("/Volumes/Shared/a.md", false).unwrap();
+        vfs.trash("/Volumes/Shared/b.md", false).unwrap();
+        assert!(!shared.join("a.md").exists());
+        assert!(data_home.join("Trash/info/a.md.trashinfo").is_file());
+        let trashed = vfs.list(TRASH_PATH).unwrap();
+        assert_eq!(trashed.len(), 2);
+        assert!(trashed.iter().all(|item| item.status == Status::Trashed
+            && item.original_path.as_deref() == Some(item.path.as_str())
+            && item.path.starts_with("/Volumes/Shared/")));
+
+        vfs.restore("/Volumes/Shared/a.md").unwrap();
+        assert_eq!(fs::read(shared.join("a.md")).unwrap(), b"a");
+        // Trashing what is already trashed deletes it
+        vfs.trash("/Volumes/Shared/b.md", false).unwrap();
+        assert!(!data_home.join("Trash/files/b.md").exists());
+
+        vfs.trash("/Volumes/Shared/a.md", false).unwrap();
+        vfs.empty_trash().unwrap();
+        assert!(vfs.list(TRASH_PATH).unwrap().is_empty());
+        assert!(other.file.is_file());
+    }
+
+    #[test]
+    fn index_survives_reopen() {
+        let (dir, vfs) = vfs();
+        vfs.write(
+            new_item(
+                "/Documents/alias",
+                false,
+                json!({ "aliasTarget": "ipod", "aliasType": "app" }),
+            ),
+            &[],
+        )
+        .unwrap();
+        drop(vfs);
+
+        let mounts = Mounts::load(dir.path().join(mounts::FILE_NAME));
+        let reopened = Vfs::open(dir.path().join(HOME_DIR_NAME), mounts, None).unwrap();
+        let items = reopened.list("/Documents").unwrap();
+        assert_eq!(items[0].extra["aliasTarget"], "ipod");
+        assert_eq!(items[0].extra["aliasType"], "app");
+    }
+
+    #[test]
+    fn home_is_created_by_the_first_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join(HOME_DIR_NAME);
+        let mounts = Mounts::load(dir.path().join(mounts::FILE_NAME));
+        let vfs = Vfs::open(root.clone(), mounts, None).unwrap();
+        assert!(vfs.list("/").unwrap().is_empty());
+        assert!(!root.exists());
+
+        vfs.write(new_item("/Documents", true, json!({})), &[])
+            .unwrap();
+        assert!(root.join("Documents").is_dir());
+    }
+}
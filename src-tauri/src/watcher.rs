@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{
+    new_debouncer, DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::mounts::Mount;
+use crate::search;
+
+/// Event carrying a batch of [`Change`]s on a mounted volume.
+pub const FS_CHANGED_EVENT: &str = "fs://changed";
+
+/// How long a burst of host events is collected before it is reported.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Create,
+    Modify,
+    Rename,
+    Delete,
+}
+
+/// A change to the item at a virtual `/Volumes/...` path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub path: String,
+    /// Where a renamed item used to be.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+}
+
+/// One watcher per mounted volume, by volume name.
+#[derive(Default)]
+pub struct Watchers {
+    debouncers: Mutex<HashMap<String, Debouncer<RecommendedWatcher, RecommendedCache>>>,
+}
+
+/// Turns a debounced batch of host events into [`Change`]s, dropping
+/// accesses, hidden files and repeats.
+fn changes(mount: &Mount, events: &[DebouncedEvent]) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    let mut push = |kind, host: &Path, old_host: Option<&Path>| {
+        let Some(path) = mount.virtual_path(host) else {
+            return;
+        };
+        let (kind, old_path) = match old_host.map(|old_host| mount.virtual_path(old_host)) {
+            // Renamed from a hidden name, e.g. an atomic save's temp file
+            Some(None) => (ChangeKind::Modify, None),
+            Some(old_path) => (kind, old_path),
+            None => (kind, None),
+        };
+        let change = Change {
+            kind,
+            path,
+            old_path,
+        };
+        if !changes.contains(&change) {
+            changes.push(change);
+        }
+    };
+
+    for event in events {
+        if event.need_rescan() {
+            push(ChangeKind::Modify, &mount.host_path, None);
+            continue;
+        }
+        match event.kind {
+            EventKind::Create(_) => {
+                for path in &event.paths {
+                    push(ChangeKind::Create, path, None);
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in &event.paths {
+                    push(ChangeKind::Delete, path, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                if let [from, to] = event.paths.as_slice() {
+                    if mount.virtual_path(to).is_some() {
+                        push(ChangeKind::Rename, to, Some(from));
+                    } else {
+                        // Renamed to a hidden name, so it's gone from the volume
+                        push(ChangeKind::Delete, from, None);
+                    }
+                }
+            }
+            // Only one side of the rename is on this volume, or the
+            // platform didn't pair them
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in &event.paths {
+                    let kind = if path.exists() {
+                        ChangeKind::Create
+                    } else {
+                        ChangeKind::Delete
+                    };
+                    push(kind, path, None);
+                }
+            }
+            EventKind::Modify(_) | EventKind::Any | EventKind::Other => {
+                for path in &event.paths {
+                    push(ChangeKind::Modify, path, None);
+                }
+            }
+            EventKind::Access(_) => {}
+        }
+    }
+    changes
+}
+
+/// Starts reporting changes under `mount` as [`FS_CHANGED_EVENT`]s.
+pub fn watch<R: Runtime>(app: &AppHandle<R>, mount: &Mount) {
+    let Some(watchers) = app.try_state::<Watchers>() else {
+        return;
+    };
+    let handle = app.clone();
+    let watched = mount.clone();
+    let handler = move |result: DebounceEventResult| match result {
+        Ok(events) => {
+            let changes = changes(&watched, &events);
+            if changes.is_empty() {
+                return;
+            }
+            search::apply(&handle, &changes);
+            if let Err(err) = handle.emit(FS_CHANGED_EVENT, &changes) {
+                eprintln!("failed to emit {FS_CHANGED_EVENT}: {err}");
+            }
+        }
+        Err(errors) => {
+            for err in errors {
+                eprintln!("error watching volume `{}`: {err}", watched.name);
+            }
+        }
+    };
+
+    let debouncer = new_debouncer(DEBOUNCE, None, handler).and_then(|mut debouncer| {
+        debouncer.watch(&mount.host_path, RecursiveMode::Recursive)?;
+        Ok(debouncer)
+    });
+    match debouncer {
+        Ok(debouncer) => {
+            watchers
+                .debouncers
+                .lock()
+                .unwrap()
+                .insert(mount.name.clone(), debouncer);
+        }
+        Err(err) => eprintln!("failed to watch volume `{}`: {err}", mount.name),
+    }
+}
+
+/// Stops watching the volume `name`.
+pub fn unwatch<R: Runtime>(app: &AppHandle<R>, name: &str) {
+    if let Some(watchers) = app.try_state::<Watchers>() {
+        // Dropping the debouncer stops its watcher
+        watchers.debouncers.lock().unwrap().remove(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    use notify_debouncer_full::notify::event::{CreateKind, DataChange, Flag, RemoveKind};
+    use notify_debouncer_full::notify::Event;
+
+    use super::*;
+
+    fn event(kind: EventKind, paths: &[&str]) -> DebouncedEvent {
+        let event = paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(PathBuf::from(path))
+        });
+        DebouncedEvent::new(event, Instant::now())
+    }
+
+    fn mount() -> Mount {
+        Mount {
+            name: "Shared".to_string(),
+            host_path: PathBuf::from("/host/shared"),
+        }
+    }
+
+    fn change(kind: ChangeKind, path: &str, old_path: Option<&str>) -> Change {
+        Change {
+            kind,
+            path: path.to_string(),
+            old_path: old_path.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn maps_host_events_to_volume_paths() {
+        let mount = mount();
+        let events = [
+            event(EventKind::Create(CreateKind::File), &["/host/shared/a.md"]),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                &["/host/shared/a.md"],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Any)),
+                &["/host/shared/a.md"],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/host/shared/a.md", "/host/shared/Docs/b.md"],
+            ),
+            event(EventKind::Remove(RemoveKind::Folder), &["/host/shared/Old"]),
+            event(
+                EventKind::Access(notify_debouncer_full::notify::event::AccessKind::Any),
+                &["/host/shared/c.md"],
+            ),
+        ];
+
+        assert_eq!(
+            changes(&mount, &events),
+            [
+                change(ChangeKind::Create, "/Volumes/Shared/a.md", None),
+                change(ChangeKind::Modify, "/Volumes/Shared/a.md", None),
+                change(
+                    ChangeKind::Rename,
+                    "/Volumes/Shared/Docs/b.md",
+                    Some("/Volumes/Shared/a.md")
+                ),
+                change(ChangeKind::Delete, "/Volumes/Shared/Old", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn hides_dotfiles_and_reports_atomic_saves_as_modify() {
+        let mount = mount();
+        let events = [
+            event(
+                EventKind::Create(CreateKind::File),
+                &["/host/shared/.a.md.tmp"],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/host/shared/.a.md.tmp", "/host/shared/a.md"],
+            ),
+            event(EventKind::Create(CreateKind::File), &["/elsewhere/x"]),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/host/shared/b.md", "/host/shared/.b.md"],
+            ),
+        ];
+        assert_eq!(
+            changes(&mount, &events),
+            [
+                change(ChangeKind::Modify, "/Volumes/Shared/a.md", None),
+                change(ChangeKind::Delete, "/Volumes/Shared/b.md", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn rescans_report_the_volume_root() {
+        let mount = mount();
+        let rescan = DebouncedEvent::new(
+            Event::new(EventKind::Other).set_flag(Flag::Rescan),
+            Instant::now(),
+        );
+        assert_eq!(
+            changes(&mount, &[rescan]),
+            [change(ChangeKind::Modify, "/Volumes/Shared", None)]
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow,
+    WindowEvent,
+};
+
+/// Name of the file the main window's geometry is kept in, per profile.
+pub const FILE_NAME: &str = "window-state.json";
+
+/// Geometry of the main window in physical pixels. Position and size are the
+/// last ones the window had while neither maximized nor fullscreen, so
+/// un-maximizing after a restore goes back to them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    /// Name of the monitor the window was on, if the platform reports one.
+    pub monitor: Option<String>,
+}
+
+/// A monitor's usable area (excluding taskbars and docks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Area {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Area {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (i64::from(x), i64::from(y));
+        x >= i64::from(self.x)
+            && y >= i64::from(self.y)
+            && x < i64::from(self.x) + i64::from(self.width)
+            && y < i64::from(self.y) + i64::from(self.height)
+    }
+}
+
+impl From<&Monitor> for Area {
+    fn from(monitor: &Monitor) -> Self {
+        let work_area = monitor.work_area();
+        Self {
+            name: monitor.name().cloned(),
+            x: work_area.position.x,
+            y: work_area.position.y,
+            width: work_area.size.width,
+            height: work_area.size.height,
+        }
+    }
+}
+
+impl WindowState {
+    /// Fits the window onto one of `areas`: the monitor it was saved on if
+    /// that is still connected, else the one under its centre, else the
+    /// first (primary) one. Returns `None` when there are no monitors.
+    pub fn clamp_to(&self, areas: &[Area]) -> Option<Self> {
+        let center_x = self.x.saturating_add((self.width / 2) as i32);
+        let center_y = self.y.saturating_add((self.height / 2) as i32);
+        let area = areas
+            .iter()
+            .find(|area| self.monitor.is_some() && area.name == self.monitor)
+            .or_else(|| areas.iter().find(|area| area.contains(center_x, center_y)))
+            .or_else(|| areas.first())?;
+
+        let width = self.width.min(area.width);
+        let height = self.height.min(area.height);
+        let max_x = area.x.saturating_add((area.width - width) as i32);
+        let max_y = area.y.saturating_add((area.height - height) as i32);
+        Some(Self {
+            x: self.x.clamp(area.x, max_x),
+            y: self.y.clamp(area.y, max_y),
+            width,
+            height,
+            monitor: area.name.clone(),
+            ..self.clone()
+        })
+    }
+}
+
+/// Missing or unreadable files mean "use the configured geometry".
+pub fn load(path: &Path) -> Option<WindowState> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents)
+        .map_err(|err| eprintln!("ignoring invalid {}: {err}", path.display()))
+        .ok()
+}
+
+fn save(path: &Path, state: &WindowState) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(state)?)
+}
+
+/// Moves `window` to the saved geometry, clamped to the connected monitors.
+pub fn restore<R: Runtime>(window: &WebviewWindow<R>, state: &WindowState) -> tauri::Result<()> {
+    let areas: Vec<Area> = window
+        .available_monitors()?
+        .iter()
+        .map(Area::from)
+        .collect();
+    let Some(state) = state.clamp_to(&areas) else {
+        return Ok(());
+    };
+
+    window.set_size(PhysicalSize::new(state.width, state.height))?;
+    window.set_position(PhysicalPosition::new(state.x, state.y))?;
+    if state.fullscreen {
+        window.set_fullscreen(true)?;
+    } else if state.maximized {
+        window.maximize()?;
+    }
+    Ok(())
+}
+
+/// Latest geometry of the tracked window, written out on close and on exit.
+pub struct Tracker {
+    path: PathBuf,
+    state: Mutex<Option<WindowState>>,
+}
+
+impl Tracker {
+    pub fn new(path: PathBuf, initial: Option<WindowState>) -> Self {
+        Self {
+            path,
+            state: Mutex::new(initial),
+        }
+    }
+
+    fn update<R: Runtime>(&self, window: &WebviewWindow<R>) {
+        // Minimized windows report a bogus position on Windows
+        if window.is_minimized().unwrap_or(false) {
+            return;
+        }
+        let maximized = window.is_maximized().unwrap_or(false);
+        let fullscreen = window.is_fullscreen().unwrap_or(false);
+        let monitor = window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned());
+
+        let mut state = self.state.lock().unwrap();
+        match state.as_mut() {
+            // Keep the normal geometry to return to
+            Some(state) if maximized || fullscreen => {
+                state.maximized = maximized;
+                state.fullscreen = fullscreen;
+                state.monitor = monitor.or(state.monitor.take());
+            }
+            _ => {
+                let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size())
+                else {
+                    return;
+                };
+                *state = Some(WindowState {
+                    x: position.x,
+                    y: position.y,
+                    width: size.width,
+                    height: size.height,
+                    maximized,
+                    fullscreen,
+                    monitor,
+                });
+            }
+        }
+    }
+
+    /// Writes the latest geometry, if any was seen.
+    pub fn persist(&self) {
+        let Some(state) = self.state.lock().unwrap().clone() else {
+            return;
+        };
+        if let Err(err) = save(&self.path, &state) {
+            eprintln!("failed to save {}: {err}", self.path.display());
+        }
+    }
+}
+
+/// Keeps the managed [`Tracker`] up to date with `window` and saves it when
+/// the window is closed.
+pub fn track<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    let handle = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        let tracker = handle.state::<Tracker>();
+        match event {
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) => tracker.update(&tracked),
+            WindowEvent::CloseRequested { .. } => tracker.persist(),
+            _ => {}
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(name: &str, x: i32, y: i32, width: u32, height: u32) -> Area {
+        Area {
+            name: Some(name.to_string()),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn state(x: i32, y: i32, width: u32, height: u32, monitor: Option<&str>) -> WindowState {
+        WindowState {
+            x,
+            y,
+            width,
+            height,
+            maximized: false,
+            fullscreen: false,
+            monitor: monitor.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn visible_window_is_unchanged() {
+        let areas = [area("A", 0, 0, 1920, 1080)];
+        let saved = state(100, 100, 1280, 800, Some("A"));
+        assert_eq!(saved.clamp_to(&areas), Some(saved));
+    }
+
+    #[test]
+    fn window_on_disconnected_monitor_moves_to_primary() {
+        let areas = [area("A", 0, 0, 1920, 1080)];
+        let saved = state(2500, 200, 1280, 800, Some("B"));
+        assert_eq!(
+            saved.clamp_to(&areas),
+            Some(state(640, 200, 1280, 800, Some("A")))
+        );
+    }
+
+    #[test]
+    fn window_follows_its_monitor() {
+        let areas = [area("A", 0, 0, 1920, 1080), area("B", 1920, 0, 2560, 1440)];
+        let saved = state(2000, 100, 1280, 800, Some("B"));
+        assert_eq!(saved.clamp_to(&areas), Some(saved));
+    }
+
+    #[test]
+    fn oversized_window_shrinks_to_fit() {
+        let areas = [area("A", 0, 25, 1440, 875)];
+        let saved = state(-50, 0, 2560, 1440, Some("A"));
+        assert_eq!(
+            saved.clamp_to(&areas),
+            Some(state(0, 25, 1440, 875, Some("A")))
+        );
+    }
+
+    #[test]
+    fn no_monitors_means_no_restore() {
+        assert_eq!(state(0, 0, 800, 600, None).clamp_to(&[]), None);
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profile").join(FILE_NAME);
+        let saved = WindowState {
+            maximized: true,
+            ..state(10, 20, 1024, 768, Some("A"))
+        };
+        save(&path, &saved).unwrap();
+        assert_eq!(load(&path), Some(saved));
+    }
+}
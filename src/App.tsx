@@ -14,6 +14,9 @@ import { useOffline } from "./hooks/useOffline";
 import { useTranslation } from "react-i18next";
 import { isTauri } from "./utils/platform";
 import { checkDesktopUpdate, onDesktopUpdate, DesktopUpdateResult } from "./utils/prefetch";
+import { checkShellCompatibility, notifyShellReady, rememberShellTheme } from "./utils/shell";
+import { startSearchIndexSync } from "./utils/searchIndex";
+import { importLocalStorage } from "./utils/shellStorage";
 import { githubRepo, productName } from "./config/branding";
 import { DownloadSimple } from "@phosphor-icons/react";
 import { ScreenSaverOverlay } from "./components/screensavers/ScreenSaverOverlay";
@@ -159,6 +162,42 @@ export function App() {
     return () => clearTimeout(timer);
   }, [setLastSeenDesktopVersion]);
 
+  // Swap the shell's splash window for the desktop
+  useEffect(() => {
+    notifyShellReady();
+  }, []);
+
+  // Keep the shell's content search up to date with browser documents
+  useEffect(() => startSearchIndexSync(), []);
+  useEffect(() => {
+    void importLocalStorage();
+  }, []);
+
+  // Let the shell theme its splash window on the next launch
+  useEffect(() => {
+    rememberShellTheme(currentTheme);
+  }, [currentTheme]);
+
+  // Warn when this web build needs a newer desktop shell than the one running it
+  useEffect(() => {
+    if (!isTauri()) return;
+
+    checkShellCompatibility().then((result) => {
+      if (result?.status !== "shellTooOld") return;
+      toast.warning(t("common.toast.shellTooOld", { productName }), {
+        id: "shell-too-old",
+        description: t("common.toast.shellTooOldDesc"),
+        duration: Infinity,
+        action: {
+          label: t("common.toast.download"),
+          onClick: () => {
+            window.open(`${githubRepo}/releases/latest`, "_blank");
+          },
+        },
+      });
+    });
+  }, [t]);
+
   if (showBootScreen) {
     return (
       <BootScreen
@@ -34,7 +34,13 @@ import { STORES } from "@/utils/indexedDB";
 import { track } from "@vercel/analytics";
 import { APPLET_ANALYTICS } from "@/utils/analytics";
 import { extractMetadataFromHtml } from "@/utils/appletMetadata";
-import { exportAppletAsHtml } from "@/utils/appletImportExport";
+import {
+  exportAppletAsHtml,
+  exportAppletToHost,
+  importAppletFromHost,
+  type ImportedAppletData,
+} from "@/utils/appletImportExport";
+import { isTauri } from "@/utils/platform";
 import { useTranslation } from "react-i18next";
 
 export function AppletViewerAppComponent({
@@ -814,6 +820,84 @@ export function AppletViewerAppComponent({
     };
   };
 
+  // Save an imported applet to /Applets and open it
+  const saveImportedApplet = async ({
+    content,
+    name: importFileName,
+    icon,
+    shareId,
+    createdBy,
+    windowWidth,
+    windowHeight,
+    createdAt,
+    modifiedAt,
+  }: ImportedAppletData) => {
+    const filePath = `/Applets/${importFileName}`;
+    const fileStore = useFilesStore.getState();
+    
+    // Save the file to the filesystem with all metadata
+    await saveFile({
+      name: importFileName,
+      path: filePath,
+      content: content,
+      type: "html",
+      icon: icon,
+      shareId: shareId,
+      createdBy,
+    });
+
+    // Update additional metadata if present
+    if (windowWidth || windowHeight || createdAt || modifiedAt) {
+      fileStore.updateItemMetadata(filePath, {
+        ...(windowWidth && { windowWidth }),
+        ...(windowHeight && { windowHeight }),
+        ...(createdAt && { createdAt }),
+        ...(modifiedAt && { modifiedAt }),
+      });
+    }
+
+    // Dispatch event to notify Finder of the new file
+    const saveEvent = new CustomEvent("saveFile", {
+      detail: {
+        name: importFileName,
+        path: filePath,
+        content: content,
+        icon: icon,
+      },
+    });
+    window.dispatchEvent(saveEvent);
+
+    // Launch a new applet viewer instance with the imported content
+    launchApp("applet-viewer", {
+      initialData: {
+        path: filePath,
+        content: content,
+      },
+    });
+
+    toast.success("Applet imported!", {
+      description: `${importFileName} saved to /Applets${
+        icon ? ` with ${icon} icon` : ""
+      }`,
+    });
+  };
+
+  // Import through the desktop shell's open dialog
+  const handleImportFromHost = async () => {
+    try {
+      const imported = await importAppletFromHost(
+        undefined,
+        username || undefined
+      );
+      if (imported) await saveImportedApplet(imported);
+    } catch (error) {
+      console.error("Import failed:", error);
+      toast.error("Import failed", {
+        description: error instanceof Error ? error.message : String(error),
+      });
+    }
+  };
+
   // Import handler
   const handleFileSelect = async (
     event: React.ChangeEvent<HTMLInputElement>
@@ -934,53 +1018,16 @@ export function AppletViewerAppComponent({
           importFileName = `${importFileName}.app`;
         }
 
-        const filePath = `/Applets/${importFileName}`;
-        const fileStore = useFilesStore.getState();
-        
-        // Save the file to the filesystem with all metadata
-        await saveFile({
+        await saveImportedApplet({
+          content,
           name: importFileName,
-          path: filePath,
-          content: content,
-          type: "html",
-          icon: icon,
-          shareId: shareId,
+          icon,
+          shareId,
           createdBy: createdBy || username || undefined,
-        });
-
-        // Update additional metadata if present
-        if (windowWidth || windowHeight || createdAt || modifiedAt) {
-          fileStore.updateItemMetadata(filePath, {
-            ...(windowWidth && { windowWidth }),
-            ...(windowHeight && { windowHeight }),
-            ...(createdAt && { createdAt }),
-            ...(modifiedAt && { modifiedAt }),
-          });
-        }
-
-        // Dispatch event to notify Finder of the new file
-        const saveEvent = new CustomEvent("saveFile", {
-          detail: {
-            name: importFileName,
-            path: filePath,
-            content: content,
-            icon: icon,
-          },
-        });
-        window.dispatchEvent(saveEvent);
-
-        // Launch a new applet viewer instance with the imported content
-        launchApp("applet-viewer", {
-          initialData: {
-            path: filePath,
-            content: content,
-          },
-        });
-
-        toast.success("Applet imported!", {
-          description: `${importFileName} saved to /Applets${
-            icon ? ` with ${icon} icon` : ""
-          }`,
+          windowWidth,
+          windowHeight,
+          createdAt,
+          modifiedAt,
         });
       } catch (error) {
         console.error("Import failed:", error);
@@ -996,9 +1043,30 @@ export function AppletViewerAppComponent({
     }
   };
 
+  // Export to a host file through the desktop shell's save dialog
+  const exportToHost = async (format: "app" | "html") => {
+    try {
+      const path = await exportAppletToHost(htmlContent, appletPath, format);
+      if (path) {
+        toast.success("Applet exported!", {
+          description: `${path.split(/[\\/]/).pop()} exported successfully.`,
+        });
+      }
+    } catch (error) {
+      console.error("Export failed:", error);
+      toast.error("Export failed", {
+        description: error instanceof Error ? error.message : String(error),
+      });
+    }
+  };
+
   // Export as App handler (with full JSON metadata, gzipped)
   const handleExportAsApp = async () => {
     if (!hasAppletContent) return;
+    if (isTauri()) {
+      await exportToHost("app");
+      return;
+    }
 
     // Get base filename without extension
     let filename = appletPath
@@ -1099,6 +1167,10 @@ export function AppletViewerAppComponent({
   // Export as HTML handler (without emoji prefix)
   const handleExportAsHtml = () => {
     if (!hasAppletContent) return;
+    if (isTauri()) {
+      exportToHost("html");
+      return;
+    }
     exportAppletAsHtml(htmlContent, appletPath);
   };
 
@@ -1373,6 +1445,7 @@ export function AppletViewerAppComponent({
       onShareApplet={handleShareApplet}
       hasAppletContent={hasAppletContent}
       handleFileSelect={handleFileSelect}
+      onImportFromDevice={isTauri() ? handleImportFromHost : undefined}
       instanceId={instanceId}
       onSetUsername={promptSetUsername}
       onVerifyToken={promptVerifyToken}
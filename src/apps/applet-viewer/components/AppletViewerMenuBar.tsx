@@ -30,6 +30,8 @@ interface AppletViewerMenuBarProps {
   onShareApplet: () => void;
   hasAppletContent: boolean;
   handleFileSelect: (event: React.ChangeEvent<HTMLInputElement>) => void;
+  /** Replaces the file input, e.g. with a native open dialog */
+  onImportFromDevice?: () => void;
   instanceId?: string;
   onSetUsername?: () => void;
   onVerifyToken?: () => void;
@@ -48,6 +50,7 @@ export function AppletViewerMenuBar({
   onShareApplet,
   hasAppletContent,
   handleFileSelect,
+  onImportFromDevice,
   instanceId,
   onSetUsername,
   onVerifyToken,
@@ -109,7 +112,9 @@ export function AppletViewerMenuBar({
           )}
           <MenubarSeparator className="h-[2px] bg-black my-1" />
           <MenubarItem
-            onClick={() => fileInputRef.current?.click()}
+            onClick={
+              onImportFromDevice ?? (() => fileInputRef.current?.click())
+            }
             className="text-md h-6 px-3"
           >
             {t("apps.applet-viewer.menu.importFromDevice")}
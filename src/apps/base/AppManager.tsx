@@ -12,6 +12,14 @@ import { extractCodeFromPath } from "@/utils/sharedUrl";
 import { toast } from "sonner";
 import { requestCloseWindow } from "@/utils/windowUtils";
 import { useThemeStore } from "@/stores/useThemeStore";
+import { useChatsStore } from "@/stores/useChatsStore";
+import { useAppStore } from "@/stores/useAppStore";
+import {
+  listenForShellDeepLinks,
+  listenForShellLaunches,
+  listenForShellOpenedFiles,
+} from "@/utils/shell";
+import { initialDataForOpenedFile } from "@/utils/openedFiles";
 
 interface AppManagerProps {
   apps: AnyApp[];
@@ -270,9 +278,10 @@ export function AppManager({ apps }: AppManagerProps) {
         appId: AppId;
         initialPath?: string;
         initialData?: unknown;
+        multiWindow?: boolean;
       }>,
     ) => {
-      const { appId, initialPath, initialData } = event.detail;
+      const { appId, initialPath, initialData, multiWindow } = event.detail;
 
       console.log(
         `[AppManager] Launch event received for ${appId}`,
@@ -285,7 +294,7 @@ export function AppManager({ apps }: AppManagerProps) {
       );
 
       // Use instance system
-      const instanceId = launchApp(appId, initialData);
+      const instanceId = launchApp(appId, initialData, undefined, multiWindow);
       console.log(
         `[AppManager] Launched instance ${instanceId} for app ${appId}`,
       );
@@ -314,6 +323,82 @@ export function AppManager({ apps }: AppManagerProps) {
     };
   }, [instances, launchApp]);
 
+  // Forward launch requests, syaos:// links and opened host files from the
+  // desktop shell
+  useEffect(() => {
+    const dispatchLaunch = (detail: {
+      appId: AppId;
+      initialData?: unknown;
+      multiWindow?: boolean;
+    }) => {
+      window.dispatchEvent(new CustomEvent("launchApp", { detail }));
+    };
+
+    const subscriptions = [
+      listenForShellLaunches((request) => {
+        dispatchLaunch({
+          appId: request.appId,
+          initialData: request.path ? { path: request.path } : undefined,
+          multiWindow: request.newWindow,
+        });
+      }),
+      listenForShellDeepLinks((link) => {
+        switch (link.route) {
+          case "applet":
+            toast.info(t("common.loading.openingSharedApplet"));
+            dispatchLaunch({
+              appId: "applet-viewer",
+              initialData: { shareCode: link.shareId, path: "", content: "" },
+            });
+            break;
+          case "ipod":
+            toast.info(t("common.loading.openingSharedIpodTrack"));
+            dispatchLaunch({ appId: "ipod", initialData: { videoId: link.songId } });
+            break;
+          case "chatRoom":
+            useChatsStore.getState().setCurrentRoomId(link.roomId);
+            dispatchLaunch({ appId: "chats" });
+            break;
+        }
+      }),
+      listenForShellOpenedFiles((file) => {
+        initialDataForOpenedFile(file)
+          .then((initialData) =>
+            dispatchLaunch({
+              appId: file.appId,
+              initialData,
+              multiWindow: file.newWindow,
+            }),
+          )
+          .catch((error) => {
+            console.error(`[AppManager] Could not open ${file.path}:`, error);
+            toast.error(error instanceof Error ? error.message : String(error));
+          });
+      }),
+    ];
+
+    return () => {
+      subscriptions.forEach((subscription) =>
+        subscription.then((unlisten) => unlisten()),
+      );
+    };
+  }, [t]);
+
+  // Kiosk mode: close everything after the shell reports inactivity
+  useEffect(() => {
+    const handleIdleReset = () => {
+      const { instances, closeAppInstance } = useAppStore.getState();
+      Object.values(instances)
+        .filter((instance) => instance.isOpen)
+        .forEach((instance) => closeAppInstance(instance.instanceId));
+    };
+
+    window.addEventListener("kioskIdleReset", handleIdleReset);
+    return () => {
+      window.removeEventListener("kioskIdleReset", handleIdleReset);
+    };
+  }, []);
+
   // Listen for expose view toggle events (e.g., from keyboard shortcut, dock menu)
   useEffect(() => {
     const handleExposeToggle = () => {
@@ -38,7 +38,13 @@ import { useAuth } from "@/hooks/useAuth";
 import { toast } from "sonner";
 import React from "react";
 import { useThemeStore } from "@/stores/useThemeStore";
-import { getApiUrl } from "@/utils/platform";
+import { getApiUrl, isTauri } from "@/utils/platform";
+import { exportProfile, importProfile } from "@/utils/profileArchive";
+import {
+  clearShellStorage,
+  readAllStorage,
+  syncShellStorage,
+} from "@/utils/shellStorage";
 import { themes } from "@/themes";
 import { OsThemeId } from "@/themes/types";
 import { getTabStyles } from "@/utils/tabStyles";
@@ -543,7 +549,16 @@ export function ControlPanelsAppComponent({
     performReset();
   };
 
-  const performReset = () => {
+  const performReset = async () => {
+    // The desktop shell keeps some stores itself
+    try {
+      await clearShellStorage(["ryos:files"]);
+    } catch (error) {
+      clearNextBootMessage();
+      toast.error("Reset Failed", { description: String(error) });
+      return;
+    }
+
     // Preserve critical recovery keys while clearing everything else
     const fileMetadataStore = localStorage.getItem("ryos:files");
     const usernameRecovery = localStorage.getItem("_usr_recovery_key_");
@@ -565,6 +580,48 @@ export function ControlPanelsAppComponent({
     window.location.reload();
   };
 
+  const handleNativeBackup = async () => {
+    try {
+      const report = await exportProfile();
+      if (report) {
+        toast.success("Profile Exported", {
+          description: report.path,
+        });
+      }
+    } catch (error) {
+      console.error("Profile export failed:", error);
+      toast.error("Export Failed", {
+        description: String(error),
+      });
+    }
+  };
+
+  const handleNativeRestore = async () => {
+    try {
+      const preview = await importProfile({ dryRun: true });
+      if (!preview) return;
+
+      const storeCounts = Object.values(preview.stores);
+      const added =
+        preview.localStorage.added +
+        storeCounts.reduce((sum, counts) => sum + counts.added, 0);
+      const summary = [
+        `${added} new item(s)`,
+        `${preview.conflicts.length} existing item(s) will be replaced`,
+        ...preview.warnings,
+      ].join("\n");
+      if (!window.confirm(`Import this profile?\n\n${summary}`)) return;
+
+      await importProfile({ dryRun: false, token: preview.token });
+      window.location.reload();
+    } catch (error) {
+      console.error("Profile import failed:", error);
+      toast.error("Import Failed", {
+        description: String(error),
+      });
+    }
+  };
+
   const handleBackup = async () => {
     const backup: {
       localStorage: Record<string, string | null>;
@@ -590,12 +647,17 @@ export function ControlPanelsAppComponent({
       version: 3, // Version 3 includes applets support
     };
 
-    // Backup all localStorage data
-    for (let i = 0; i < localStorage.length; i++) {
-      const key = localStorage.key(i);
-      if (key) {
-        backup.localStorage[key] = localStorage.getItem(key);
-      }
+    // Backup all localStorage data, and the stores the desktop shell keeps
+    try {
+      backup.localStorage = await readAllStorage();
+    } catch (error) {
+      console.error("Error backing up settings:", error);
+      alert(
+        `Failed to backup settings: ${
+          error instanceof Error ? error.message : String(error)
+        }`
+      );
+      return;
     }
 
     try {
@@ -1474,6 +1536,11 @@ export function ControlPanelsAppComponent({
             }
           }
         }
+        // Hand what was restored to the stores the desktop shell keeps
+        await syncShellStorage([
+          ...Object.keys(backup.localStorage ?? {}),
+          "ryos:files",
+        ]);
         setNextBootMessage(t("common.system.restoringSystem"));
         window.location.reload();
       } catch (err) {
@@ -1883,14 +1950,18 @@ export function ControlPanelsAppComponent({
                   <div className="flex gap-2">
                     <Button
                       variant="retro"
-                      onClick={handleBackup}
+                      onClick={isTauri() ? handleNativeBackup : handleBackup}
                       className="flex-1"
                     >
                       {t("apps.control-panels.backup")}
                     </Button>
                     <Button
                       variant="retro"
-                      onClick={() => fileInputRef.current?.click()}
+                      onClick={() =>
+                        isTauri()
+                          ? handleNativeRestore()
+                          : fileInputRef.current?.click()
+                      }
                       className="flex-1"
                     >
                       {t("apps.control-panels.restore")}
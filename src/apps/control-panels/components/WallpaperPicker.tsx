@@ -15,6 +15,31 @@ import { useDisplaySettingsStore } from "@/stores/useDisplaySettingsStore";
 import { loadWallpaperManifest } from "@/utils/wallpapers";
 import type { WallpaperManifest as WallpaperManifestType } from "@/utils/wallpapers";
 import { useTranslation } from "react-i18next";
+import { blobThumbnailUrl } from "@/utils/thumbnails";
+
+// Longest side (CSS px) of a wallpaper tile in the picker grid
+const PREVIEW_SIZE = 160;
+
+/**
+ * Preview URL for a custom wallpaper: a thumbnail cached by the desktop
+ * shell, or the full image elsewhere. Custom wallpapers never change, so
+ * their thumbnails don't need a version.
+ */
+async function customWallpaperPreview(
+  ref: string,
+  getWallpaperData: (ref: string) => Promise<string | null>
+): Promise<string | null> {
+  const thumbnail = await blobThumbnailUrl(
+    `wallpaper:${ref}`,
+    0,
+    PREVIEW_SIZE,
+    async () => {
+      const data = await getWallpaperData(ref);
+      return data ? (await fetch(data)).blob() : null;
+    }
+  );
+  return thumbnail ?? getWallpaperData(ref);
+}
 
 // Remove unused constants
 interface WallpaperItemProps {
@@ -219,7 +244,7 @@ export function WallpaperPicker({ onSelect }: WallpaperPickerProps) {
         // Load preview data for each reference
         const previews: Record<string, string> = {};
         for (const ref of refs) {
-          const data = await getWallpaperData(ref);
+          const data = await customWallpaperPreview(ref, getWallpaperData);
           if (data) {
             previews[ref] = data;
           }
@@ -265,7 +290,7 @@ export function WallpaperPicker({ onSelect }: WallpaperPickerProps) {
       // Load preview for the new wallpaper
       for (const ref of refs) {
         if (!customWallpaperPreviews[ref]) {
-          const data = await getWallpaperData(ref);
+          const data = await customWallpaperPreview(ref, getWallpaperData);
           if (data) {
             setCustomWallpaperPreviews((prev) => ({
               ...prev,
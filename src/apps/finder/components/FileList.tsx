@@ -25,6 +25,7 @@ export interface FileItem {
   appId?: string; // For application files
   content?: string | Blob; // For document files or images
   contentUrl?: string; // For blob URLs
+  thumbnailUrl?: string; // Cached thumbnail, preferred over contentUrl for display
   size?: number; // File size in bytes
   modifiedAt?: Date; // Last modified date
   type?: string;
@@ -391,6 +392,10 @@ export function FileList({
     return isImageFile(file) || (isMusicFile(file) && !!file.contentUrl);
   };
 
+  // Smallest URL that can stand in for the file's picture
+  const getThumbnailUrl = (file: FileItem) =>
+    file.thumbnailUrl ?? file.contentUrl;
+
   // Helper to resolve icon path (legacy-aware names, works with ThemedIcon)
   const getIconPath = (file: FileItem) => {
     if (file.icon) return file.icon;
@@ -520,9 +525,9 @@ export function FileList({
             >
               {file.icon}
             </span>
-          ) : file.contentUrl && shouldShowThumbnail(file) ? (
+          ) : getThumbnailUrl(file) && shouldShowThumbnail(file) ? (
             <img
-              src={file.contentUrl}
+              src={getThumbnailUrl(file)}
               alt={file.name}
               className="w-4 h-4 object-cover rounded-sm"
               style={{ imageRendering: isImageFile(file) ? "pixelated" : "auto" }}
@@ -607,7 +612,9 @@ export function FileList({
           isDirectory={file.isDirectory}
           icon={file.icon}
           content={isImageFile(file) ? file.content : undefined}
-          contentUrl={shouldShowThumbnail(file) ? file.contentUrl : undefined}
+          contentUrl={
+            shouldShowThumbnail(file) ? getThumbnailUrl(file) : undefined
+          }
           onDoubleClick={() => handleFileOpen(file)}
           onClick={() => handleFileSelect(file)}
           isSelected={selectedFile?.path === file.path}
@@ -30,6 +30,13 @@ import { importAppletFile } from "@/utils/appletImportExport";
 import { useTranslation } from "react-i18next";
 import { useTranslatedHelpItems } from "@/hooks/useTranslatedHelpItems";
 import { getTranslatedFolderNameFromName } from "@/utils/i18n";
+import { useHostDrop } from "@/hooks/useHostDrop";
+import {
+  importDroppedItems,
+  readDroppedFile,
+  type DroppedItem,
+} from "@/utils/hostDrop";
+import { isVolumePath, VOLUMES_PATH } from "@/utils/nativeFs";
 
 // Type for Finder initial data
 interface FinderInitialData {
@@ -105,6 +112,7 @@ export function FinderAppComponent({
   const [isDraggingOver, setIsDraggingOver] = useState(false);
   const pathInputRef = useRef<HTMLInputElement>(null);
   const fileInputRef = useRef<HTMLInputElement>(null);
+  const dropZoneRef = useRef<HTMLDivElement>(null);
   const [storageSpace, setStorageSpace] = useState(calculateStorageSpace());
   const fileStore = useFilesStore();
   const [contextMenuPos, setContextMenuPos] = useState<{
@@ -360,34 +368,64 @@ export function FinderAppComponent({
 
     const file = e.dataTransfer.files[0];
     if (file) {
-      // Only accept text and markdown files
-      if (!file.type.startsWith("text/") && !file.name.endsWith(".md")) {
-        return;
-      }
+      await saveDroppedFile(file);
+    }
+  };
 
-      try {
-        const text = await file.text();
-        const filePath = `/Documents/${file.name}`;
+  // Saves a file dropped from the user's disk into /Documents
+  const saveDroppedFile = async (file: File) => {
+    // Only accept text and markdown files
+    if (!file.type.startsWith("text/") && !file.name.endsWith(".md")) {
+      return;
+    }
+
+    try {
+      const text = await file.text();
+      const filePath = `/Documents/${file.name}`;
 
-        await saveFile({
+      await saveFile({
+        name: file.name,
+        path: filePath,
+        content: text,
+      });
+
+      // Notify file was added
+      const event = new CustomEvent("fileUpdated", {
+        detail: {
           name: file.name,
           path: filePath,
-          content: text,
-        });
+        },
+      });
+      window.dispatchEvent(event);
+    } catch (err) {
+      console.error("Error saving dropped file:", err);
+    }
+  };
 
-        // Notify file was added
-        const event = new CustomEvent("fileUpdated", {
-          detail: {
-            name: file.name,
-            path: filePath,
-          },
-        });
-        window.dispatchEvent(event);
+  // Host files dropped on the desktop app's window: copied into the open
+  // mounted volume, or saved like browser drops in /Documents
+  const canDropHostFiles =
+    currentPath === "/Documents" ||
+    (isVolumePath(currentPath) && currentPath !== VOLUMES_PATH);
+  const handleHostDrop = async (items: DroppedItem[]) => {
+    if (isVolumePath(currentPath)) {
+      try {
+        await importDroppedItems(items, currentPath);
       } catch (err) {
-        console.error("Error saving dropped file:", err);
+        console.error("Error importing dropped files:", err);
+        toast.error(String(err));
       }
+      return;
+    }
+    for (const item of items) {
+      if (item.isDirectory) continue;
+      await saveDroppedFile(await readDroppedFile(item));
     }
   };
+  const isHostDraggingOver = useHostDrop(dropZoneRef, {
+    enabled: canDropHostFiles,
+    onDrop: handleHostDrop,
+  });
 
   // Internal file move handler (between folders in the app)
   const handleFileMoved = (sourceFile: FileItem, targetFolder: FileItem) => {
@@ -1041,8 +1079,10 @@ export function FinderAppComponent({
         menuBar={isXpTheme ? menuBar : undefined}
       >
         <div
+          ref={dropZoneRef}
           className={`flex flex-col h-full w-full relative ${
-            isDraggingOver && currentPath === "/Documents"
+            (isDraggingOver && currentPath === "/Documents") ||
+            isHostDraggingOver
               ? "after:absolute after:inset-0 after:bg-black/20"
               : ""
           }`}
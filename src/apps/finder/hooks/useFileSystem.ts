@@ -18,9 +18,24 @@ import { useAppStore } from "@/stores/useAppStore";
 import { migrateIndexedDBToUUIDs } from "@/utils/indexedDBMigration";
 import { useFinderStore } from "@/stores/useFinderStore";
 import { formatKugouImageUrl } from "@/apps/ipod/constants";
+import {
+  emptyTrash as emptyNativeTrash,
+  isNativeFsAvailable,
+  isVolumePath,
+  listItems as listNativeItems,
+  onFsChanged,
+  restoreItem as restoreNativeItem,
+  trashItem as trashNativeItem,
+} from "@/utils/nativeFs";
+import { blobThumbnailUrl, fileThumbnailUrl } from "@/utils/thumbnails";
 
 // STORES is now imported from @/utils/indexedDB to avoid duplication
 
+// Images the desktop shell can thumbnail, and the longest side (CSS px)
+// they're shown at: Finder icons and the Photo Booth strip
+const THUMBNAIL_EXTENSIONS = /\.(png|jpe?g|gif|webp|bmp)$/i;
+const THUMBNAIL_SIZE = 128;
+
 // Interface for content stored in IndexedDB
 export interface DocumentContent {
   name: string; // Used as the key in IndexedDB
@@ -32,6 +47,7 @@ export interface DocumentContent {
 interface ExtendedDisplayFileItem extends Omit<DisplayFileItem, "content"> {
   content?: string | Blob; // Keep content for passing to apps
   contentUrl?: string;
+  thumbnailUrl?: string; // Shell-cached thumbnail for images (desktop app)
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   data?: any; // Add optional data field for virtual files
   originalPath?: string; // For trash items
@@ -545,6 +561,13 @@ export function useFileSystem(
 
     try {
       let displayFiles: ExtendedDisplayFileItem[] = [];
+      // Images whose thumbnails are filled in after the listing shows
+      const pendingThumbnails: {
+        path: string;
+        key: string;
+        version: number;
+        blob: Blob;
+      }[] = [];
 
       // 1. Handle Virtual Directories
       if (currentPath === "/Applications") {
@@ -722,10 +745,36 @@ export function useFileSystem(
           displayFiles
         ); // Log final result
       }
+      // Host folders mounted by the desktop shell
+      else if (isVolumePath(currentPath) && isNativeFsAvailable()) {
+        const itemsMetadata = await listNativeItems(currentPath);
+        displayFiles = await Promise.all(
+          itemsMetadata.map(async (item) => ({
+            ...item,
+            icon: item.icon || getFileIcon(item),
+            thumbnailUrl:
+              !item.isDirectory && THUMBNAIL_EXTENSIONS.test(item.name)
+                ? (await fileThumbnailUrl(
+                    item.path,
+                    THUMBNAIL_SIZE,
+                    item.modifiedAt
+                  )) ?? undefined
+                : undefined,
+            modifiedAt: item.modifiedAt ? new Date(item.modifiedAt) : undefined,
+          }))
+        );
+      }
       // 2. Handle Trash Directory (Uses fileStore)
       else if (currentPath === "/Trash") {
         // Get metadata from the store
-        const itemsMetadata = fileStore.getItemsInPath(currentPath);
+        const itemsMetadata = [...fileStore.getItemsInPath(currentPath)];
+        // Files from mounted volumes sit in the host trash
+        if (isNativeFsAvailable()) {
+          const nativeItems = await listNativeItems(currentPath);
+          itemsMetadata.push(
+            ...nativeItems.filter((item) => isVolumePath(item.path))
+          );
+        }
         displayFiles = itemsMetadata.map((item) => ({
           ...item,
           icon: getFileIcon(item), // Get icon based on metadata
@@ -763,6 +812,12 @@ export function useFileSystem(
                       `[useFileSystem:loadFiles] Found Blob content for ${item.name}, creating URL`
                     );
                     contentUrl = URL.createObjectURL(contentData.content);
+                    pendingThumbnails.push({
+                      path: item.path,
+                      key: `image:${item.uuid}`,
+                      version: item.modifiedAt ?? 0,
+                      blob: contentData.content,
+                    });
                     console.log(
                       `[useFileSystem:loadFiles] Created URL: ${contentUrl}`
                     );
@@ -846,6 +901,34 @@ export function useFileSystem(
       }
 
       setFiles(displayFiles);
+
+      // The shell may have to render them first, so don't hold up the listing
+      if (pendingThumbnails.length > 0) {
+        void Promise.all(
+          pendingThumbnails.map(async ({ path, key, version, blob }) => {
+            const url = await blobThumbnailUrl(
+              key,
+              version,
+              THUMBNAIL_SIZE,
+              async () => blob
+            );
+            return [path, url] as const;
+          })
+        ).then((results) => {
+          const urls = new Map<string, string>();
+          for (const [path, url] of results) {
+            if (url) urls.set(path, url);
+          }
+          if (urls.size === 0) return;
+          setFiles((files) =>
+            files.map((file) =>
+              urls.has(file.path)
+                ? { ...file, thumbnailUrl: urls.get(file.path) }
+                : file
+            )
+          );
+        });
+      }
     } catch (err) {
       console.error("[useFileSystem] Error loading files:", err);
       setError(err instanceof Error ? err.message : "Failed to load files");
@@ -1147,6 +1230,36 @@ export function useFileSystem(
     }
   }, [loadFiles, options.skipLoad]); // Depend only on the memoized loadFiles
 
+  // Reload mounted volumes when their host folders change
+  useEffect(() => {
+    if (
+      options.skipLoad ||
+      !isVolumePath(currentPath) ||
+      !isNativeFsAvailable()
+    ) {
+      return;
+    }
+    // A change to the folder's children, or to the folder or one of its
+    // ancestors (e.g. renaming or deleting it)
+    const unlisten = onFsChanged((changes) => {
+      const affected = changes.some((change) =>
+        [change.path, change.oldPath].some(
+          (path) =>
+            path !== undefined &&
+            (currentPath === path ||
+              currentPath.startsWith(`${path}/`) ||
+              path.slice(0, path.lastIndexOf("/")) === currentPath)
+        )
+      );
+      if (affected) {
+        loadFiles();
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [currentPath, loadFiles, options.skipLoad]);
+
   // --- handleFileSelect, Navigation Functions --- //
   const handleFileSelect = useCallback(
     (file: ExtendedDisplayFileItem | undefined) => {
@@ -1495,6 +1608,18 @@ export function useFileSystem(
       )
         return;
 
+      // Host files go to the host trash through the desktop shell
+      if (isVolumePath(fileMetadata.path) && isNativeFsAvailable()) {
+        try {
+          await trashNativeItem(fileMetadata.path);
+          await loadFiles();
+        } catch (err) {
+          console.error("Error moving host file to trash:", err);
+          setError(`Failed to move ${fileMetadata.name} to trash`);
+        }
+        return;
+      }
+
       // 1. Mark item as trashed in FileStore
       fileStore.removeItem(fileMetadata.path);
 
@@ -1532,11 +1657,22 @@ export function useFileSystem(
         }
       }
     },
-    [fileStore]
+    [fileStore, loadFiles]
   );
 
   const restoreFromTrash = useCallback(
     async (itemToRestore: ExtendedDisplayFileItem) => {
+      if (isVolumePath(itemToRestore.path) && isNativeFsAvailable()) {
+        try {
+          await restoreNativeItem(itemToRestore.path);
+          await loadFiles();
+        } catch (err) {
+          console.error("Error restoring host file from trash:", err);
+          setError("Cannot restore item.");
+        }
+        return;
+      }
+
       const fileMetadata = fileStore.getItem(itemToRestore.path);
       if (
         !fileMetadata ||
@@ -1588,7 +1724,7 @@ export function useFileSystem(
         }
       }
     },
-    [fileStore]
+    [fileStore, loadFiles]
   );
 
   const emptyTrash = useCallback(async () => {
@@ -1606,7 +1742,18 @@ export function useFileSystem(
       console.error("Error clearing trash content from IndexedDB:", err);
       setError("Failed to empty trash storage.");
     }
-  }, [fileStore]);
+
+    // 3. Delete host files trashed from mounted volumes
+    if (isNativeFsAvailable()) {
+      try {
+        await emptyNativeTrash();
+        await loadFiles();
+      } catch (err) {
+        console.error("Error emptying the host trash:", err);
+        setError("Failed to empty trash storage.");
+      }
+    }
+  }, [fileStore, loadFiles]);
 
   // --- Format File System (Refactored) --- //
   const formatFileSystem = useCallback(async () => {
@@ -44,6 +44,16 @@ import { BACKLIGHT_TIMEOUT_MS, SEEK_AMOUNT_SECONDS, getYouTubeVideoId, formatKug
 import type { WheelArea, RotationDirection } from "../types";
 import { useActivityState } from "@/hooks/useActivityState";
 import { useLyricsErrorToast } from "@/hooks/useLyricsErrorToast";
+import { useHostDrop } from "@/hooks/useHostDrop";
+import { readDroppedFile, type DroppedItem } from "@/utils/hostDrop";
+import { putBlob } from "@/utils/blobStore";
+
+/** Audio dropped from the host, by MIME type, with the extension react-player plays it by */
+const DROPPED_AUDIO_EXTENSIONS: Record<string, string> = {
+  "audio/mpeg": "mp3",
+  "audio/wav": "wav",
+  "audio/mp4": "m4a",
+};
 
 export function IpodAppComponent({
   isWindowOpen,
@@ -1270,6 +1280,32 @@ export function IpodAppComponent({
 
   // Scaling
   const containerRef = useRef<HTMLDivElement>(null);
+
+  // Songs dragged from the host go into the shell's blob store, so they
+  // still play after a restart
+  const handleHostDrop = async (items: DroppedItem[]) => {
+    for (const item of items) {
+      try {
+        const file = await readDroppedFile(item);
+        const { hash, url } = await putBlob(file);
+        // react-player picks its file player by extension
+        const extension = DROPPED_AUDIO_EXTENSIONS[item.mime];
+        useIpodStore.getState().addTrack({
+          id: `blob-${hash}`,
+          url: `${url}#${hash}.${extension}`,
+          title: item.name.replace(/\.[^/.]+$/, ""),
+        });
+        showStatus(t("apps.ipod.status.added"));
+        startTrackSwitch();
+      } catch (err) {
+        console.error("[iPod] Failed to add dropped song:", err);
+      }
+    }
+  };
+  useHostDrop(containerRef, {
+    accept: (item) => item.mime in DROPPED_AUDIO_EXTENSIONS,
+    onDrop: handleHostDrop,
+  });
   const [scale, setScale] = useState(1);
   const prevMinimizedRef = useRef(isMinimized);
 
@@ -21,6 +21,17 @@ import { useAppStore } from "@/stores/useAppStore";
 import { toast } from "sonner";
 import { useThemeStore } from "@/stores/useThemeStore";
 import { useTranslation } from "react-i18next";
+import {
+  isHostFilesAvailable,
+  openHostFile,
+  readHostFile,
+  saveHostFile,
+  saveHostFileAs,
+  type HostFile,
+} from "@/utils/hostFiles";
+import { encodeImageFor } from "../utils/encodeImage";
+import { useHostDrop } from "@/hooks/useHostDrop";
+import { readDroppedFile } from "@/utils/hostDrop";
 
 export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
   isWindowOpen,
@@ -73,6 +84,23 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
     applyFilter: (filter: Filter) => void;
   } | null>(null);
   const { saveFile } = useFileSystem("/Images");
+  // Host file the image was imported from or exported to; saves of that
+  // image are written back to it (`path` is set once it is saved)
+  const hostFileRef = useRef<{ file: HostFile; path: string | null } | null>(
+    null
+  );
+
+  const saveToHostFile = async (filePath: string, png: Blob) => {
+    const hostFile = hostFileRef.current;
+    if (!hostFile) return;
+    if (hostFile.path === null) hostFile.path = filePath;
+    if (hostFile.path !== filePath) return;
+    hostFile.file = await saveHostFile(
+      hostFile.file.handle,
+      await encodeImageFor(png, hostFile.file.name)
+    );
+  };
+
   const launchApp = useLaunchApp();
   const contentChangeTimeoutRef = useRef<number | null>(null);
   const clearInitialData = useAppStore((state) => state.clearInitialData);
@@ -204,6 +232,7 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
     handleClear();
     setLastFilePath(null);
     setHasUnsavedChanges(false);
+    hostFileRef.current = null;
   };
 
   const handleSave = async () => {
@@ -227,6 +256,7 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
           content: blob,
           type: "png",
         });
+        await saveToHostFile(currentFilePath, blob);
 
         setHasUnsavedChanges(false);
         toast.success(t("apps.paint.dialogs.imageSavedSuccessfully"));
@@ -261,6 +291,7 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
         },
       });
       window.dispatchEvent(saveEvent);
+      await saveToHostFile(filePath, blob);
 
       setLastFilePath(filePath);
       setHasUnsavedChanges(false);
@@ -281,9 +312,20 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
 
     try {
       const blob = await canvasRef.current.exportCanvas();
-      const blobUrl = URL.createObjectURL(blob);
       const fileName = currentFilePath?.split("/").pop() || `${t("apps.paint.untitled")}.png`;
 
+      if (isHostFilesAvailable()) {
+        let hostFile = await saveHostFileAs("image", blob, fileName);
+        if (!hostFile) return;
+        const encoded = await encodeImageFor(blob, hostFile.name);
+        if (encoded !== blob) {
+          hostFile = await saveHostFile(hostFile.handle, encoded);
+        }
+        hostFileRef.current = { file: hostFile, path: currentFilePath };
+        return;
+      }
+
+      const blobUrl = URL.createObjectURL(blob);
       const link = document.createElement("a");
       link.download = fileName;
       link.href = blobUrl;
@@ -297,35 +339,65 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
     }
   };
 
+  const importImageFile = (file: File) => {
+    const reader = new FileReader();
+    reader.onload = (e) => {
+      const dataUrl = e.target?.result as string;
+      const img = new Image();
+      img.onload = () => {
+        let newWidth = img.width;
+        let newHeight = img.height;
+        if (newWidth > 589) {
+          const ratio = 589 / newWidth;
+          newWidth = 589;
+          newHeight = Math.round(img.height * ratio);
+        }
+        setCanvasWidth(newWidth);
+        setCanvasHeight(newHeight);
+        setIsLoadingFile(true);
+        canvasRef.current?.importImage(dataUrl);
+        setIsLoadingFile(false);
+        setSaveFileName(file.name);
+        setIsSaveDialogOpen(true);
+      };
+      img.src = dataUrl;
+    };
+    reader.readAsDataURL(file);
+  };
+
   const handleFileSelect = (event: React.ChangeEvent<HTMLInputElement>) => {
     const file = event.target.files?.[0];
     if (file) {
-      const reader = new FileReader();
-      reader.onload = (e) => {
-        const dataUrl = e.target?.result as string;
-        const img = new Image();
-        img.onload = () => {
-          let newWidth = img.width;
-          let newHeight = img.height;
-          if (newWidth > 589) {
-            const ratio = 589 / newWidth;
-            newWidth = 589;
-            newHeight = Math.round(img.height * ratio);
-          }
-          setCanvasWidth(newWidth);
-          setCanvasHeight(newHeight);
-          setIsLoadingFile(true);
-          canvasRef.current?.importImage(dataUrl);
-          setIsLoadingFile(false);
-          setSaveFileName(file.name);
-          setIsSaveDialogOpen(true);
-        };
-        img.src = dataUrl;
-      };
-      reader.readAsDataURL(file);
+      hostFileRef.current = null;
+      importImageFile(file);
     }
   };
 
+  const handleImportFromDevice = async () => {
+    try {
+      const hostFile = await openHostFile("image");
+      if (!hostFile) return;
+      const bytes = await readHostFile(hostFile.handle);
+      importImageFile(new File([bytes as BlobPart], hostFile.name));
+      hostFileRef.current = { file: hostFile, path: null };
+    } catch (err) {
+      console.error("Error importing file:", err);
+    }
+  };
+
+  const containerRef = useRef<HTMLDivElement>(null);
+  useHostDrop(containerRef, {
+    accept: (item) => item.mime.startsWith("image/"),
+    onDrop: async ([item]) => {
+      try {
+        hostFileRef.current = null;
+        importImageFile(await readDroppedFile(item));
+      } catch (err) {
+        console.error("Error importing dropped image:", err);
+      }
+    },
+  });
+
   const handleCut = () => {
     canvasRef.current?.cut();
   };
@@ -434,6 +506,9 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
       onNewFile={handleNewFile}
       onSave={handleSave}
       onImportFile={handleImportFile}
+      onImportFromDevice={
+        isHostFilesAvailable() ? handleImportFromDevice : undefined
+      }
       onExportFile={handleExportFile}
       hasUnsavedChanges={hasUnsavedChanges}
       currentFilePath={currentFilePath}
@@ -466,6 +541,7 @@ export const PaintAppComponent: React.FC<AppProps<PaintInitialData>> = ({
         menuBar={isXpTheme ? menuBar : undefined}
       >
         <div
+          ref={containerRef}
           className="flex flex-col h-full w-full min-h-0 p-2"
           style={{
             backgroundImage: 'url("/patterns/Property 1=7.svg")',
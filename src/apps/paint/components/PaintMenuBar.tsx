@@ -31,6 +31,8 @@ interface PaintMenuBarProps {
   onNewFile: () => void;
   onSave: () => void;
   onImportFile: () => void;
+  /** Replaces the file input, e.g. with a native open dialog */
+  onImportFromDevice?: () => void;
   onExportFile: () => void;
   hasUnsavedChanges: boolean;
   currentFilePath: string | null;
@@ -632,6 +634,7 @@ export function PaintMenuBar({
   onNewFile,
   onSave,
   onImportFile,
+  onImportFromDevice,
   onExportFile,
   currentFilePath,
   handleFileSelect,
@@ -716,7 +719,9 @@ export function PaintMenuBar({
           </MenubarItem>
           <MenubarSeparator className="h-[2px] bg-black my-1" />
           <MenubarItem
-            onClick={() => fileInputRef.current?.click()}
+            onClick={
+              onImportFromDevice ?? (() => fileInputRef.current?.click())
+            }
             className="text-md h-6 px-3"
           >
             {t("apps.paint.menu.importFromDevice")}
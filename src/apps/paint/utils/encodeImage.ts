@@ -0,0 +1,73 @@
+/** 24-bit BMP of `image`, bottom-up as the format expects */
+function encodeBmp(image: ImageData): Blob {
+  const { width, height, data } = image;
+  const rowSize = Math.ceil((width * 3) / 4) * 4;
+  const pixelBytes = rowSize * height;
+  const buffer = new ArrayBuffer(54 + pixelBytes);
+  const view = new DataView(buffer);
+
+  // BITMAPFILEHEADER
+  view.setUint8(0, 0x42);
+  view.setUint8(1, 0x4d);
+  view.setUint32(2, buffer.byteLength, true);
+  view.setUint32(10, 54, true);
+  // BITMAPINFOHEADER
+  view.setUint32(14, 40, true);
+  view.setInt32(18, width, true);
+  view.setInt32(22, height, true);
+  view.setUint16(26, 1, true);
+  view.setUint16(28, 24, true);
+  view.setUint32(34, pixelBytes, true);
+  view.setInt32(38, 2835, true);
+  view.setInt32(42, 2835, true);
+
+  const pixels = new Uint8Array(buffer, 54);
+  for (let y = 0; y < height; y++) {
+    const row = (height - 1 - y) * rowSize;
+    for (let x = 0; x < width; x++) {
+      const source = (y * width + x) * 4;
+      const target = row + x * 3;
+      pixels[target] = data[source + 2];
+      pixels[target + 1] = data[source + 1];
+      pixels[target + 2] = data[source];
+    }
+  }
+  return new Blob([buffer], { type: "image/bmp" });
+}
+
+/**
+ * Re-encodes the canvas's PNG export for `fileName`'s extension: JPEG for
+ * .jpg/.jpeg and BMP for .bmp, flattened onto white as neither has alpha.
+ */
+export async function encodeImageFor(
+  png: Blob,
+  fileName: string
+): Promise<Blob> {
+  const extension = fileName.split(".").pop()?.toLowerCase();
+  if (extension !== "jpg" && extension !== "jpeg" && extension !== "bmp") {
+    return png;
+  }
+
+  const bitmap = await createImageBitmap(png);
+  const canvas = document.createElement("canvas");
+  canvas.width = bitmap.width;
+  canvas.height = bitmap.height;
+  const ctx = canvas.getContext("2d");
+  if (!ctx) throw new Error("Canvas is not available");
+  ctx.fillStyle = "#ffffff";
+  ctx.fillRect(0, 0, canvas.width, canvas.height);
+  ctx.drawImage(bitmap, 0, 0);
+  bitmap.close();
+
+  if (extension === "bmp") {
+    return encodeBmp(ctx.getImageData(0, 0, canvas.width, canvas.height));
+  }
+  return new Promise((resolve, reject) =>
+    canvas.toBlob(
+      (blob) =>
+        blob ? resolve(blob) : reject(new Error("Failed to encode JPEG")),
+      "image/jpeg",
+      0.92
+    )
+  );
+}
@@ -5,7 +5,7 @@ import { PcMenuBar } from "./PcMenuBar";
 import { HelpDialog } from "@/components/dialogs/HelpDialog";
 import { AboutDialog } from "@/components/dialogs/AboutDialog";
 import { ConfirmDialog } from "@/components/dialogs/ConfirmDialog";
-import { helpItems, appMetadata } from "..";
+import { helpItems, appMetadata, type PcInitialData } from "..";
 import { getTranslatedAppName } from "@/utils/i18n";
 import { Game, loadGames } from "@/stores/usePcStore";
 import { motion } from "framer-motion";
@@ -14,6 +14,7 @@ import { useThemeStore } from "@/stores/useThemeStore";
 import { useTranslation } from "react-i18next";
 import { useTranslatedHelpItems } from "@/hooks/useTranslatedHelpItems";
 import { ActivityIndicator } from "@/components/ui/activity-indicator";
+import { useAppStore } from "@/stores/useAppStore";
 
 export function PcAppComponent({
   isWindowOpen,
@@ -23,7 +24,8 @@ export function PcAppComponent({
   instanceId,
   onNavigateNext,
   onNavigatePrevious,
-}: AppProps) {
+  initialData,
+}: AppProps<PcInitialData>) {
   const [isHelpDialogOpen, setIsHelpDialogOpen] = useState(false);
   const [isAboutDialogOpen, setIsAboutDialogOpen] = useState(false);
   const [isResetDialogOpen, setIsResetDialogOpen] = useState(false);
@@ -97,6 +99,15 @@ export function PcAppComponent({
     }
   }, [isScriptLoaded, pendingGame]);
 
+  // Start a game handed over at launch (e.g. a .jsdos file opened from the
+  // host), then drop it so it isn't restarted on the next render
+  useEffect(() => {
+    const game = initialData?.game;
+    if (!game || !instanceId) return;
+    handleLoadGame(game);
+    useAppStore.getState().clearInstanceInitialData(instanceId);
+  }, [initialData, instanceId]);
+
   // Listen for App Menu fullscreen toggle
   useEffect(() => {
     const handleAppMenuFullScreen = (e: CustomEvent<{ appId: string; instanceId: string }>) => {
@@ -1,6 +1,12 @@
 import { BaseApp } from "../base/types";
 import { PcAppComponent } from "./components/PcAppComponent";
 import { githubRepo } from "@/config/branding";
+import type { Game } from "@/stores/usePcStore";
+
+export interface PcInitialData {
+  /** A game to start right away, e.g. a .jsdos bundle opened from the host */
+  game?: Game;
+}
 
 export const appMetadata = {
   name: "Virtual PC",
@@ -1163,7 +1163,10 @@ export function PhotoBoothComponent({
                             }}
                           >
                             <img
-                              src={matchingFile.contentUrl}
+                              src={
+                                matchingFile.thumbnailUrl ??
+                                matchingFile.contentUrl
+                              }
                               alt={t("apps.photo-booth.ariaLabels.photo", {
                                 index: originalIndex,
                               })}
@@ -4,7 +4,7 @@ import { BoardList } from "./BoardList";
 import { SoundGrid } from "./SoundGrid";
 import { useSoundboard } from "@/hooks/useSoundboard";
 import { useAudioRecorder } from "@/hooks/useAudioRecorder";
-import { DialogState, Soundboard } from "@/types/types";
+import { DialogState, Soundboard, SoundSlot } from "@/types/types";
 import { EmojiDialog } from "@/components/dialogs/EmojiDialog";
 import { InputDialog } from "@/components/dialogs/InputDialog";
 import { HelpDialog } from "@/components/dialogs/HelpDialog";
@@ -17,6 +17,8 @@ import { useSoundboardStore } from "@/stores/useSoundboardStore";
 import { useThemeStore } from "@/stores/useThemeStore";
 import { getTranslatedAppName } from "@/utils/i18n";
 import { useTranslation } from "react-i18next";
+import { useHostDrop } from "@/hooks/useHostDrop";
+import { readDroppedFile, type DroppedItem } from "@/utils/hostDrop";
 
 interface ImportedSlot {
   audioData: string | null;
@@ -25,6 +27,25 @@ interface ImportedSlot {
   title?: string;
 }
 
+/** Slot formats for the dropped audio types the slots can play */
+const DROPPED_AUDIO_FORMATS: Record<string, SoundSlot["audioFormat"]> = {
+  "audio/wav": "wav",
+  "audio/mpeg": "mpeg",
+  "audio/mp4": "mp4",
+  "video/webm": "webm",
+};
+
+/** Slots are kept in localStorage, so long recordings don't fit */
+const MAX_DROPPED_AUDIO_SIZE = 2 * 1024 * 1024;
+
+const readAsBase64 = (file: File): Promise<string> =>
+  new Promise((resolve, reject) => {
+    const reader = new FileReader();
+    reader.onload = () => resolve((reader.result as string).split(",")[1]);
+    reader.onerror = () => reject(reader.error);
+    reader.readAsDataURL(file);
+  });
+
 interface ImportedBoard {
   id?: string;
   name: string;
@@ -388,6 +409,37 @@ export function SoundboardAppComponent({
     };
   }, [activeBoard, playbackStates, playSound, stopSound, isForeground]);
 
+  // Audio files dropped from the host fill the empty slots in order
+  const containerRef = useRef<HTMLDivElement>(null);
+  const handleHostDrop = async (items: DroppedItem[]) => {
+    if (!activeBoard) return;
+    const emptySlots = activeBoard.slots
+      .map((slot, index) => (slot.audioData ? -1 : index))
+      .filter((index) => index !== -1);
+    for (const item of items) {
+      const slotIndex = emptySlots.shift();
+      if (slotIndex === undefined) break;
+      if (item.size > MAX_DROPPED_AUDIO_SIZE) {
+        console.warn(`[Soundboard] ${item.name} is too large for a slot`);
+        continue;
+      }
+      try {
+        const file = await readDroppedFile(item);
+        updateSlot(slotIndex, {
+          audioData: await readAsBase64(file),
+          audioFormat: DROPPED_AUDIO_FORMATS[item.mime],
+          title: item.name.replace(/\.[^/.]+$/, ""),
+        });
+      } catch (err) {
+        console.error("[Soundboard] Failed to import dropped audio:", err);
+      }
+    }
+  };
+  useHostDrop(containerRef, {
+    accept: (item) => item.mime in DROPPED_AUDIO_FORMATS,
+    onDrop: handleHostDrop,
+  });
+
   if (!hasInitialized || !activeBoard || !activeBoardId) {
     return (
       <WindowFrame
@@ -431,6 +483,7 @@ export function SoundboardAppComponent({
         }}
       >
         <div
+          ref={containerRef}
           className={`h-full w-full flex flex-col md:flex-row ${
             isXpTheme ? "border-t border-[#919b9c]" : ""
           }`}
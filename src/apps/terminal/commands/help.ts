@@ -18,6 +18,7 @@ navigation & files
   mkdir <dir>      ${t("apps.terminal.commands.mkdir")}
   rm <file>        ${t("apps.terminal.commands.rm")}
   open <target>    ${t("apps.terminal.commands.open")}
+  search <words>   ${t("apps.terminal.commands.search")}
   edit <file>      ${t("apps.terminal.commands.edit")}
   vim <file>       ${t("apps.terminal.commands.vim")}
 
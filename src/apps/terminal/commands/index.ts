@@ -17,6 +17,7 @@ import { aboutCommand } from "./about";
 import { aiCommand, chatCommand, ryoCommand } from "./ai";
 import { vimCommand } from "./vim";
 import { openCommand } from "./open";
+import { searchCommand } from "./search";
 
 // Create command registry
 export const commands: Record<string, Command> = {
@@ -38,6 +39,7 @@ export const commands: Record<string, Command> = {
   ryo: ryoCommand,
   vim: vimCommand,
   open: openCommand,
+  search: searchCommand,
 };
 
 // Export list of available command names for autocompletion
@@ -0,0 +1,39 @@
+import { Command, CommandContext, CommandResult } from "../types";
+import { isTauri } from "@/utils/platform";
+import { searchFiles } from "@/utils/searchIndex";
+import i18n from "@/lib/i18n";
+
+export const searchCommand: Command = {
+  name: "search",
+  description: "apps.terminal.commands.search",
+  usage: "search <words>",
+  handler: async (
+    args: string[],
+    context: CommandContext
+  ): Promise<CommandResult> => {
+    if (args.length === 0) {
+      return { output: "usage: search <words>", isError: true };
+    }
+    if (!isTauri()) {
+      return {
+        output: i18n.t("apps.terminal.output.searchDesktopOnly"),
+        isError: true,
+      };
+    }
+
+    // Like grep -r, search from the current directory down
+    const scope = context.currentPath === "/" ? undefined : context.currentPath;
+    const hits = await searchFiles(args.join(" "), { scope });
+    if (hits.length === 0) {
+      return {
+        output: i18n.t("apps.terminal.output.noFilesFound"),
+        isError: false,
+        isSystemMessage: true,
+      };
+    }
+    return {
+      output: hits.map((hit) => `${hit.path}\n  ${hit.snippet}`).join("\n"),
+      isError: false,
+    };
+  },
+};
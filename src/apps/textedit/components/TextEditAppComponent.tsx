@@ -20,6 +20,7 @@ import { useThemeStore } from "@/stores/useThemeStore";
 import { useLaunchApp } from "@/hooks/useLaunchApp";
 import { markdownToHtml } from "@/utils/markdown";
 import { useTranslation } from "react-i18next";
+import { isHostFilesAvailable } from "@/utils/hostFiles";
 
 // Inner component that has access to editor context
 function TextEditContent({
@@ -66,6 +67,8 @@ function TextEditContent({
     handleSave,
     handleSaveAs,
     handleImportFile,
+    handleOpenHostFile,
+    handleImportFromHost,
     handleExportFile,
     handleLoadFromPath,
     handleLoadFromDatabase,
@@ -145,7 +148,22 @@ function TextEditContent({
     const loadContent = async () => {
       // Prioritize initialData passed from launch event
       const typedInitialData = initialData as TextEditInitialData;
-      if (typedInitialData?.path && typedInitialData?.content !== undefined) {
+      if (typedInitialData?.hostFile) {
+        console.log(
+          "[TextEdit] Opening host file from initialData:",
+          typedInitialData.hostFile.path
+        );
+        clearInstanceInitialData(instanceId);
+        try {
+          await handleOpenHostFile(typedInitialData.hostFile);
+        } catch (error) {
+          console.error("[TextEdit] Failed to open host file:", error);
+        }
+        return;
+      } else if (
+        typedInitialData?.path &&
+        typedInitialData?.content !== undefined
+      ) {
         console.log(
           "[TextEdit] Loading content from initialData:",
           typedInitialData.path
@@ -180,6 +198,7 @@ function TextEditContent({
     instanceId,
     handleLoadFromPath,
     handleLoadFromDatabase,
+    handleOpenHostFile,
     clearInstanceInitialData,
   ]);
 
@@ -379,6 +398,14 @@ function TextEditContent({
     }
   };
 
+  const handleImportFromDevice = async () => {
+    try {
+      await handleImportFromHost();
+    } catch (error) {
+      console.error("Import failed:", error);
+    }
+  };
+
   const handleImportFileClick = () => {
     launchApp("finder", { initialPath: "/Documents" });
   };
@@ -455,6 +482,9 @@ function TextEditContent({
       onShowAbout={() => dialogControls?.openAboutDialog()}
       onNewFile={handleNewFile}
       onImportFile={handleImportFileClick}
+      onImportFromDevice={
+        isHostFilesAvailable() ? handleImportFromDevice : undefined
+      }
       onExportFile={handleExportFile}
       onSave={handleSaveClick}
       hasUnsavedChanges={hasUnsavedChanges}
@@ -26,6 +26,8 @@ interface TextEditMenuBarProps {
   isWindowOpen: boolean;
   onNewFile: () => void;
   onImportFile: () => void;
+  /** Replaces the file input, e.g. with a native open dialog */
+  onImportFromDevice?: () => void;
   onExportFile: (format: "html" | "md" | "txt") => void;
   onSave: () => void;
   hasUnsavedChanges: boolean;
@@ -40,6 +42,7 @@ export function TextEditMenuBar({
   onShowAbout,
   onNewFile,
   onImportFile,
+  onImportFromDevice,
   onExportFile,
   onSave,
   currentFilePath,
@@ -90,7 +93,9 @@ export function TextEditMenuBar({
           </MenubarItem>
           <MenubarSeparator className="h-[2px] bg-black my-1" />
           <MenubarItem
-            onClick={() => fileInputRef.current?.click()}
+            onClick={
+              onImportFromDevice ?? (() => fileInputRef.current?.click())
+            }
             className="text-md h-6 px-3"
           >
             {t("apps.textedit.menu.importFromDevice")}
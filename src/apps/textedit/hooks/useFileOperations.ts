@@ -1,4 +1,4 @@
-import { useCallback } from "react";
+import { useCallback, useRef } from "react";
 import { Editor } from "@tiptap/core";
 import { useFileSystem } from "@/apps/finder/hooks/useFileSystem";
 import {
@@ -10,12 +10,40 @@ import {
   removeFileExtension,
   getContentAsString,
   generateSuggestedFilename,
+  plainTextToRtf,
+  rtfToPlainText,
 } from "../utils/textEditUtils";
 import {
   dbOperations,
   DocumentContent,
 } from "@/apps/finder/hooks/useFileSystem";
 import { STORES } from "@/utils/indexedDB";
+import {
+  isHostFilesAvailable,
+  openHostFile,
+  readHostFileText,
+  saveHostFile,
+  saveHostFileAs,
+  type HostFile,
+} from "@/utils/hostFiles";
+
+/** The editor's HTML in the format `fileName`'s extension calls for */
+const formatForFile = (html: string, fileName: string): string => {
+  const extension = fileName.split(".").pop()?.toLowerCase();
+  switch (extension) {
+    case "md":
+      return htmlToMarkdown(html);
+    case "txt":
+      return htmlToPlainText(html);
+    case "rtf":
+      return plainTextToRtf(htmlToPlainText(html));
+    default:
+      return html;
+  }
+};
+
+const escapeHtml = (text: string): string =>
+  text.replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;");
 
 interface UseFileOperationsProps {
   editor: Editor | null;
@@ -33,6 +61,23 @@ export function useFileOperations({
   onLoadSuccess,
 }: UseFileOperationsProps) {
   const { saveFile } = useFileSystem("/Documents");
+  // Host file the open document was imported from or exported to, which
+  // saves are written back to while that document stays open
+  const hostFileRef = useRef<{ file: HostFile; path: string | null } | null>(
+    null
+  );
+
+  const saveToHostFile = useCallback(
+    async (filePath: string, html: string): Promise<void> => {
+      const hostFile = hostFileRef.current;
+      if (!hostFile || hostFile.path !== filePath) return;
+      hostFile.file = await saveHostFile(
+        hostFile.file.handle,
+        formatForFile(html, hostFile.file.name)
+      );
+    },
+    []
+  );
 
   const handleSave = useCallback(async (): Promise<void> => {
     if (!editor) return;
@@ -50,6 +95,7 @@ export function useFileOperations({
         path: currentFilePath,
         content: markdownContent,
       });
+      await saveToHostFile(currentFilePath, htmlContent);
 
       onSaveSuccess?.(currentFilePath);
       console.log("[TextEdit] File saved successfully:", currentFilePath);
@@ -57,7 +103,7 @@ export function useFileOperations({
       console.error("[TextEdit] Failed to save file:", error);
       throw error;
     }
-  }, [editor, currentFilePath, saveFile, onSaveSuccess]);
+  }, [editor, currentFilePath, saveFile, saveToHostFile, onSaveSuccess]);
 
   const handleSaveAs = useCallback(
     async (fileName: string): Promise<string> => {
@@ -101,6 +147,11 @@ export function useFileOperations({
         editorContent = text;
       } else if (file.name.endsWith(".md")) {
         editorContent = markdownToHtml(text);
+      } else if (file.name.endsWith(".rtf")) {
+        editorContent = rtfToPlainText(text)
+          .split("\n")
+          .map((line) => `<p>${escapeHtml(line)}</p>`)
+          .join("");
       } else {
         editorContent = `<p>${text}</p>`;
       }
@@ -130,8 +181,30 @@ export function useFileOperations({
     [editor, saveFile, onLoadSuccess]
   );
 
+  /**
+   * Import a host file like a dropped file; later saves also go back to
+   * the host file
+   */
+  const handleOpenHostFile = useCallback(
+    async (hostFile: HostFile): Promise<string> => {
+      const text = await readHostFileText(hostFile.handle);
+      const filePath = await handleImportFile(new File([text], hostFile.name));
+      hostFileRef.current = { file: hostFile, path: filePath };
+      return filePath;
+    },
+    [handleImportFile]
+  );
+
+  /** Pick a host file in a native dialog and open it */
+  const handleImportFromHost = useCallback(async (): Promise<
+    string | null
+  > => {
+    const hostFile = await openHostFile("text");
+    return hostFile ? handleOpenHostFile(hostFile) : null;
+  }, [handleOpenHostFile]);
+
   const handleExportFile = useCallback(
-    (format: "html" | "md" | "txt") => {
+    async (format: "html" | "md" | "txt") => {
       if (!editor) return;
 
       const html = editor.getHTML();
@@ -162,6 +235,22 @@ export function useFileOperations({
         ? removeFileExtension(currentFilePath.split("/").pop() || "")
         : "Untitled";
 
+      if (isHostFilesAvailable()) {
+        try {
+          const hostFile = await saveHostFileAs(
+            "text",
+            content,
+            `${filename}.${extension}`
+          );
+          if (hostFile) {
+            hostFileRef.current = { file: hostFile, path: currentFilePath };
+          }
+        } catch (error) {
+          console.error("[TextEdit] Failed to export file:", error);
+        }
+        return;
+      }
+
       const blob = new Blob([content], { type: mimeType });
       const url = URL.createObjectURL(blob);
       const a = document.createElement("a");
@@ -256,6 +345,8 @@ export function useFileOperations({
     handleSave,
     handleSaveAs,
     handleImportFile,
+    handleOpenHostFile,
+    handleImportFromHost,
     handleExportFile,
     handleLoadFromPath,
     handleLoadFromDatabase,
@@ -1,4 +1,5 @@
 import { Editor } from "@tiptap/core";
+import type { HostFile } from "@/utils/hostFiles";
 
 export const removeFileExtension = (filename: string): string => {
   return filename.replace(/\.[^/.]+$/, "");
@@ -57,4 +58,65 @@ export const generateSuggestedFilename = (
 export interface TextEditInitialData {
   path?: string;
   content?: string;
-}
\ No newline at end of file
+  /** A host file to open, which saves are written back to */
+  hostFile?: HostFile;
+}
+/** Minimal RTF document holding `text`, one paragraph per line */
+export const plainTextToRtf = (text: string): string => {
+  const body = text
+    .replace(/[\\{}]/g, (char) => `\\${char}`)
+    .replace(/[\u0080-\uffff]/g, (char) => {
+      const code = char.charCodeAt(0);
+      return `\\u${code > 32767 ? code - 65536 : code}?`;
+    })
+    .split("\n")
+    .join("\\par\n");
+  return `{\\rtf1\\ansi\\deff0\n${body}\n}`;
+};
+
+/** Destinations whose text isn't part of the document */
+const RTF_SKIPPED_GROUPS = ["fonttbl", "colortbl", "stylesheet", "info", "pict"];
+
+/** The text of an RTF document, dropping control words and styling */
+export const rtfToPlainText = (rtf: string): string => {
+  let text = "";
+  // Depth of the group being skipped, or -1 when reading text
+  let skipUntil = -1;
+  let depth = 0;
+  // `\uN` is followed by a fallback character for older readers
+  let skipFallback = false;
+  const token =
+    /\\([a-z]+)(-?\d+)? ?|\\'([0-9a-f]{2})|\\([^a-z])|([{}])|[\r\n]+|([^\\{}\r\n]+)/gi;
+  for (const match of rtf.matchAll(token)) {
+    const [, word, param, hex, symbol, brace, chars] = match;
+    if (brace === "{") {
+      depth++;
+      continue;
+    }
+    if (brace === "}") {
+      if (depth === skipUntil) skipUntil = -1;
+      depth--;
+      continue;
+    }
+    if (symbol === "*" || (word && RTF_SKIPPED_GROUPS.includes(word))) {
+      if (skipUntil === -1) skipUntil = depth;
+      continue;
+    }
+    if (skipUntil !== -1) continue;
+
+    const fallback = skipFallback;
+    skipFallback = false;
+    if (chars) text += fallback ? chars.slice(1) : chars;
+    else if (hex) text += fallback ? "" : String.fromCharCode(parseInt(hex, 16));
+    else if (symbol === "~") text += "\u00a0";
+    else if (symbol === "\\" || symbol === "{" || symbol === "}") text += symbol;
+    else if (word === "par" || word === "line") text += "\n";
+    else if (word === "tab") text += "\t";
+    else if (word === "u" && param) {
+      const code = Number(param);
+      text += String.fromCharCode(code < 0 ? code + 65536 : code);
+      skipFallback = true;
+    }
+  }
+  return text.trim();
+};
@@ -7,12 +7,14 @@ export const productName = "syaOS";
 
 /**
  * Base URL of the deployed app (no trailing slash).
- * In browser/Tauri: uses window.location.origin at runtime.
+ * In Tauri: uses the remote URL reported by the shell, since the bundled
+ * frontend is served from a local origin.
+ * In browser: uses window.location.origin at runtime.
  * At build/SSR: uses VITE_APP_BASE_URL or default.
  */
 export function getBaseUrl(): string {
   if (typeof window !== "undefined") {
-    return window.location.origin;
+    return window.__SYAOS_SHELL__?.remoteUrl || window.location.origin;
   }
   return import.meta.env.VITE_APP_BASE_URL || "https://sya-os.vercel.app";
 }
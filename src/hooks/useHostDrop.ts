@@ -0,0 +1,67 @@
+import { useEffect, useRef, useState } from "react";
+import {
+  isHostDropAvailable,
+  onHostDragLeave,
+  onHostDragOver,
+  onHostDrop,
+  type DroppedItem,
+  type DropPosition,
+  type HostDrop,
+} from "@/utils/hostDrop";
+
+/** Whether `position` is over `element` and not covered by anything else */
+const isOver = (element: HTMLElement | null, position: DropPosition) => {
+  if (!element) return false;
+  const target = document.elementFromPoint(position.x, position.y);
+  return target !== null && element.contains(target);
+};
+
+/**
+ * Receive host files dropped on `ref`'s element in the desktop app.
+ * `onDrop` gets the items `accept` lets through (all by default).
+ * Returns whether a drag is currently over the element.
+ *
+ * @example
+ * const isDraggingOver = useHostDrop(containerRef, {
+ *   accept: (item) => item.mime.startsWith("image/"),
+ *   onDrop: (items) => items.forEach(openImage),
+ * });
+ */
+export function useHostDrop(
+  ref: React.RefObject<HTMLElement | null>,
+  options: {
+    accept?: (item: DroppedItem) => boolean;
+    onDrop: (items: DroppedItem[], drop: HostDrop) => void;
+    enabled?: boolean;
+  }
+): boolean {
+  const [isDraggingOver, setIsDraggingOver] = useState(false);
+  const savedOptions = useRef(options);
+  savedOptions.current = options;
+  const enabled = options.enabled ?? true;
+
+  useEffect(() => {
+    if (!enabled || !isHostDropAvailable()) return;
+
+    const unlisteners = [
+      onHostDragOver((position) =>
+        setIsDraggingOver(isOver(ref.current, position))
+      ),
+      onHostDragLeave(() => setIsDraggingOver(false)),
+      onHostDrop((drop) => {
+        setIsDraggingOver(false);
+        if (!isOver(ref.current, drop.position)) return;
+        const { accept, onDrop } = savedOptions.current;
+        const items = accept ? drop.items.filter(accept) : drop.items;
+        if (items.length > 0) onDrop(items, drop);
+      }),
+    ];
+    return () => {
+      for (const unlisten of unlisteners) {
+        unlisten.then((stop) => stop());
+      }
+    };
+  }, [enabled, ref]);
+
+  return isDraggingOver;
+}
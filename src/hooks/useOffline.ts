@@ -1,4 +1,5 @@
 import { useSyncExternalStore } from "react";
+import { isShellOfflineFallback } from "@/utils/offline";
 
 /**
  * Hook that detects online/offline status using useSyncExternalStore.
@@ -19,6 +20,9 @@ let pollIntervalId: ReturnType<typeof setInterval> | null = null;
  * Get the current online/offline state from the browser
  */
 function getSnapshot(): boolean {
+  if (isShellOfflineFallback()) {
+    return true;
+  }
   if (typeof navigator === "undefined" || !("onLine" in navigator)) {
     return false; // Assume online if navigator.onLine not available
   }
@@ -7,6 +7,7 @@ import { AppManagerState, AppState } from "@/apps/base/types";
 import { AIModel } from "@/types/aiModels";
 import { track } from "@vercel/analytics";
 import { APP_ANALYTICS } from "@/utils/analytics";
+import { isAppAllowedInKiosk } from "@/utils/shell";
 export type { AIModel } from "@/types/aiModels";
 
 // ---------------- Types ---------------------------------------------------------
@@ -735,6 +736,10 @@ const createUseAppStore = () =>
         });
       },
       launchApp: (appId, initialData, title, multiWindow = false) => {
+        if (!isAppAllowedInKiosk(appId)) {
+          console.warn(`[AppStore] ${appId} is not allowed in kiosk mode`);
+          return "";
+        }
         const state = get();
         
         // Check if all instances of this app are minimized
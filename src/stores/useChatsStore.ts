@@ -9,6 +9,7 @@ import { track } from "@vercel/analytics";
 import { APP_ANALYTICS } from "@/utils/analytics";
 import i18n from "@/lib/i18n";
 import { getApiUrl } from "@/utils/platform";
+import { persistStorage } from "@/utils/shellStorage";
 
 // Recovery mechanism - uses different prefix to avoid reset
 const USERNAME_RECOVERY_KEY = "_usr_recovery_key_";
@@ -1608,7 +1609,7 @@ export const useChatsStore = create<ChatsStoreState>()(
     {
       name: STORE_NAME,
       version: STORE_VERSION,
-      storage: createJSONStorage(() => localStorage), // Use localStorage
+      storage: createJSONStorage(() => persistStorage),
       partialize: (state) => ({
         // Select properties to persist
         aiMessages: state.aiMessages,
@@ -4,6 +4,7 @@ import { v4 as uuidv4 } from "uuid";
 import { ensureIndexedDBInitialized, STORES } from "@/utils/indexedDB";
 import type { OsThemeId } from "@/themes/types";
 import { appRegistry } from "@/config/appRegistry";
+import { persistStorage } from "@/utils/shellStorage";
 
 // Define the structure for a file system item (metadata)
 export interface FileSystemItem {
@@ -1181,7 +1182,7 @@ export const useFilesStore = create<FilesStoreState>()(
     {
       name: STORE_NAME,
       version: STORE_VERSION,
-      storage: createJSONStorage(() => localStorage),
+      storage: createJSONStorage(() => persistStorage),
       partialize: (state) => ({
         items: state.items, // Persist the entire file structure
         libraryState: state.libraryState,
@@ -1,6 +1,7 @@
 import { create } from "zustand";
-import { persist } from "zustand/middleware";
+import { persist, createJSONStorage } from "zustand/middleware";
 import { getBaseUrl } from "@/config/branding";
+import { persistStorage } from "@/utils/shellStorage";
 
 // Define types
 export interface Favorite {
@@ -876,6 +877,7 @@ export const useInternetExplorerStore = create<InternetExplorerStore>()(
     {
       name: "ryos:internet-explorer",
       version: CURRENT_IE_STORE_VERSION,
+      storage: createJSONStorage(() => persistStorage),
       partialize: (state) => ({
         url: state.url,
         year: state.year,
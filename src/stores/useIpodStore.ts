@@ -1,5 +1,5 @@
 import { create } from "zustand";
-import { persist } from "zustand/middleware";
+import { persist, createJSONStorage } from "zustand/middleware";
 import { LyricsAlignment, KoreanDisplay, JapaneseFurigana, LyricsFont, RomanizationSettings } from "@/types/lyrics";
 import { LyricLine } from "@/types/lyrics";
 import type { FuriganaSegment } from "@/utils/romanization";
@@ -8,6 +8,7 @@ import { getCachedSongMetadata, listAllCachedSongMetadata } from "@/utils/songMe
 import i18n from "@/lib/i18n";
 import { useChatsStore } from "./useChatsStore";
 import { getBaseUrl } from "@/config/branding";
+import { persistStorage } from "@/utils/shellStorage";
 
 /** Special value for lyricsTranslationLanguage that means "use syaOS locale" */
 export const LYRICS_TRANSLATION_AUTO = "auto";
@@ -1308,8 +1309,9 @@ export const useIpodStore = create<IpodState>()(
       },
     }),
     {
-      name: "ryos:ipod", // Unique name for localStorage persistence
+      name: "ryos:ipod", // Unique name for persistence
       version: CURRENT_IPOD_STORE_VERSION, // Set the current version
+      storage: createJSONStorage(() => persistStorage),
       partialize: (state) => ({
         tracks: state.tracks,
         currentSongId: state.currentSongId,
@@ -0,0 +1,21 @@
+/**
+ * Launch info injected by the desktop shell before the app loads
+ * (see src-tauri/src/launch.rs).
+ */
+export interface ShellLaunchInfo {
+  launchMode: "bundled" | "remote";
+  remoteUrl: string;
+  /** True when the remote origin was unreachable at launch and the shell fell back to the bundled app */
+  offlineFallback?: boolean;
+  /** Present when the shell runs in kiosk mode (see src-tauri/src/kiosk.rs) */
+  kiosk?: {
+    /** App ids that may be opened; empty allows every app */
+    allowedApps: string[];
+  };
+}
+
+declare global {
+  interface Window {
+    __SYAOS_SHELL__?: ShellLaunchInfo;
+  }
+}
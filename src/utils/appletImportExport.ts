@@ -319,3 +319,75 @@ export function exportAppletAsHtml(
   });
 }
 
+
+/**
+ * An applet package decoded by the desktop shell (see src-tauri/src/applet.rs)
+ */
+interface HostAppletPackage {
+  applet: ImportedAppletData;
+  format: "app" | "html";
+  compressed: boolean;
+  warnings: string[];
+}
+
+/**
+ * Import an applet package through the desktop shell: `path` when the OS
+ * opened it with syaOS, otherwise one the user picks in a native dialog.
+ * Resolves to `null` if the dialog is cancelled.
+ */
+export async function importAppletFromHost(
+  path?: string,
+  username?: string
+): Promise<ImportedAppletData | null> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  const result = await invoke<HostAppletPackage | null>("applet_import", {
+    path,
+  });
+  if (!result) return null;
+  result.warnings.forEach((warning) =>
+    console.warn(`[Applet] ${result.applet.name}: ${warning}`)
+  );
+  return {
+    ...result.applet,
+    createdBy: result.applet.createdBy || username,
+  };
+}
+
+/**
+ * Export an applet to a host file chosen in a native save dialog, as a
+ * gzipped `.app` package or HTML with metadata comments. Resolves to the
+ * saved path, or `null` if the dialog is cancelled.
+ */
+export async function exportAppletToHost(
+  htmlContent: string,
+  appletPath: string | null,
+  format: "app" | "html"
+): Promise<string | null> {
+  const baseFilename =
+    appletPath
+      ?.split("/")
+      .pop()
+      ?.replace(/\.(html|app)$/i, "") || "Untitled";
+  const currentFile = appletPath
+    ? useFilesStore.getState().getItem(appletPath)
+    : null;
+  // The package stores whole pixels
+  const round = (value?: number) =>
+    value === undefined ? undefined : Math.round(value);
+
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<string | null>("applet_export", {
+    applet: {
+      name: currentFile?.name || baseFilename,
+      content: htmlContent,
+      icon: currentFile?.icon,
+      shareId: currentFile?.shareId,
+      createdBy: currentFile?.createdBy,
+      windowWidth: round(currentFile?.windowWidth),
+      windowHeight: round(currentFile?.windowHeight),
+      createdAt: currentFile?.createdAt,
+      modifiedAt: currentFile?.modifiedAt,
+    },
+    format,
+  });
+}
@@ -0,0 +1,98 @@
+import { isTauri } from "@/utils/platform";
+
+/**
+ * Client for the desktop shell's content-addressed blob store (see
+ * src-tauri/src/blob.rs). Blobs are named by the SHA-256 of their
+ * content and served on a URL that can go straight into `<img>` or
+ * `<audio>` (with Range requests for seeking), so the bytes don't have to
+ * pass through JS again. Only available in the desktop app.
+ */
+
+/** URI scheme the shell serves blobs on */
+const SCHEME = "syaos-blob";
+
+/** Header the shell reads the options JSON from in `blob_put` */
+const OPTIONS_HEADER = "x-syaos-blob";
+
+export interface StoredBlob {
+  /** SHA-256 of the content, as lowercase hex */
+  hash: string;
+  /** Recognised from the content, or the type it was put with */
+  type: string;
+  size: number;
+  /** When it was last put, in milliseconds since the epoch */
+  createdAt: number;
+}
+
+export interface BlobGcReport {
+  removed: number;
+  freedBytes: number;
+  remaining: number;
+  totalBytes: number;
+}
+
+export const isBlobStoreAvailable = (): boolean => isTauri();
+
+async function invoke<T>(
+  command: string,
+  args?: Record<string, unknown> | Uint8Array,
+  headers?: Record<string, string>
+): Promise<T> {
+  const core = await import("@tauri-apps/api/core");
+  return core.invoke<T>(command, args, headers ? { headers } : undefined);
+}
+
+/** URL the blob with `hash` is served on */
+export async function blobUrl(hash: string): Promise<string> {
+  const { convertFileSrc } = await import("@tauri-apps/api/core");
+  return convertFileSrc(hash, SCHEME);
+}
+
+/**
+ * Store `data`, or find it already stored. `type` is used when the shell
+ * doesn't recognise the content, e.g. for SVG; a Blob's own type is used
+ * by default.
+ */
+export async function putBlob(
+  data: Blob | ArrayBuffer | Uint8Array,
+  type?: string
+): Promise<StoredBlob & { url: string }> {
+  const bytes =
+    data instanceof Uint8Array
+      ? data
+      : new Uint8Array(
+          data instanceof Blob ? await data.arrayBuffer() : data
+        );
+  const declared = type ?? (data instanceof Blob ? data.type : undefined);
+  const stored = await invoke<StoredBlob>(
+    "blob_put",
+    bytes,
+    declared
+      ? {
+          [OPTIONS_HEADER]: encodeURIComponent(
+            JSON.stringify({ type: declared })
+          ),
+        }
+      : undefined
+  );
+  return { ...stored, url: await blobUrl(stored.hash) };
+}
+
+/** The blob's content, or null if there is no such blob */
+export async function getBlob(hash: string): Promise<Blob | null> {
+  const info = await invoke<StoredBlob | null>("blob_stat", { hash });
+  if (!info) return null;
+  const content = await invoke<ArrayBuffer>("blob_get", { hash });
+  return new Blob([content], { type: info.type });
+}
+
+/** Whether there was such a blob */
+export const deleteBlob = (hash: string): Promise<boolean> =>
+  invoke("blob_delete", { hash });
+
+/**
+ * Delete every blob whose hash isn't in `keep`. Blobs put in the last few
+ * minutes are spared, in case their hashes haven't been saved yet.
+ */
+export const gcBlobs = (keep: Iterable<string>): Promise<BlobGcReport> =>
+  invoke("blob_gc", { keep: [...keep] });
@@ -0,0 +1,85 @@
+import type { FileSystemItem } from "@/stores/useFilesStore";
+import { isTauri, isTauriWindows } from "@/utils/platform";
+
+/**
+ * Host files dragged onto the desktop app's window (see
+ * src-tauri/src/drag_drop.rs). The shell resolves and sniffs them and
+ * reports where they were dropped; `readDroppedFile` and
+ * `importDroppedItems` only accept paths from a drop.
+ */
+
+/** A point in the window, in CSS pixels */
+export interface DropPosition {
+  x: number;
+  y: number;
+}
+
+export interface DroppedItem {
+  path: string;
+  name: string;
+  isDirectory: boolean;
+  /** Bytes; a directory's whole tree */
+  size: number;
+  /** Sniffed from the content, falling back to the extension */
+  mime: string;
+  /** Finder file type, as `getFileTypeFromExtension` gives it */
+  type: string;
+}
+
+export interface HostDrop {
+  items: DroppedItem[];
+  /** Paths that couldn't be used, e.g. over the size limit */
+  rejected: { path: string; reason: string }[];
+  position: DropPosition;
+}
+
+/**
+ * Whether host drops are reported. The Windows shell leaves drops to the
+ * page, as WebView2 would otherwise stop HTML5 drag-and-drop inside it.
+ */
+export const isHostDropAvailable = (): boolean =>
+  isTauri() && !isTauriWindows();
+
+async function listen<T>(
+  event: string,
+  listener: (payload: T) => void
+): Promise<() => void> {
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<T>(event, (event) => listener(event.payload));
+}
+
+/** Call `listener` as host files are dragged over the window */
+export const onHostDragOver = (
+  listener: (position: DropPosition) => void
+): Promise<() => void> => listen("drop://over", listener);
+
+/** Call `listener` when a drag leaves the window without dropping */
+export const onHostDragLeave = (listener: () => void): Promise<() => void> =>
+  listen("drop://leave", listener);
+
+/** Call `listener` with each drop of host files on the window */
+export const onHostDrop = (
+  listener: (drop: HostDrop) => void
+): Promise<() => void> => listen("drop://drop", listener);
+
+/** The content of a dropped file */
+export async function readDroppedFile(item: DroppedItem): Promise<File> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  const bytes = await invoke<ArrayBuffer>("drop_read", { path: item.path });
+  return new File([bytes], item.name, { type: item.mime });
+}
+
+/**
+ * Copy dropped items into `destination` in syaOS Home or a mounted volume;
+ * by default images go to /Images and everything else to /Documents
+ */
+export async function importDroppedItems(
+  items: DroppedItem[],
+  destination?: string
+): Promise<FileSystemItem[]> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke("drop_import", {
+    paths: items.map((item) => item.path),
+    destination,
+  });
+}
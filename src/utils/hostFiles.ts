@@ -0,0 +1,105 @@
+import { isTauri } from "@/utils/platform";
+
+/**
+ * Client for host files opened and saved through the desktop shell's native
+ * dialogs (see src-tauri/src/host_files.rs). The shell hands out a handle for
+ * each file the user picks; reads and re-saves go through that handle, so
+ * the web app never touches other paths.
+ */
+
+/** Header naming the open file `host_file_save` writes to */
+const HANDLE_HEADER = "x-syaos-handle";
+
+/** Header carrying the kind and suggested name for a new file */
+const SAVE_AS_HEADER = "x-syaos-save-as";
+
+/** "text" files are .md/.txt/.html/.rtf, "image" files .png/.jpg/.bmp */
+export type HostFileKind = "text" | "image";
+
+export interface HostFile {
+  handle: string;
+  name: string;
+  path: string;
+  kind: HostFileKind;
+  size: number;
+  modifiedAt: number;
+}
+
+export interface RecentHostFile {
+  name: string;
+  path: string;
+  kind: HostFileKind;
+  openedAt: number;
+}
+
+/** Whether native open/save dialogs can be used */
+export const isHostFilesAvailable = (): boolean => isTauri();
+
+async function invoke<T>(
+  command: string,
+  args?: Record<string, unknown> | Uint8Array,
+  headers?: Record<string, string>
+): Promise<T> {
+  const core = await import("@tauri-apps/api/core");
+  return core.invoke<T>(command, args, headers ? { headers } : undefined);
+}
+
+async function toBytes(
+  content: string | Blob | Uint8Array
+): Promise<Uint8Array> {
+  if (typeof content === "string") return new TextEncoder().encode(content);
+  if (content instanceof Blob) {
+    return new Uint8Array(await content.arrayBuffer());
+  }
+  return content;
+}
+
+/** Ask for a file to open; `null` if the dialog is cancelled */
+export const openHostFile = (kind: HostFileKind): Promise<HostFile | null> =>
+  invoke("host_file_open", { kind });
+
+/** Open a file from the recent list again */
+export const openRecentHostFile = (path: string): Promise<HostFile> =>
+  invoke("host_file_open_recent", { path });
+
+/** Recently opened or saved files of `kind`, newest first */
+export const listRecentHostFiles = (
+  kind?: HostFileKind
+): Promise<RecentHostFile[]> => invoke("host_file_recent", { kind });
+
+export const readHostFile = async (handle: string): Promise<Uint8Array> =>
+  new Uint8Array(await invoke<ArrayBuffer>("host_file_read", { handle }));
+
+export const readHostFileText = async (handle: string): Promise<string> =>
+  new TextDecoder().decode(await readHostFile(handle));
+
+/** Replace the contents of an open file */
+export async function saveHostFile(
+  handle: string,
+  content: string | Blob | Uint8Array
+): Promise<HostFile> {
+  const file = await invoke<HostFile | null>(
+    "host_file_save",
+    await toBytes(content),
+    { [HANDLE_HEADER]: handle }
+  );
+  return file!;
+}
+
+/**
+ * Ask where to save `content` as a new file, suggesting `name`. Resolves to
+ * its handle, or `null` if the dialog is cancelled.
+ */
+export async function saveHostFileAs(
+  kind: HostFileKind,
+  content: string | Blob | Uint8Array,
+  name?: string
+): Promise<HostFile | null> {
+  return invoke("host_file_save", await toBytes(content), {
+    [SAVE_AS_HEADER]: encodeURIComponent(JSON.stringify({ kind, name })),
+  });
+}
+
+/** Give up access to an open file */
+export const closeHostFile = (handle: string): Promise<void> =>
+  invoke("host_file_close", { handle });
@@ -0,0 +1,125 @@
+import type { FileSystemItem } from "@/stores/useFilesStore";
+import { isTauri } from "@/utils/platform";
+
+/**
+ * Client for the desktop shell's "syaOS Home" filesystem
+ * (see src-tauri/src/vfs.rs). Every call mirrors the matching
+ * useFilesStore action, but on real files on disk.
+ */
+
+/** Header the shell reads the item JSON from in `fs_write` */
+const ITEM_HEADER = "x-syaos-item";
+
+/** Virtual directory the mounted host folders are listed in */
+export const VOLUMES_PATH = "/Volumes";
+
+/** Whether `path` is "/Volumes" or inside a mounted volume */
+export const isVolumePath = (path: string): boolean =>
+  path === VOLUMES_PATH || path.startsWith(`${VOLUMES_PATH}/`);
+
+/** Whether the native filesystem commands can be used */
+export const isNativeFsAvailable = (): boolean => isTauri();
+
+async function invoke<T>(
+  command: string,
+  args?: Record<string, unknown> | Uint8Array,
+  headers?: Record<string, string>
+): Promise<T> {
+  const core = await import("@tauri-apps/api/core");
+  return core.invoke<T>(command, args, headers ? { headers } : undefined);
+}
+
+/** A host folder shown at `/Volumes/<name>` */
+export interface Mount {
+  name: string;
+  hostPath: string;
+}
+
+/**
+ * Active children of `path`, every trashed item for "/Trash", or the
+ * mounted volumes for "/Volumes"
+ */
+export const listItems = (path: string): Promise<FileSystemItem[]> =>
+  invoke("fs_list", { path });
+
+/** Raw content of the file at `path` */
+export const readFile = async (path: string): Promise<Uint8Array> =>
+  new Uint8Array(await invoke<ArrayBuffer>("fs_read", { path }));
+
+/**
+ * Create or update an item like `addItem`, writing `content` for files.
+ * Existing items keep their `uuid` and `createdAt`.
+ */
+export async function writeItem(
+  item: Omit<FileSystemItem, "status" | "name">,
+  content?: string | Blob | Uint8Array
+): Promise<FileSystemItem> {
+  let body: Uint8Array;
+  if (content === undefined) {
+    body = new Uint8Array();
+  } else if (typeof content === "string") {
+    body = new TextEncoder().encode(content);
+  } else if (content instanceof Blob) {
+    body = new Uint8Array(await content.arrayBuffer());
+  } else {
+    body = content;
+  }
+  return invoke("fs_write", body, {
+    [ITEM_HEADER]: encodeURIComponent(JSON.stringify(item)),
+  });
+}
+
+export const renameItem = (
+  oldPath: string,
+  newPath: string,
+  newName: string
+): Promise<void> => invoke("fs_rename", { oldPath, newPath, newName });
+
+export const moveItem = (
+  sourcePath: string,
+  destinationPath: string
+): Promise<void> => invoke("fs_move", { sourcePath, destinationPath });
+
+/**
+ * Move an item to the trash, or delete it for good when `permanent` or
+ * already trashed. Returns the UUIDs of deleted files.
+ */
+export const trashItem = (path: string, permanent = false): Promise<string[]> =>
+  invoke("fs_trash", { path, permanent });
+
+export const restoreItem = (path: string): Promise<void> =>
+  invoke("fs_restore", { path });
+
+/** Delete everything in the trash. Returns the UUIDs of deleted files. */
+export const emptyTrash = (): Promise<string[]> => invoke("fs_empty_trash");
+
+export const listMounts = (): Promise<Mount[]> => invoke("mount_list");
+
+/**
+ * Mount a host folder the user picks under /Volumes. Resolves to `null` if
+ * the picker is cancelled.
+ */
+export const addMount = (name?: string): Promise<Mount | null> =>
+  invoke("mount_add", { name });
+
+export const removeMount = (name: string): Promise<void> =>
+  invoke("mount_remove", { name });
+
+/** A change made to a mounted volume outside syaOS */
+export interface FsChange {
+  kind: "create" | "modify" | "rename" | "delete";
+  path: string;
+  /** Where a renamed item used to be */
+  oldPath?: string;
+}
+
+/**
+ * Call `listener` with each debounced batch of changes to mounted volumes.
+ * Resolves to a function that stops listening.
+ */
+export async function onFsChanged(
+  listener: (changes: FsChange[]) => void
+): Promise<() => void> {
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<FsChange[]>("fs://changed", (event) => listener(event.payload));
+}
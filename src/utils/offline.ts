@@ -1,9 +1,20 @@
 import { toast } from "sonner";
 
+/**
+ * Check if the desktop shell fell back to the bundled app because the remote
+ * origin was unreachable. The shell clears the flag once it is reachable again.
+ */
+export function isShellOfflineFallback(): boolean {
+  return typeof window !== "undefined" && window.__SYAOS_SHELL__?.offlineFallback === true;
+}
+
 /**
  * Check if the browser is currently offline
  */
 export function isOffline(): boolean {
+  if (isShellOfflineFallback()) {
+    return true;
+  }
   if (typeof navigator !== "undefined" && "onLine" in navigator) {
     return !navigator.onLine;
   }
@@ -0,0 +1,57 @@
+import type { AppletViewerInitialData } from "@/apps/applet-viewer";
+import type { PcInitialData } from "@/apps/pc";
+import type { TextEditInitialData } from "@/apps/textedit/utils/textEditUtils";
+import { importAppletFromHost } from "@/utils/appletImportExport";
+import { readShellOpenedFile, type ShellOpenedFile } from "@/utils/shell";
+
+/**
+ * Turns a host file the shell was asked to open (a file association, see
+ * src-tauri/src/file_open.rs) into the initialData its app expects.
+ * TextEdit gets a host file handle, so saving writes back to the file;
+ * other files are handed over as content.
+ */
+export async function initialDataForOpenedFile(
+  file: ShellOpenedFile
+): Promise<unknown> {
+  switch (file.appId) {
+    case "textedit": {
+      if (file.hostFile) {
+        const initialData: TextEditInitialData = { hostFile: file.hostFile };
+        return initialData;
+      }
+      // Shells before API version 14 don't hand out a handle
+      const initialData: TextEditInitialData = {
+        path: `/Documents/${file.name}`,
+        content: new TextDecoder().decode(
+          await readShellOpenedFile(file.path)
+        ),
+      };
+      return initialData;
+    }
+    case "applet-viewer": {
+      // The shell decodes and validates the package
+      const applet = (await importAppletFromHost(file.path))!;
+      const initialData: AppletViewerInitialData = {
+        path: "",
+        content: applet.content,
+        name: applet.name,
+        icon: applet.icon,
+      };
+      return initialData;
+    }
+    case "pc": {
+      const bytes = await readShellOpenedFile(file.path);
+      const initialData: PcInitialData = {
+        game: {
+          id: `host:${file.path}`,
+          name: file.name.replace(/\.jsdos$/i, ""),
+          path: URL.createObjectURL(new Blob([bytes as BlobPart])),
+          image: "",
+        },
+      };
+      return initialData;
+    }
+    default:
+      throw new Error(`${file.appId} can't open ${file.name}`);
+  }
+}
@@ -0,0 +1,309 @@
+import { ensureIndexedDBInitialized, STORES } from "@/utils/indexedDB";
+import {
+  listStorageKeys,
+  readAllStorage,
+  syncShellStorage,
+} from "@/utils/shellStorage";
+
+/**
+ * Whole-profile export/import through the desktop shell
+ * (see src-tauri/src/backup.rs). The shell writes and reads the archive;
+ * this side collects and applies the localStorage keys (with the shell's
+ * own storage, see shellStorage.ts) and IndexedDB records, which only the
+ * webview can reach.
+ *
+ * Messages are a little-endian u32 JSON length, the JSON, then the bytes
+ * of each blob listed in its `blobs`. Blobs inside stored values are
+ * replaced by `{ $blob: <index> }`.
+ */
+
+interface BlobInfo {
+  size: number;
+  type: string;
+}
+
+interface StoreRecord {
+  key: IDBValidKey;
+  value: unknown;
+}
+
+interface Snapshot {
+  localStorage: Record<string, string>;
+  stores: Record<string, StoreRecord[]>;
+  blobs: BlobInfo[];
+}
+
+export interface ProfileManifest {
+  format: string;
+  version: number;
+  createdAt: number;
+  appVersion: string;
+  localStorageKeys: number;
+  stores: Record<string, number>;
+  blobs: number;
+}
+
+export interface ExportReport {
+  path: string;
+  manifest: ProfileManifest;
+  bytes: number;
+}
+
+export interface ImportCounts {
+  added: number;
+  overwritten: number;
+  skipped: number;
+}
+
+export interface ImportReport {
+  path: string;
+  /** Names the picked archive for the import after a dry run */
+  token: string;
+  dryRun: boolean;
+  manifest: ProfileManifest;
+  localStorage: ImportCounts;
+  stores: Record<string, ImportCounts>;
+  conflicts: { store: string; key: IDBValidKey }[];
+  warnings: string[];
+}
+
+export type OnConflict = "overwrite" | "skip";
+
+function encodeMessage(json: unknown, blobs: Uint8Array[]): Uint8Array {
+  const encoded = new TextEncoder().encode(JSON.stringify(json));
+  const total =
+    4 + encoded.length + blobs.reduce((sum, blob) => sum + blob.length, 0);
+  const message = new Uint8Array(total);
+  new DataView(message.buffer).setUint32(0, encoded.length, true);
+  message.set(encoded, 4);
+  let offset = 4 + encoded.length;
+  for (const blob of blobs) {
+    message.set(blob, offset);
+    offset += blob.length;
+  }
+  return message;
+}
+
+function decodeMessage<T>(
+  buffer: ArrayBuffer,
+  blobInfos: (json: T) => BlobInfo[]
+): { json: T; blobs: Uint8Array[] } {
+  const length = new DataView(buffer).getUint32(0, true);
+  const json = JSON.parse(
+    new TextDecoder().decode(new Uint8Array(buffer, 4, length))
+  ) as T;
+  let offset = 4 + length;
+  const blobs = (json === null ? [] : blobInfos(json)).map((info) => {
+    const blob = new Uint8Array(buffer, offset, info.size);
+    offset += info.size;
+    return blob;
+  });
+  return { json, blobs };
+}
+
+/** Replaces every Blob in `value` with a reference, collecting its bytes */
+async function extractBlobs(
+  value: unknown,
+  infos: BlobInfo[],
+  blobs: Uint8Array[]
+): Promise<unknown> {
+  if (value instanceof Blob) {
+    infos.push({ size: value.size, type: value.type });
+    blobs.push(new Uint8Array(await value.arrayBuffer()));
+    return { $blob: infos.length - 1 };
+  }
+  if (Array.isArray(value)) {
+    return Promise.all(value.map((item) => extractBlobs(item, infos, blobs)));
+  }
+  if (value && typeof value === "object" && !(value instanceof Date)) {
+    const entries = await Promise.all(
+      Object.entries(value).map(
+        async ([key, item]) =>
+          [key, await extractBlobs(item, infos, blobs)] as const
+      )
+    );
+    return Object.fromEntries(entries);
+  }
+  return value;
+}
+
+/** The inverse of `extractBlobs` */
+function restoreBlobs(
+  value: unknown,
+  infos: BlobInfo[],
+  blobs: Uint8Array[]
+): unknown {
+  if (Array.isArray(value)) {
+    return value.map((item) => restoreBlobs(item, infos, blobs));
+  }
+  if (value && typeof value === "object") {
+    const entries = Object.entries(value);
+    if (
+      entries.length === 1 &&
+      entries[0][0] === "$blob" &&
+      typeof entries[0][1] === "number"
+    ) {
+      const index = entries[0][1];
+      return new Blob([blobs[index] as BlobPart], { type: infos[index].type });
+    }
+    return Object.fromEntries(
+      entries.map(([key, item]) => [key, restoreBlobs(item, infos, blobs)])
+    );
+  }
+  return value;
+}
+
+function readStore(db: IDBDatabase, storeName: string): Promise<StoreRecord[]> {
+  return new Promise((resolve, reject) => {
+    const records: StoreRecord[] = [];
+    const request = db
+      .transaction(storeName, "readonly")
+      .objectStore(storeName)
+      .openCursor();
+    request.onsuccess = () => {
+      const cursor = request.result;
+      if (cursor) {
+        records.push({ key: cursor.key, value: cursor.value });
+        cursor.continue();
+      } else {
+        resolve(records);
+      }
+    };
+    request.onerror = () => reject(request.error);
+  });
+}
+
+function readStoreKeys(
+  db: IDBDatabase,
+  storeName: string
+): Promise<IDBValidKey[]> {
+  return new Promise((resolve, reject) => {
+    const request = db
+      .transaction(storeName, "readonly")
+      .objectStore(storeName)
+      .getAllKeys();
+    request.onsuccess = () => resolve(request.result);
+    request.onerror = () => reject(request.error);
+  });
+}
+
+function writeStore(
+  db: IDBDatabase,
+  storeName: string,
+  records: StoreRecord[]
+): Promise<void> {
+  return new Promise((resolve, reject) => {
+    const transaction = db.transaction(storeName, "readwrite");
+    const store = transaction.objectStore(storeName);
+    for (const record of records) {
+      store.put(record.value, record.key);
+    }
+    transaction.oncomplete = () => resolve();
+    transaction.onerror = () => reject(transaction.error);
+  });
+}
+
+const localStorageKeys = (): string[] =>
+  Array.from({ length: localStorage.length }, (_, index) =>
+    localStorage.key(index)
+  ).filter((key): key is string => key !== null);
+
+/**
+ * Export every localStorage key and IndexedDB record to an archive the
+ * user picks in a native save dialog. Resolves to `null` if cancelled.
+ */
+export async function exportProfile(): Promise<ExportReport | null> {
+  const snapshot: Snapshot = {
+    localStorage: await readAllStorage(),
+    stores: {},
+    blobs: [],
+  };
+  const blobs: Uint8Array[] = [];
+
+  const db = await ensureIndexedDBInitialized();
+  try {
+    for (const storeName of Object.values(STORES)) {
+      const records = await readStore(db, storeName);
+      snapshot.stores[storeName] = await Promise.all(
+        records.map(async (record) => ({
+          key: record.key,
+          value: await extractBlobs(record.value, snapshot.blobs, blobs),
+        }))
+      );
+    }
+  } finally {
+    db.close();
+  }
+
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<ExportReport | null>(
+    "profile_export",
+    encodeMessage(snapshot, blobs)
+  );
+}
+
+/**
+ * Validate an archive and check it against the current profile. Without
+ * `dryRun`, the archive's entries are also written (reload afterwards so
+ * the stores pick them up). Pass the `token` from a dry run's report to
+ * skip the open dialog. Resolves to `null` if the dialog is cancelled.
+ */
+export async function importProfile(options: {
+  dryRun: boolean;
+  onConflict?: OnConflict;
+  token?: string;
+}): Promise<ImportReport | null> {
+  const db = await ensureIndexedDBInitialized();
+  try {
+    const existing = {
+      localStorage: [
+        ...new Set([
+          ...localStorageKeys(),
+          ...(await listStorageKeys()).map(({ key }) => key),
+        ]),
+      ],
+      stores: Object.fromEntries(
+        await Promise.all(
+          Object.values(STORES).map(
+            async (storeName) =>
+              [storeName, await readStoreKeys(db, storeName)] as const
+          )
+        )
+      ),
+    };
+
+    const { invoke } = await import("@tauri-apps/api/core");
+    const response = await invoke<ArrayBuffer>("profile_import", {
+      token: options.token,
+      dryRun: options.dryRun,
+      onConflict: options.onConflict,
+      existing,
+    });
+    const { json, blobs } = decodeMessage<{
+      report: ImportReport;
+      apply?: Snapshot;
+    } | null>(response, (json) => json?.apply?.blobs ?? []);
+    if (!json) return null;
+
+    const { report, apply } = json;
+    if (apply) {
+      for (const [key, value] of Object.entries(apply.localStorage)) {
+        localStorage.setItem(key, value);
+      }
+      await syncShellStorage(Object.keys(apply.localStorage));
+      for (const [storeName, records] of Object.entries(apply.stores)) {
+        await writeStore(
+          db,
+          storeName,
+          records.map((record) => ({
+            key: record.key,
+            value: restoreBlobs(record.value, apply.blobs, blobs),
+          }))
+        );
+      }
+    }
+    return report;
+  } finally {
+    db.close();
+  }
+}
@@ -0,0 +1,139 @@
+import { useFilesStore, type FileSystemItem } from "@/stores/useFilesStore";
+import { STORES } from "@/utils/indexedDB";
+import { loadFileContent } from "@/utils/indexedDBOperations";
+import { isTauri } from "@/utils/platform";
+
+/**
+ * Client for the desktop shell's full-text search (see
+ * src-tauri/src/search.rs). The shell indexes files on disk itself;
+ * documents and applets in IndexedDB are sent to it as they change.
+ */
+
+/** Header the shell reads the document JSON from in `search_index_document` */
+const DOCUMENT_HEADER = "x-syaos-search-document";
+
+/** How long file changes settle before the index is synced */
+const SYNC_DELAY_MS = 2000;
+
+export interface SearchHit {
+  path: string;
+  name: string;
+  kind: "document" | "applet" | "file";
+  modifiedAt: number;
+  score: number;
+  /** About 160 characters of the content around the best match */
+  snippet: string;
+  /** `[start, end)` ranges of `snippet` that matched */
+  highlights: [number, number][];
+}
+
+export interface SearchOptions {
+  /** Only search under this path, e.g. "/Documents" */
+  scope?: string;
+  limit?: number;
+}
+
+async function invoke<T>(
+  command: string,
+  args?: Record<string, unknown> | Uint8Array,
+  headers?: Record<string, string>
+): Promise<T> {
+  const core = await import("@tauri-apps/api/core");
+  return core.invoke<T>(command, args, headers ? { headers } : undefined);
+}
+
+/** The IndexedDB store holding the content of `path`, if it is searchable */
+function storeFor(path: string): string | null {
+  if (path.startsWith("/Documents/")) return STORES.DOCUMENTS;
+  if (path.startsWith("/Applets/")) return STORES.APPLETS;
+  return null;
+}
+
+async function indexDocument(item: FileSystemItem): Promise<void> {
+  const stored = await loadFileContent(item.uuid!, storeFor(item.path)!);
+  if (!stored) return;
+  const content =
+    typeof stored.content === "string"
+      ? stored.content
+      : await stored.content.text();
+  await invoke(
+    "search_index_document",
+    new TextEncoder().encode(content),
+    {
+      [DOCUMENT_HEADER]: encodeURIComponent(
+        JSON.stringify({ path: item.path, modifiedAt: item.modifiedAt ?? 0 })
+      ),
+    }
+  );
+}
+
+/** Send the shell every document it hasn't indexed since it last changed */
+async function syncSearchIndex(): Promise<void> {
+  const documents = Object.values(useFilesStore.getState().items).filter(
+    (item) =>
+      !item.isDirectory &&
+      item.status === "active" &&
+      item.uuid &&
+      storeFor(item.path)
+  );
+  const stale = await invoke<string[]>("search_sync", {
+    documents: documents.map((item) => ({
+      path: item.path,
+      modifiedAt: item.modifiedAt ?? 0,
+    })),
+  });
+  const byPath = new Map(documents.map((item) => [item.path, item]));
+  for (const path of stale) {
+    try {
+      await indexDocument(byPath.get(path)!);
+    } catch (error) {
+      console.error(`[search] Failed to index ${path}:`, error);
+    }
+  }
+}
+
+/**
+ * Keep the shell's index in sync with the files store. Does nothing
+ * outside the desktop app. Returns a function that stops syncing.
+ */
+export function startSearchIndexSync(): () => void {
+  if (!isTauri()) return () => {};
+
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  let running: Promise<void> | null = null;
+  let pending = false;
+  const sync = () => {
+    if (running) {
+      pending = true;
+      return;
+    }
+    running = syncSearchIndex()
+      .catch((error) => console.error("[search] Sync failed:", error))
+      .finally(() => {
+        running = null;
+        if (pending) {
+          pending = false;
+          sync();
+        }
+      });
+  };
+  const schedule = () => {
+    clearTimeout(timer);
+    timer = setTimeout(sync, SYNC_DELAY_MS);
+  };
+
+  schedule();
+  const unsubscribe = useFilesStore.subscribe((state, previous) => {
+    if (state.items !== previous.items) schedule();
+  });
+  return () => {
+    clearTimeout(timer);
+    unsubscribe();
+  };
+}
+
+/** Ranked documents that contain every word of `query` */
+export const searchFiles = (
+  query: string,
+  { scope, limit }: SearchOptions = {}
+): Promise<SearchHit[]> => invoke("search", { query, scope, limit });
@@ -0,0 +1,191 @@
+import type { AppId } from "@/config/appIds";
+import type { OsThemeId } from "@/themes/types";
+import { isTauri } from "@/utils/platform";
+import type { HostFile } from "@/utils/hostFiles";
+
+/**
+ * Desktop shell (src-tauri) integration.
+ */
+
+/**
+ * Shell API version this web build relies on. Raise it when the app starts
+ * depending on shell commands added in a newer desktop release
+ * (see API_VERSION in src-tauri/src/shell_info.rs).
+ */
+export const REQUIRED_SHELL_API_VERSION = 12;
+
+export interface ShellInfo {
+  version: string;
+  apiVersion: number;
+  buildCommit: string;
+  features: string[];
+  launchMode: "bundled" | "remote";
+  origin: string;
+  offlineFallback: boolean;
+  profile: string | null;
+}
+
+/** An app the shell asks the web app to open (see src-tauri/src/cli.rs) */
+export interface ShellLaunchRequest {
+  appId: AppId;
+  path: string | null;
+  newWindow: boolean;
+}
+
+/** A host file opened with syaOS (see src-tauri/src/file_open.rs) */
+export interface ShellOpenedFile {
+  appId: AppId;
+  path: string;
+  name: string;
+  size: number;
+  newWindow: boolean;
+  /** For TextEdit documents, the handle saves go back to the file through */
+  hostFile?: HostFile | null;
+}
+
+/**
+ * Whether an app may be opened. Always true unless the shell runs in kiosk
+ * mode with an app allowlist.
+ */
+export function isAppAllowedInKiosk(appId: string): boolean {
+  const allowedApps = window.__SYAOS_SHELL__?.kiosk?.allowedApps;
+  return !allowedApps?.length || allowedApps.includes(appId);
+}
+
+export type ShellCompatibility =
+  | { status: "compatible" }
+  | { status: "shellTooOld"; apiVersion: number; requiredApiVersion: number };
+
+/**
+ * Get version/build info from the desktop shell, or null outside Tauri or
+ * when the shell predates the handshake.
+ */
+export async function getShellInfo(): Promise<ShellInfo | null> {
+  if (!isTauri()) return null;
+  try {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return await invoke<ShellInfo>("get_shell_info");
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Ask the shell whether it supports the API this web build needs.
+ * Returns null outside Tauri. Shells without the handshake are reported as
+ * too old, since they predate API version 1.
+ */
+export async function checkShellCompatibility(): Promise<ShellCompatibility | null> {
+  if (!isTauri()) return null;
+  try {
+    const { invoke } = await import("@tauri-apps/api/core");
+    return await invoke<ShellCompatibility>("check_shell_compatibility", {
+      requiredApiVersion: REQUIRED_SHELL_API_VERSION,
+    });
+  } catch {
+    return {
+      status: "shellTooOld",
+      apiVersion: 0,
+      requiredApiVersion: REQUIRED_SHELL_API_VERSION,
+    };
+  }
+}
+
+/**
+ * Tell the shell the desktop has rendered so it can close its splash window.
+ * Older shells without a splash ignore this.
+ */
+export async function notifyShellReady(): Promise<void> {
+  if (!isTauri()) return;
+  try {
+    const { invoke } = await import("@tauri-apps/api/core");
+    await invoke("app_ready");
+  } catch {
+    // Shell predates the splash window
+  }
+}
+
+/** Remember the OS theme so the shell's next splash window matches it */
+export async function rememberShellTheme(theme: OsThemeId): Promise<void> {
+  if (!isTauri()) return;
+  try {
+    const { invoke } = await import("@tauri-apps/api/core");
+    await invoke("remember_theme", { theme });
+  } catch {
+    // Shell predates the splash window
+  }
+}
+
+/** A syaos:// link routed by the shell (see src-tauri/src/deep_link.rs) */
+export type ShellDeepLink = { url: string } & (
+  | { route: "applet"; shareId: string }
+  | { route: "ipod"; songId: string }
+  | { route: "chatRoom"; roomId: string }
+);
+
+/**
+ * Subscribe to a shell event whose early occurrences the shell queues until
+ * the app asks for them with `takeCommand`. Returns an unsubscribe function.
+ */
+async function listenWithBacklog<T>(
+  event: string,
+  takeCommand: string,
+  onItem: (item: T) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  try {
+    const [{ invoke }, { listen }] = await Promise.all([
+      import("@tauri-apps/api/core"),
+      import("@tauri-apps/api/event"),
+    ]);
+    // Subscribe first so nothing is dropped between draining and listening
+    const unlisten = await listen<T>(event, (e) => onItem(e.payload));
+    const pending = await invoke<T[]>(takeCommand);
+    pending.forEach(onItem);
+    return unlisten;
+  } catch (error) {
+    console.warn(`[Shell] Could not listen for ${event}:`, error);
+    return () => {};
+  }
+}
+
+/**
+ * Deliver launch requests from the shell: the ones queued while the app was
+ * loading, then each new one as it arrives. Returns an unsubscribe function.
+ */
+export function listenForShellLaunches(
+  onLaunch: (request: ShellLaunchRequest) => void
+): Promise<() => void> {
+  return listenWithBacklog("shell://launch", "take_launch_requests", onLaunch);
+}
+
+/**
+ * Deliver syaos:// links opened from outside the app, including the one the
+ * app was started with. Returns an unsubscribe function.
+ */
+export function listenForShellDeepLinks(
+  onDeepLink: (link: ShellDeepLink) => void
+): Promise<() => void> {
+  return listenWithBacklog("shell://deep-link", "take_deep_links", onDeepLink);
+}
+
+/**
+ * Deliver host files opened with syaOS from the OS (file associations),
+ * including the ones the app was started with. Returns an unsubscribe
+ * function.
+ */
+export function listenForShellOpenedFiles(
+  onOpen: (file: ShellOpenedFile) => void
+): Promise<() => void> {
+  return listenWithBacklog("shell://open-file", "take_opened_files", onOpen);
+}
+
+/** Read the content of a file delivered by `listenForShellOpenedFiles` */
+export async function readShellOpenedFile(
+  path: string
+): Promise<Uint8Array> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return new Uint8Array(
+    await invoke<ArrayBuffer>("opened_file_read", { path })
+  );
+}
@@ -0,0 +1,153 @@
+import type { StateStorage } from "zustand/middleware";
+import { isTauri } from "@/utils/platform";
+
+/**
+ * zustand storage backed by the desktop shell's SQLite database (see
+ * src-tauri/src/storage.rs), which has no quota and survives clearing the
+ * webview's data. A store opts in with
+ * `storage: createJSONStorage(() => persistStorage)`; outside the desktop
+ * app that is plain localStorage. Backups, restores and resets go through
+ * `readAllStorage`, `syncShellStorage` and `clearShellStorage` so they see
+ * the same values as the stores.
+ */
+
+export interface StorageKeyStat {
+  key: string;
+  /** Of the value, in UTF-8 bytes */
+  size: number;
+  updatedAt: number;
+}
+
+async function invoke<T>(
+  command: string,
+  args?: Record<string, unknown>
+): Promise<T> {
+  const core = await import("@tauri-apps/api/core");
+  return core.invoke<T>(command, args);
+}
+
+function localStorageEntries(): Record<string, string> {
+  const entries: Record<string, string> = {};
+  for (let i = 0; i < localStorage.length; i++) {
+    const key = localStorage.key(i);
+    const value = key === null ? null : localStorage.getItem(key);
+    if (key !== null && value !== null) entries[key] = value;
+  }
+  return entries;
+}
+
+let imported: Promise<void> | null = null;
+
+/**
+ * Copy everything in localStorage into the shell's database, once per
+ * profile. Keys the shell already has are left alone. localStorage is
+ * kept as it was, so nothing is lost if the shell is downgraded.
+ */
+export function importLocalStorage(): Promise<void> {
+  if (!isTauri()) return Promise.resolve();
+  imported ??= (async () => {
+    try {
+      await invoke<number | null>("storage_import", {
+        entries: localStorageEntries(),
+      });
+    } catch (error) {
+      console.error("[storage] localStorage import failed:", error);
+    }
+  })();
+  return imported;
+}
+
+// Operations run one at a time in the order they were made, so a read
+// issued while a store is created can't see a later write, and the last
+// write of a key wins
+let queue: Promise<unknown> = Promise.resolve();
+
+function enqueue<T>(operation: () => Promise<T>): Promise<T> {
+  const result = queue.then(() => importLocalStorage()).then(operation);
+  queue = result.catch(() => {});
+  return result;
+}
+
+/** Logs a failed operation on `name` and passes the error on */
+const failed =
+  (action: string, name: string) =>
+  (error: unknown): never => {
+    console.error(`[storage] Failed to ${action} ${name}:`, error);
+    throw error;
+  };
+
+/**
+ * The shell's storage as a zustand `StateStorage`. Failures, e.g. when the
+ * shell couldn't open its database, are rejected rather than written
+ * somewhere else, so there's only ever one copy of a store.
+ */
+export const shellStorage: StateStorage = {
+  getItem: (name) =>
+    enqueue(() =>
+      invoke<string | null>("storage_get", { name }).catch(
+        failed("read", name)
+      )
+    ),
+  setItem: (name, value) =>
+    enqueue(() =>
+      invoke<void>("storage_set", { name, value }).catch(
+        failed("write", name)
+      )
+    ),
+  removeItem: (name) =>
+    enqueue(() =>
+      invoke<void>("storage_remove", { name }).catch(failed("remove", name))
+    ),
+};
+
+/** Storage for persisted stores: the shell's in the desktop app */
+export const persistStorage: StateStorage = isTauri()
+  ? shellStorage
+  : localStorage;
+
+/** Every key in the shell's storage, or those starting with `prefix` */
+export const listStorageKeys = (
+  prefix?: string
+): Promise<StorageKeyStat[]> =>
+  enqueue(() => invoke("storage_list", { prefix }));
+
+/**
+ * Everything in localStorage and, in the desktop app, the shell's storage,
+ * whose values win since they're what the stores read. For backups.
+ */
+export async function readAllStorage(): Promise<Record<string, string>> {
+  const entries = localStorageEntries();
+  if (isTauri()) {
+    for (const { key } of await listStorageKeys()) {
+      const value = await shellStorage.getItem(key);
+      if (value !== null) entries[key] = value;
+    }
+  }
+  return entries;
+}
+
+/**
+ * Copy `keys` from localStorage to the shell's storage, removing those
+ * localStorage doesn't have, after a restore wrote them there. Does
+ * nothing outside the desktop app.
+ */
+export async function syncShellStorage(keys: Iterable<string>): Promise<void> {
+  if (!isTauri()) return;
+  for (const key of new Set(keys)) {
+    const value = localStorage.getItem(key);
+    await (value === null
+      ? shellStorage.removeItem(key)
+      : shellStorage.setItem(key, value));
+  }
+}
+
+/**
+ * Remove every key from the shell's storage except `keep`, alongside
+ * clearing localStorage. Does nothing outside the desktop app.
+ */
+export async function clearShellStorage(keep: string[] = []): Promise<void> {
+  if (!isTauri()) return;
+  for (const { key } of await listStorageKeys()) {
+    if (!keep.includes(key)) await shellStorage.removeItem(key);
+  }
+}
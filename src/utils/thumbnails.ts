@@ -0,0 +1,78 @@
+import { isTauri } from "@/utils/platform";
+
+/**
+ * Client for the desktop shell's thumbnailer (see
+ * src-tauri/src/thumbnail.rs). Files on disk are thumbnailed by path;
+ * images kept in IndexedDB are uploaded once per version and then
+ * served from the shell's cache.
+ */
+
+/** URI scheme the shell serves thumbnails on */
+const SCHEME = "syaos-thumb";
+
+/** Header the shell reads the source JSON from in `thumbnail_put` */
+const SOURCE_HEADER = "x-syaos-thumbnail";
+
+/** Device pixels needed to show an image `cssSize` CSS pixels wide */
+const pixelsFor = (cssSize: number): number =>
+  Math.ceil(cssSize * (window.devicePixelRatio || 1));
+
+/**
+ * URL of a thumbnail of the image at `path` in syaOS Home or on a mounted
+ * volume, or null outside the desktop app. `modifiedAt` only keeps the
+ * webview from reusing an outdated response; the shell checks the file.
+ */
+export async function fileThumbnailUrl(
+  path: string,
+  cssSize: number,
+  modifiedAt?: number
+): Promise<string | null> {
+  if (!isTauri()) return null;
+  const { convertFileSrc } = await import("@tauri-apps/api/core");
+  const url = convertFileSrc(`fs/${pixelsFor(cssSize)}${path}`, SCHEME);
+  return modifiedAt ? `${url}?v=${Math.trunc(modifiedAt)}` : url;
+}
+
+/**
+ * URL of a thumbnail of an image the shell can't read itself, stored
+ * under `key` (e.g. `image:<uuid>`). `load` is only called when the shell
+ * has no thumbnails for this `version` yet. Resolves to null outside the
+ * desktop app or when the image can't be thumbnailed.
+ */
+export async function blobThumbnailUrl(
+  key: string,
+  version: number,
+  cssSize: number,
+  load: () => Promise<Blob | null | undefined>
+): Promise<string | null> {
+  if (!isTauri()) return null;
+  const size = pixelsFor(cssSize);
+  version = Math.trunc(version);
+  try {
+    const core = await import("@tauri-apps/api/core");
+    const cached = await core.invoke<boolean>("thumbnail_cached", {
+      key,
+      version,
+      size,
+    });
+    if (!cached) {
+      const blob = await load();
+      if (!blob) return null;
+      await core.invoke(
+        "thumbnail_put",
+        new Uint8Array(await blob.arrayBuffer()),
+        {
+          headers: {
+            [SOURCE_HEADER]: encodeURIComponent(
+              JSON.stringify({ key, version })
+            ),
+          },
+        }
+      );
+    }
+    return `${core.convertFileSrc(`blob/${size}/${key}`, SCHEME)}?v=${version}`;
+  } catch (error) {
+    console.error(`[thumbnails] No thumbnail for ${key}:`, error);
+    return null;
+  }
+}